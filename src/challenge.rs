@@ -0,0 +1,50 @@
+use cosmwasm_std::{Addr, Uint128};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::error::ChallengeRejectedReason;
+
+/// Which challenges a player accepts in `CreateMatch`. The default policy
+/// accepts every challenge.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq, JsonSchema)]
+pub struct ChallengePolicy {
+    pub blocked: Vec<Addr>,
+    // Only these challengers, if set
+    pub allow_only: Option<Vec<Addr>>,
+    // Challenger's rating
+    pub min_rating: Option<u32>,
+    // Bounds on the stake, in the contract's bet denomination
+    pub min_bet: Option<Uint128>,
+    pub max_bet: Option<Uint128>,
+}
+
+impl ChallengePolicy {
+    pub fn check(
+        &self,
+        challenger: &Addr,
+        rating: u32,
+        bet: Uint128,
+    ) -> Result<(), ChallengeRejectedReason> {
+        if self.blocked.contains(challenger) {
+            return Err(ChallengeRejectedReason::Blocked);
+        }
+        if let Some(allowed) = &self.allow_only {
+            if !allowed.contains(challenger) {
+                return Err(ChallengeRejectedReason::NotAllowed);
+            }
+        }
+        if self
+            .min_rating
+            .is_some_and(|min_rating| rating < min_rating)
+        {
+            return Err(ChallengeRejectedReason::RatingTooLow);
+        }
+        if self.min_bet.is_some_and(|min_bet| bet < min_bet) {
+            return Err(ChallengeRejectedReason::BetTooLow);
+        }
+        if self.max_bet.is_some_and(|max_bet| bet > max_bet) {
+            return Err(ChallengeRejectedReason::BetTooHigh);
+        }
+        Ok(())
+    }
+}
@@ -1,24 +1,83 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    ensure_eq, ensure_ne, Addr, BankMsg, Binary, Coin, CosmosMsg, Deps, DepsMut, Env, Event,
-    MessageInfo, Response, StdResult, SubMsg, Uint128,
+    ensure, ensure_eq, ensure_ne, to_json_binary, Addr, BankMsg, Binary, Coin, CosmosMsg, Deps,
+    DepsMut, Env, Event, IbcMsg, MessageInfo, Order, Reply, Response, StdAck, StdError, StdResult,
+    SubMsg, SubMsgResult, Uint128, WasmMsg,
 };
-use cozy_chess::Move;
+use cozy_chess::{Board, GameStatus, Move};
 use cw2::{ensure_from_older_version, set_contract_version};
+use cw_storage_plus::Bound;
 use sha2::{Digest, Sha256};
 
-use crate::error::{ContractError, InvalidBetReason};
-use crate::game::{Match, MatchState, NextMove, MOVE_FEN_LENGTH};
-use crate::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg};
+use crate::challenge::ChallengePolicy;
+use crate::eco::MAX_OPENING_PLIES;
+use crate::error::{
+    ContractError, InvalidBetReason, InvalidPuzzleReason, InvalidTeamMatchReason,
+    InvalidTournamentReason,
+};
+use crate::game::{
+    Adjudication, Match, MatchResult, MatchState, MatchStyle, NextMove, PlayedMove,
+    MAX_CONDITIONAL_LINES, MAX_CONDITIONAL_PLIES, MOVE_FEN_LENGTH,
+};
+use crate::ibc::{self, ibc_timeout, is_bech32_address, IbcEscrow, IbcOpponent, IbcPeer};
+use crate::market::{settle, Market, SideBet, SideBetConfig, HOUSE_FEE_TOTAL, MAX_SIDE_BETTORS};
+use crate::migrations::{self, DEFAULT_MIGRATION_LIMIT};
+use crate::msg::{
+    BotExecuteMsg, BracketResponse, ChessPacket, ExecuteMsg, HeadToHeadResponse, HookExecuteMsg,
+    HooksResponse, IbcPeersResponse, InstantiateMsg, MatchResponse, MigrateMsg,
+    PlayerStatsResponse, QueryMsg, SeasonLeaderboardResponse, StandingsResponse,
+    ValidateLineResponse, VotesResponse,
+};
+use crate::notation::to_san;
+use crate::profile::Profile;
+use crate::puzzle::{
+    key_move_forces_mate, solution_commitment, Puzzle, PuzzleId, SearchLimitExceeded,
+    SolutionCommitment, MAX_MATE_IN, MAX_SEARCH_NODES, REVEAL_WINDOW,
+};
+use crate::rating::{update_ratings, DEFAULT_RATING};
+use crate::season::{
+    keep_leaders, rank_key, rank_season, Season, SeasonClose, SeasonId, SeasonStanding,
+    DEFAULT_END_SEASON_LIMIT, DEFAULT_LEADERBOARD_LIMIT, MAX_LEADERBOARD_LIMIT,
+};
 use crate::state::{
-    increment_nonce, MatchId, ADMIN, MATCHES, MATCH_IDS, MIN_BET, NEXT_NONCE, PLAYER_MATCHES,
+    increment_nonce, next_puzzle_id, next_season_id, next_team_match_id, next_tournament_id,
+    FinishedMatch, MatchId, MoveKey, ADMIN, ARBITER, BOT_CALLBACKS, BRACKET, BRACKET_MATCHES,
+    CHALLENGE_POLICIES, CONDITIONAL_MOVES, CURRENT_SEASON, DRAW_OFFERS, FINISHED_BY_HEIGHT,
+    FINISHED_MATCHES, FINISHED_PRUNE_LIMIT, HEAD_TO_HEAD, HOOKS, IBC_CHANNELS, IBC_ESCROWS,
+    IBC_OPPONENTS, IBC_PEERS, MARKETS, MATCHES, MATCH_ARBITERS, MATCH_IDS, MATCH_MOVES, MIN_BET,
+    MOVE_KEYS, NEXT_NONCE, NICKNAMES, PLAYER_MATCHES, PLAYER_STATS, PROFILES, PUZZLES, RATINGS,
+    REMATCH_WINDOW, SEASONS, SEASON_CLOSE, SEASON_RANKING, SEASON_STANDINGS, SIDE_BETS,
+    SIDE_BET_CONFIG, SOLUTION_COMMITMENTS, STANDINGS, TAKEBACK_REQUESTS, TEAM_BOARDS, TEAM_MATCHES,
+    TOURNAMENTS, VOTE_GROUPS,
+};
+use crate::stats::pair_key;
+use crate::team::{
+    Team, TeamBoard, TeamMatch, TeamMatchId, TeamMatchState, TeamTiebreak, MAX_BOARDS,
 };
+use crate::tournament::{
+    bracket_order, bracket_rounds, game_scores, greedy_swiss_pairings, knockout_ranking,
+    prize_shares, rank_standings, round_robin_pairings, seed_commitment, shuffle, swiss_pairings,
+    BracketTie, Color, PairingLimitExceeded, Seeding, Standing, TieGame, Tournament, TournamentId,
+    TournamentKind, TournamentState, PRIZE_SPLIT_TOTAL,
+};
+use crate::vote::{VoteGroup, MAX_VOTERS};
 
 // Version info for migration info
 const CONTRACT_NAME: &str = "crates.io:cw-chess";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+// Reply id of `YourTurn` notifications sent to bot contracts
+pub const BOT_CALLBACK_REPLY_ID: u64 = 1;
+// Gas a bot may spend handling a `YourTurn` notification
+pub const BOT_CALLBACK_GAS_LIMIT: u64 = 500_000;
+// Reply id of `MatchFinishedHook` notifications sent to hooks
+pub const HOOK_REPLY_ID: u64 = 2;
+// Gas a hook may spend handling a `MatchFinishedHook` notification
+pub const HOOK_GAS_LIMIT: u64 = 500_000;
+// Reply id of the `ReceiveIbcPacket` messages handling received packets
+pub const IBC_RECEIVE_REPLY_ID: u64 = 3;
+
 pub fn instantiate(
     deps: DepsMut,
     _env: Env,
@@ -44,11 +103,188 @@ pub fn execute(
 ) -> Result<Response, ContractError> {
     use ExecuteMsg::*;
 
+    // Matches can't be touched while some are still stored in an older layout
+    if !matches!(msg, MigrateMatches { .. }) {
+        ensure!(
+            !migrations::in_progress(deps.storage),
+            ContractError::MigrationInProgress {}
+        );
+    }
+
     match msg {
-        CreateMatch { opponent } => exec::create_match(deps, info, opponent),
+        CreateMatch {
+            opponent,
+            rated,
+            arbiter,
+        } => exec::create_match(deps, info, opponent, rated, arbiter),
         AbortMatch { match_id } => exec::abort_match(deps, info, match_id),
-        JoinMatch { match_id } => exec::join_match(deps, env, info, match_id),
+        JoinMatch {
+            match_id,
+            expected_arbiter,
+        } => exec::join_match(deps, env, info, match_id, expected_arbiter),
         MakeMove { match_id, move_fen } => exec::make_move(deps, env, info, match_id, move_fen),
+        CreateTournament {
+            kind,
+            entry_fee,
+            max_players,
+            time_control,
+            prize_split,
+        } => exec::create_tournament(
+            deps,
+            info,
+            kind,
+            entry_fee,
+            max_players,
+            time_control,
+            prize_split,
+        ),
+        RegisterTournament { tournament_id } => {
+            exec::register_tournament(deps, info, tournament_id)
+        }
+        StartTournament {
+            tournament_id,
+            seed,
+        } => exec::start_tournament(deps, env, info, tournament_id, seed),
+        PairNextRound { tournament_id } => exec::pair_next_round(deps, env, info, tournament_id),
+        CancelTournament { tournament_id } => exec::cancel_tournament(deps, info, tournament_id),
+        Resign { match_id } => exec::resign(deps, env, info, match_id),
+        OfferDraw { match_id } => exec::offer_draw(deps, env, info, match_id),
+        RequestTakeback { match_id, plies } => exec::request_takeback(deps, info, match_id, plies),
+        AcceptTakeback { match_id } => exec::accept_takeback(deps, info, match_id),
+        SetConditionalMoves { match_id, lines } => {
+            exec::set_conditional_moves(deps, info, match_id, lines)
+        }
+        GrantMoveKey {
+            delegate,
+            expires,
+            match_id,
+        } => exec::grant_move_key(deps, env, info, delegate, expires, match_id),
+        RevokeMoveKey { delegate } => exec::revoke_move_key(deps, info, delegate),
+        RegisterBotCallback {} => exec::register_bot_callback(deps, info),
+        UnregisterBotCallback {} => exec::unregister_bot_callback(deps, info),
+        OfferRematch { match_id } => exec::offer_rematch(deps, env, info, match_id),
+        AcceptRematch { match_id } => exec::accept_rematch(deps, env, info, match_id),
+        CancelRematch { match_id } => exec::cancel_rematch(deps, info, match_id),
+        UpdateArbiter { arbiter } => exec::update_arbiter(deps, info, arbiter),
+        Adjudicate {
+            match_id,
+            result,
+            reason,
+        } => exec::adjudicate(deps, env, info, match_id, result, reason),
+        MigrateMatches { limit } => exec::migrate_matches(deps, limit),
+        AddHook { addr } => exec::add_hook(deps, info, addr),
+        RemoveHook { addr } => exec::remove_hook(deps, info, addr),
+        PlaceSideBet { match_id, outcome } => exec::place_side_bet(deps, info, match_id, outcome),
+        UpdateSideBetConfig {
+            close_after_plies,
+            house_fee_percent,
+        } => exec::update_side_bet_config(deps, info, close_after_plies, house_fee_percent),
+        SetProfile {
+            nickname,
+            country,
+            avatar_uri,
+            bio,
+        } => exec::set_profile(
+            deps,
+            info,
+            Profile {
+                nickname,
+                country,
+                avatar_uri,
+                bio,
+            },
+        ),
+        StartSeason {
+            prize_pool,
+            prize_split,
+        } => exec::start_season(deps, env, info, prize_pool, prize_split),
+        EndSeason { limit } => exec::end_season(deps, env, info, limit),
+        PostPuzzle {
+            fen,
+            mate_in,
+            reward,
+        } => exec::post_puzzle(deps, info, fen, mate_in, reward),
+        CommitSolution {
+            puzzle_id,
+            commitment,
+        } => exec::commit_solution(deps, env, info, puzzle_id, commitment),
+        SolvePuzzle {
+            puzzle_id,
+            line,
+            salt,
+        } => exec::solve_puzzle(deps, env, info, puzzle_id, line, salt),
+        WithdrawPuzzle { puzzle_id } => exec::withdraw_puzzle(deps, env, info, puzzle_id),
+        SetChallengePolicy {
+            blocked,
+            allow_only,
+            min_rating,
+            min_bet,
+            max_bet,
+        } => exec::set_challenge_policy(
+            deps,
+            info,
+            ChallengePolicy {
+                blocked,
+                allow_only,
+                min_rating,
+                min_bet,
+                max_bet,
+            },
+        ),
+        CreateVoteMatch {
+            voters,
+            voters_color,
+            voting_period,
+        } => exec::create_vote_match(deps, env, info, voters, voters_color, voting_period),
+        VoteMove { match_id, move_fen } => exec::vote_move(deps, env, info, match_id, move_fen),
+        FinalizeVote { match_id } => exec::finalize_vote(deps, env, info, match_id),
+        CreateTeamMatch {
+            team_a,
+            team_b,
+            boards,
+            stake,
+            tiebreak,
+        } => exec::create_team_match(deps, info, team_a, team_b, boards, stake, tiebreak),
+        AcceptTeamMatch { team_match_id } => {
+            exec::accept_team_match(deps, env, info, team_match_id)
+        }
+        ConfirmTeamBoard { team_match_id } => {
+            exec::confirm_team_board(deps, env, info, team_match_id)
+        }
+        AbortTeamMatch { team_match_id } => exec::abort_team_match(deps, info, team_match_id),
+        CreateIbcMatch {
+            channel_id,
+            opponent,
+            opponent_bet,
+            payout_address,
+        } => exec::create_ibc_match(
+            deps,
+            env,
+            info,
+            channel_id,
+            opponent,
+            opponent_bet,
+            payout_address,
+        ),
+        JoinIbcMatch {
+            channel_id,
+            match_id,
+            payout_address,
+        } => exec::join_ibc_match(deps, env, info, channel_id, match_id, payout_address),
+        RelayMove {
+            channel_id,
+            match_id,
+            move_fen,
+        } => exec::relay_move(deps, env, info, channel_id, match_id, move_fen),
+        AddIbcPeer {
+            connection_id,
+            port_id,
+        } => exec::add_ibc_peer(deps, info, connection_id, port_id),
+        RemoveIbcPeer {
+            connection_id,
+            port_id,
+        } => exec::remove_ibc_peer(deps, info, connection_id, port_id),
+        ReceiveIbcPacket { channel_id, data } => ibc::receive(deps, env, info, channel_id, data),
     }
 }
 
@@ -58,25 +294,35 @@ pub(crate) mod exec {
     pub fn create_match(
         deps: DepsMut,
         info: MessageInfo,
-        opponent: Addr,
+        opponent: String,
+        rated: Option<bool>,
+        arbiter: Option<Addr>,
     ) -> Result<Response, ContractError> {
         let challenger = info.sender;
-        let opponent = validate_address(deps.api, opponent.as_str())?;
+        let opponent = resolve_opponent(&deps, &opponent)?;
         validate_match_players(&challenger, &opponent)?;
+        let arbiter = arbiter
+            .map(|arbiter| validate_address(deps.api, arbiter.as_str()))
+            .transpose()?;
+        if let Some(arbiter) = &arbiter {
+            ensure!(
+                *arbiter != challenger && *arbiter != opponent,
+                ContractError::Unauthorized {}
+            );
+        }
 
         let min_bet = MIN_BET.load(deps.storage)?;
         let bet = validate_bet(&info.funds, &Coin::new(min_bet.0.into(), min_bet.1))?;
+        check_challenge_policy(deps.storage, &challenger, &opponent, bet.amount)?;
 
         let nonce = NEXT_NONCE.load(deps.storage)?;
 
-        let new_match = Match::new(challenger.clone(), opponent.clone(), nonce, bet);
-        let match_id = match_id(&challenger, &opponent, nonce);
-
-        save_match_state(deps.storage, match_id, &new_match)?;
-        save_player_match(deps.storage, &challenger, match_id)?;
-        save_player_match(deps.storage, &opponent, match_id)?;
-        save_match_id(deps.storage, nonce, match_id)?;
-        increment_nonce(deps.storage)?;
+        let mut new_match = Match::new(challenger.clone(), opponent.clone(), nonce, bet);
+        new_match.rated = rated.unwrap_or(true);
+        let match_id = register_match(deps.storage, &new_match)?;
+        if let Some(arbiter) = &arbiter {
+            MATCH_ARBITERS.save(deps.storage, match_id, arbiter)?;
+        }
 
         Ok(Response::new()
             .add_attribute("action", "create_match")
@@ -89,6 +335,22 @@ pub(crate) mod exec {
             ))
     }
 
+    /// Refuses a challenge from `challenger` that `opponent`'s policy rejects.
+    fn check_challenge_policy(
+        storage: &dyn cosmwasm_std::Storage,
+        challenger: &Addr,
+        opponent: &Addr,
+        bet: Uint128,
+    ) -> Result<(), ContractError> {
+        let Some(policy) = CHALLENGE_POLICIES.may_load(storage, opponent)? else {
+            return Ok(());
+        };
+        let rating = load_rating(storage, challenger)?;
+        policy
+            .check(challenger, rating, bet)
+            .map_err(|reason| ContractError::ChallengeRejected { reason })
+    }
+
     pub fn abort_match(
         deps: DepsMut,
         info: MessageInfo,
@@ -98,117 +360,3347 @@ pub(crate) mod exec {
         let match_id = validate_match_id(&match_id)?;
         let chess_match = lookup_match(&deps, match_id)?;
         validate_match_creator(&chess_match, &challenger)?;
+        // Team boards are withdrawn with their team match
+        ensure!(
+            !TEAM_BOARDS.has(deps.storage, match_id),
+            ContractError::Unauthorized {}
+        );
         ensure_awaiting_opponent(&chess_match)?;
 
         clean_match_state(deps.storage, match_id, &chess_match);
 
-        let mut msgs: Vec<CosmosMsg> = vec![];
+        let mut msgs: Vec<SubMsg> = vec![];
+        let mut events =
+            vec![Event::new("match_aborted").add_attribute("match_id", hex::encode(match_id))];
         refund_challenger(&mut msgs, &chess_match);
+        settle_side_bets(deps.storage, match_id, None, &mut msgs, &mut events)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "abort_match")
+            .add_attribute("sender", &challenger)
+            .add_events(events)
+            .add_submessages(msgs))
+    }
+
+    pub fn join_match(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        match_id: String,
+        expected_arbiter: Option<Addr>,
+    ) -> Result<Response, ContractError> {
+        let opponent = info.sender;
+
+        let match_id = validate_match_id(&match_id)?;
+        let mut chess_match = lookup_match(&deps, match_id)?;
+        validate_match_opponent(&opponent, &chess_match.opponent)?;
+        // Team boards start once every board player confirmed
+        ensure!(
+            !TEAM_BOARDS.has(deps.storage, match_id),
+            ContractError::Unauthorized {}
+        );
+        // The opponent agrees to the arbiter the challenger picked
+        ensure!(
+            MATCH_ARBITERS.may_load(deps.storage, match_id)? == expected_arbiter,
+            ContractError::ArbiterMismatch {}
+        );
+
+        let bet = validate_bet(&info.funds, &chess_match.bet)?;
+        validate_opponent_bet(&chess_match.bet.amount, &bet.amount)?;
+        ensure_awaiting_opponent(&chess_match)?;
+
+        chess_match.start(env.block.height);
+        save_match_state(deps.storage, match_id, &chess_match)?;
+        let notification = notify_bot(deps.storage, match_id, &chess_match, None)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "join_match")
+            .add_attribute("sender", &opponent)
+            .add_event(Event::new("match_started").add_attribute("match_id", hex::encode(match_id)))
+            .add_submessages(notification))
+    }
+
+    pub fn make_move(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        match_id: String,
+        move_fen: String,
+    ) -> Result<Response, ContractError> {
+        let signer = info.sender;
+        let match_id = validate_match_id(&match_id)?;
+        validate_fen_move(&move_fen)?;
+
+        let mut chess_match = lookup_match(&deps, match_id)?;
+        let player = resolve_player(deps.storage, &env, match_id, &chess_match, &signer)?;
+        validate_match_state(&chess_match, &player)?;
+
+        let mov = decode_move(&move_fen)?;
+        record_move(deps.storage, &env, match_id, &mut chess_match, &mov)?;
+
+        let (events, submsgs) = conclude_move(
+            deps.storage,
+            &env,
+            match_id,
+            chess_match,
+            player,
+            &signer,
+            move_fen,
+        )?;
+        Ok(Response::new()
+            .add_attribute("action", "make_move")
+            .add_attribute("sender", &signer)
+            .add_events(events)
+            .add_submessages(submsgs))
+    }
+
+    /// Plays `mov` and records it in the move history of the match, writing
+    /// only its own ply. The opening is classified while the game can still
+    /// follow one.
+    fn record_move(
+        storage: &mut dyn cosmwasm_std::Storage,
+        env: &Env,
+        match_id: MatchId,
+        chess_match: &mut Match,
+        mov: &Move,
+    ) -> Result<(), ContractError> {
+        let ply = chess_match.plies();
+        let played = chess_match
+            .play_move(mov, env.block.height)
+            .map_err(|_| ContractError::IllegalMove {})?;
+        MATCH_MOVES.save(storage, (match_id.as_slice(), ply), &played)?;
+        if (ply as usize) < MAX_OPENING_PLIES {
+            let history = load_history(storage, match_id, ply + 1)?;
+            chess_match.classify_opening(&history);
+        }
+        Ok(())
+    }
+
+    /// The first `plies` moves of the history of a match, in order.
+    fn load_history(
+        storage: &dyn cosmwasm_std::Storage,
+        match_id: MatchId,
+        plies: u32,
+    ) -> StdResult<Vec<PlayedMove>> {
+        MATCH_MOVES
+            .prefix(match_id.as_slice())
+            .range(
+                storage,
+                None,
+                Some(Bound::exclusive(plies)),
+                Order::Ascending,
+            )
+            .map(|entry| entry.map(|(_, played)| played))
+            .collect()
+    }
+
+    /// Follows up on a move `player` just played: plays the opponent's queued
+    /// reply, if any, then settles the match if it is over or saves it.
+    fn conclude_move(
+        storage: &mut dyn cosmwasm_std::Storage,
+        env: &Env,
+        match_id: MatchId,
+        mut chess_match: Match,
+        player: Addr,
+        signer: &Addr,
+        move_fen: String,
+    ) -> Result<(Vec<Event>, Vec<SubMsg>), ContractError> {
+        decline_offers(storage, match_id, &player)?;
+
+        let mut msgs: Vec<SubMsg> = vec![];
+        let mut events = vec![Event::new("move_executed")
+            .add_attribute("match_id", hex::encode(match_id))
+            .add_attribute("player", &player)
+            .add_attribute("signer", signer)
+            .add_attribute("move", &move_fen)];
+
+        // The opponent may have queued a reply to this move
+        let (mut mover, mut last_move) = (player, move_fen);
+        if let Some((replier, reply)) =
+            play_conditional_move(storage, env, match_id, &mut chess_match, &last_move)?
+        {
+            decline_offers(storage, match_id, &replier)?;
+            events.push(
+                Event::new("move_executed")
+                    .add_attribute("match_id", hex::encode(match_id))
+                    .add_attribute("player", &replier)
+                    .add_attribute("signer", &env.contract.address)
+                    .add_attribute("move", &reply),
+            );
+            (mover, last_move) = (replier, reply);
+        }
+
+        if chess_match.state == MatchState::Won {
+            // Match was won with move that was just executed
+            events.push(
+                Event::new("match_won")
+                    .add_attribute("match_id", hex::encode(match_id))
+                    .add_attribute("winner", &mover)
+                    .add_attribute("board", chess_match.board())
+                    .add_attributes(opening_attributes(&chess_match)),
+            );
+
+            let result = if mover == chess_match.challenger {
+                MatchResult::WhiteWins
+            } else {
+                MatchResult::BlackWins
+            };
+            settle_match(
+                storage,
+                env,
+                match_id,
+                &chess_match,
+                &result,
+                &mut msgs,
+                &mut events,
+            )?;
+        } else if chess_match.state == MatchState::Drawn {
+            // Match drawn, refund deposits to both players
+            events.push(
+                Event::new("match_drawn")
+                    .add_attribute("match_id", hex::encode(match_id))
+                    .add_attribute("board", chess_match.board())
+                    .add_attributes(opening_attributes(&chess_match)),
+            );
+
+            settle_match(
+                storage,
+                env,
+                match_id,
+                &chess_match,
+                &MatchResult::Draw,
+                &mut msgs,
+                &mut events,
+            )?;
+        } else {
+            // match still ongoing, update on-chain board
+            save_match_state(storage, match_id, &chess_match)?;
+        }
+
+        msgs.extend(notify_bot(
+            storage,
+            match_id,
+            &chess_match,
+            Some(last_move),
+        )?);
+        Ok((events, msgs))
+    }
+
+    /// Starts a match between the sender and a group of voters, playing
+    /// `voters_color` under the contract's address. Vote matches are neither
+    /// staked nor rated.
+    pub fn create_vote_match(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        voters: Vec<Addr>,
+        voters_color: Color,
+        voting_period: u64,
+    ) -> Result<Response, ContractError> {
+        let champion = info.sender;
+        ensure!(
+            !voters.is_empty() && voters.len() <= MAX_VOTERS && voting_period > 0,
+            ContractError::InvalidVoteGroup {}
+        );
+        let mut members: Vec<Addr> = vec![];
+        for voter in voters {
+            let voter = validate_address(deps.api, voter.as_str())?;
+            ensure!(
+                voter != champion && !members.contains(&voter),
+                ContractError::InvalidVoteGroup {}
+            );
+            members.push(voter);
+        }
+
+        let crowd = env.contract.address.clone();
+        let (white, black) = match voters_color {
+            Color::White => (crowd, champion.clone()),
+            Color::Black => (champion.clone(), crowd),
+        };
+        let (_, denom) = MIN_BET.load(deps.storage)?;
+        let nonce = NEXT_NONCE.load(deps.storage)?;
+        let mut vote_match = Match::new(white, black, nonce, Coin::new(0, denom));
+        vote_match.rated = false;
+        vote_match.start(env.block.height);
+        let match_id = register_match(deps.storage, &vote_match)?;
+        let group = VoteGroup::new(members, voters_color, voting_period);
+        VOTE_GROUPS.save(deps.storage, match_id, &group)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "create_vote_match")
+            .add_attribute("sender", &champion)
+            .add_event(
+                Event::new("vote_match_created")
+                    .add_attribute("champion", champion)
+                    .add_attribute("match_id", hex::encode(match_id))
+                    .add_attribute("voting_ends", group.voting_ends(&vote_match).to_string()),
+            ))
+    }
+
+    /// Votes for the group's next move, replacing the voter's earlier vote.
+    pub fn vote_move(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        match_id: String,
+        move_fen: String,
+    ) -> Result<Response, ContractError> {
+        let voter = info.sender;
+        let match_id = validate_match_id(&match_id)?;
+        validate_fen_move(&move_fen)?;
+
+        let chess_match = lookup_match(&deps, match_id)?;
+        let mut group = lookup_vote_group(&deps, match_id)?;
+        ensure!(group.voters.contains(&voter), ContractError::NotAVoter {});
+        validate_match_state(&chess_match, &env.contract.address)?;
+        ensure!(
+            env.block.height < group.voting_ends(&chess_match),
+            ContractError::VotingClosed {}
+        );
+
+        let mov = decode_move(&move_fen)?;
+        chess_match
+            .position()
+            .try_play(mov)
+            .map_err(|_| ContractError::IllegalMove {})?;
+        group.vote(&voter, mov.to_string());
+        VOTE_GROUPS.save(deps.storage, match_id, &group)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "vote_move")
+            .add_attribute("sender", &voter)
+            .add_event(
+                Event::new("move_voted")
+                    .add_attribute("match_id", hex::encode(match_id))
+                    .add_attribute("voter", voter)
+                    .add_attribute("move", mov.to_string()),
+            ))
+    }
+
+    /// Plays the group's most voted move once voting closed, anyone can call it.
+    /// A group that cast no vote loses the match, as a player would on time.
+    pub fn finalize_vote(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        match_id: String,
+    ) -> Result<Response, ContractError> {
+        let signer = info.sender;
+        let match_id = validate_match_id(&match_id)?;
+        let mut chess_match = lookup_match(&deps, match_id)?;
+        let mut group = lookup_vote_group(&deps, match_id)?;
+        let crowd = env.contract.address.clone();
+        validate_match_state(&chess_match, &crowd)?;
+        ensure!(
+            env.block.height >= group.voting_ends(&chess_match),
+            ContractError::VotingStillOpen {}
+        );
+
+        let tally = group.tally();
+        let Some((move_fen, votes)) = tally.first().cloned() else {
+            let (champion, result) = match group.color {
+                Color::White => (&chess_match.opponent, MatchResult::BlackWins),
+                Color::Black => (&chess_match.challenger, MatchResult::WhiteWins),
+            };
+            let mut msgs: Vec<SubMsg> = vec![];
+            let mut events = vec![Event::new("match_won")
+                .add_attribute("match_id", hex::encode(match_id))
+                .add_attribute("winner", champion)
+                .add_attribute("board", chess_match.board())
+                .add_attribute("reason", "no_votes")
+                .add_attributes(opening_attributes(&chess_match))];
+            settle_match(
+                deps.storage,
+                &env,
+                match_id,
+                &chess_match,
+                &result,
+                &mut msgs,
+                &mut events,
+            )?;
+            return Ok(Response::new()
+                .add_attribute("action", "finalize_vote")
+                .add_attribute("sender", &signer)
+                .add_events(events)
+                .add_submessages(msgs));
+        };
+        let mov = decode_move(&move_fen)?;
+        record_move(deps.storage, &env, match_id, &mut chess_match, &mov)?;
+        group.votes.clear();
+        VOTE_GROUPS.save(deps.storage, match_id, &group)?;
+
+        let vote_event = Event::new("vote_finalized")
+            .add_attribute("match_id", hex::encode(match_id))
+            .add_attribute("move", &move_fen)
+            .add_attribute("votes", votes.to_string());
+        let (events, submsgs) = conclude_move(
+            deps.storage,
+            &env,
+            match_id,
+            chess_match,
+            crowd,
+            &signer,
+            move_fen,
+        )?;
+        Ok(Response::new()
+            .add_attribute("action", "finalize_vote")
+            .add_attribute("sender", &signer)
+            .add_event(vote_event)
+            .add_events(events)
+            .add_submessages(submsgs))
+    }
+
+    fn lookup_vote_group(deps: &DepsMut, match_id: MatchId) -> Result<VoteGroup, ContractError> {
+        VOTE_GROUPS
+            .may_load(deps.storage, match_id)?
+            .ok_or(ContractError::NotAVoteMatch {})
+    }
+
+    pub fn resign(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        match_id: String,
+    ) -> Result<Response, ContractError> {
+        let signer = info.sender;
+        let match_id = validate_match_id(&match_id)?;
+        let chess_match = lookup_match(&deps, match_id)?;
+        let player = resolve_player(deps.storage, &env, match_id, &chess_match, &signer)?;
+        ensure_ongoing(&chess_match)?;
+
+        let (winner, result) = if player == chess_match.challenger {
+            (&chess_match.opponent, MatchResult::BlackWins)
+        } else if player == chess_match.opponent {
+            (&chess_match.challenger, MatchResult::WhiteWins)
+        } else {
+            return Err(ContractError::Unauthorized {});
+        };
+
+        let mut msgs: Vec<SubMsg> = vec![];
+        let mut events = vec![Event::new("match_won")
+            .add_attribute("match_id", hex::encode(match_id))
+            .add_attribute("winner", winner)
+            .add_attribute("board", chess_match.board())
+            .add_attribute("reason", "resignation")
+            .add_attributes(opening_attributes(&chess_match))];
+        settle_match(
+            deps.storage,
+            &env,
+            match_id,
+            &chess_match,
+            &result,
+            &mut msgs,
+            &mut events,
+        )?;
+
+        Ok(Response::new()
+            .add_attribute("action", "resign")
+            .add_attribute("sender", &signer)
+            .add_attribute("player", &player)
+            .add_events(events)
+            .add_submessages(msgs))
+    }
+
+    /// Offers a draw, or accepts the opponent's standing offer.
+    pub fn offer_draw(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        match_id: String,
+    ) -> Result<Response, ContractError> {
+        let signer = info.sender;
+        let match_id = validate_match_id(&match_id)?;
+        let chess_match = lookup_match(&deps, match_id)?;
+        let player = resolve_player(deps.storage, &env, match_id, &chess_match, &signer)?;
+        ensure_ongoing(&chess_match)?;
+        ensure!(
+            player == chess_match.challenger || player == chess_match.opponent,
+            ContractError::Unauthorized {}
+        );
+
+        let mut msgs: Vec<SubMsg> = vec![];
+        let mut events = vec![];
+        match DRAW_OFFERS.may_load(deps.storage, match_id)? {
+            Some(offerer) if offerer != player => {
+                events.push(
+                    Event::new("match_drawn")
+                        .add_attribute("match_id", hex::encode(match_id))
+                        .add_attribute("board", chess_match.board())
+                        .add_attribute("reason", "agreement")
+                        .add_attributes(opening_attributes(&chess_match)),
+                );
+                settle_match(
+                    deps.storage,
+                    &env,
+                    match_id,
+                    &chess_match,
+                    &MatchResult::Draw,
+                    &mut msgs,
+                    &mut events,
+                )?;
+            }
+            _ => {
+                DRAW_OFFERS.save(deps.storage, match_id, &player)?;
+                events.push(
+                    Event::new("draw_offered")
+                        .add_attribute("match_id", hex::encode(match_id))
+                        .add_attribute("player", &player),
+                );
+            }
+        }
+
+        Ok(Response::new()
+            .add_attribute("action", "offer_draw")
+            .add_attribute("sender", &signer)
+            .add_events(events)
+            .add_submessages(msgs))
+    }
+
+    /// Queues replies for the sender: each line alternates an expected opponent
+    /// move and the reply to play, starting from the current position. An empty
+    /// list clears them.
+    pub fn set_conditional_moves(
+        deps: DepsMut,
+        info: MessageInfo,
+        match_id: String,
+        lines: Vec<Vec<String>>,
+    ) -> Result<Response, ContractError> {
+        let player = info.sender;
+        let match_id = validate_match_id(&match_id)?;
+        let chess_match = lookup_match(&deps, match_id)?;
+        ensure_ongoing(&chess_match)?;
+        let opponent_to_move = match chess_match.state {
+            MatchState::OnGoing(NextMove::Whites) => player == chess_match.opponent,
+            MatchState::OnGoing(NextMove::Blacks) => player == chess_match.challenger,
+            _ => false,
+        };
+        ensure!(
+            player == chess_match.challenger || player == chess_match.opponent,
+            ContractError::Unauthorized {}
+        );
+        ensure!(
+            opponent_to_move || lines.is_empty(),
+            ContractError::NotYourTurn {}
+        );
+        ensure!(
+            lines.len() <= MAX_CONDITIONAL_LINES
+                && lines
+                    .iter()
+                    .all(|line| !line.is_empty() && line.len() <= MAX_CONDITIONAL_PLIES),
+            ContractError::InvalidConditionalMoves {}
+        );
+
+        for line in &lines {
+            let mut board = chess_match.position();
+            for move_fen in line {
+                validate_fen_move(move_fen)?;
+                board
+                    .try_play(decode_move(move_fen)?)
+                    .map_err(|_| ContractError::IllegalMove {})?;
+            }
+        }
+
+        let key = (match_id.as_slice(), &player);
+        if lines.is_empty() {
+            CONDITIONAL_MOVES.remove(deps.storage, key);
+        } else {
+            CONDITIONAL_MOVES.save(deps.storage, key, &lines)?;
+        }
+
+        Ok(Response::new()
+            .add_attribute("action", "set_conditional_moves")
+            .add_attribute("sender", &player)
+            .add_attribute("match_id", hex::encode(match_id))
+            .add_attribute("lines", lines.len().to_string()))
+    }
+
+    /// Plays the reply queued by the side to move for the opponent's
+    /// `last_move`, if any. Lines that don't follow the game are dropped.
+    fn play_conditional_move(
+        storage: &mut dyn cosmwasm_std::Storage,
+        env: &Env,
+        match_id: MatchId,
+        chess_match: &mut Match,
+        last_move: &str,
+    ) -> StdResult<Option<(Addr, String)>> {
+        let replier = match chess_match.state {
+            MatchState::OnGoing(NextMove::Whites) => chess_match.challenger.clone(),
+            MatchState::OnGoing(NextMove::Blacks) => chess_match.opponent.clone(),
+            _ => return Ok(None),
+        };
+        let key = (match_id.as_slice(), &replier);
+        let Some(lines) = CONDITIONAL_MOVES.may_load(storage, key)? else {
+            return Ok(None);
+        };
+        CONDITIONAL_MOVES.remove(storage, key);
+
+        let mut matching = lines
+            .into_iter()
+            .filter(|line| line.len() > 1 && line[0] == last_move);
+        let Some(line) = matching.next() else {
+            return Ok(None);
+        };
+        let reply = line[1].clone();
+        let Ok(mov) = Match::decode_move(&reply) else {
+            return Ok(None);
+        };
+        match record_move(storage, env, match_id, chess_match, &mov) {
+            Ok(()) => {}
+            Err(ContractError::Std(err)) => return Err(err),
+            Err(_) => return Ok(None),
+        }
+
+        let remaining: Vec<Vec<String>> = std::iter::once(line)
+            .chain(matching)
+            .filter(|line| line[1] == reply && line.len() > 2)
+            .map(|line| line[2..].to_vec())
+            .collect();
+        if !remaining.is_empty() {
+            CONDITIONAL_MOVES.save(storage, key, &remaining)?;
+        }
+        Ok(Some((replier, reply)))
+    }
+
+    /// Playing on declines the opponent's draw offer, and any takeback request.
+    fn decline_offers(
+        storage: &mut dyn cosmwasm_std::Storage,
+        match_id: MatchId,
+        player: &Addr,
+    ) -> StdResult<()> {
+        if DRAW_OFFERS.may_load(storage, match_id)?.as_ref() != Some(player) {
+            DRAW_OFFERS.remove(storage, match_id);
+        }
+        TAKEBACK_REQUESTS.remove(storage, match_id);
+        Ok(())
+    }
+
+    /// Attributes naming the opening of a finished match, when it is known.
+    fn opening_attributes(chess_match: &Match) -> Vec<(&'static str, String)> {
+        chess_match
+            .opening
+            .iter()
+            .flat_map(|opening| {
+                [
+                    ("eco", opening.eco.clone()),
+                    ("opening", opening.name.clone()),
+                ]
+            })
+            .collect()
+    }
+
+    pub fn request_takeback(
+        deps: DepsMut,
+        info: MessageInfo,
+        match_id: String,
+        plies: u32,
+    ) -> Result<Response, ContractError> {
+        let player = info.sender;
+        let match_id = validate_match_id(&match_id)?;
+        let chess_match = lookup_match(&deps, match_id)?;
+        ensure_ongoing(&chess_match)?;
+        ensure!(
+            player == chess_match.challenger || player == chess_match.opponent,
+            ContractError::Unauthorized {}
+        );
+        ensure!(
+            !chess_match.rated && chess_match.tournament.is_none(),
+            ContractError::TakebacksNotAllowed {}
+        );
+        ensure!(
+            plies > 0 && plies <= chess_match.plies(),
+            ContractError::InvalidTakeback {}
+        );
+
+        TAKEBACK_REQUESTS.save(deps.storage, match_id, &(player.clone(), plies))?;
+
+        Ok(Response::new()
+            .add_attribute("action", "request_takeback")
+            .add_attribute("sender", &player)
+            .add_event(
+                Event::new("takeback_requested")
+                    .add_attribute("match_id", hex::encode(match_id))
+                    .add_attribute("player", player)
+                    .add_attribute("plies", plies.to_string()),
+            ))
+    }
+
+    pub fn accept_takeback(
+        deps: DepsMut,
+        info: MessageInfo,
+        match_id: String,
+    ) -> Result<Response, ContractError> {
+        let player = info.sender;
+        let match_id = validate_match_id(&match_id)?;
+        let mut chess_match = lookup_match(&deps, match_id)?;
+        ensure_ongoing(&chess_match)?;
+        let (requester, plies) = TAKEBACK_REQUESTS
+            .may_load(deps.storage, match_id)?
+            .ok_or(ContractError::NoTakebackRequest {})?;
+        ensure!(
+            player != requester
+                && (player == chess_match.challenger || player == chess_match.opponent),
+            ContractError::Unauthorized {}
+        );
+
+        // Side betting doesn't reopen when the match is rewound
+        let config = SIDE_BET_CONFIG.may_load(deps.storage)?.unwrap_or_default();
+        if chess_match.plies() >= config.close_after_plies {
+            let mut market = MARKETS
+                .may_load(deps.storage, match_id)?
+                .unwrap_or_else(|| Market::new(&chess_match.bet.denom));
+            market.closed = true;
+            MARKETS.save(deps.storage, match_id, &market)?;
+        }
+
+        let played = chess_match.plies();
+        let kept = played.saturating_sub(plies);
+        let history = load_history(deps.storage, match_id, kept)?;
+        ensure!(
+            history.len() == kept as usize,
+            ContractError::InvalidTakeback {}
+        );
+        for ply in kept..played {
+            MATCH_MOVES.remove(deps.storage, (match_id.as_slice(), ply));
+        }
+        let chess_match = chess_match
+            .take_back(&history)
+            .map_err(|_| ContractError::InvalidTakeback {})?;
+        save_match_state(deps.storage, match_id, chess_match)?;
+        TAKEBACK_REQUESTS.remove(deps.storage, match_id);
+        DRAW_OFFERS.remove(deps.storage, match_id);
+        clear_conditional_moves(deps.storage, match_id, chess_match);
+        let notification = notify_bot(deps.storage, match_id, chess_match, None)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "accept_takeback")
+            .add_attribute("sender", &player)
+            .add_event(
+                Event::new("takeback_accepted")
+                    .add_attribute("match_id", hex::encode(match_id))
+                    .add_attribute("plies", plies.to_string())
+                    .add_attribute("board", chess_match.board()),
+            )
+            .add_submessages(notification))
+    }
+
+    pub fn grant_move_key(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        delegate: Addr,
+        expires: u64,
+        match_id: Option<String>,
+    ) -> Result<Response, ContractError> {
+        let grantor = info.sender;
+        let delegate = validate_address(deps.api, delegate.as_str())?;
+        ensure_ne!(grantor, delegate, ContractError::InvalidDelegate {});
+        ensure!(
+            expires > env.block.height,
+            ContractError::InvalidExpiration {}
+        );
+        let match_id = match match_id {
+            Some(match_id) => Some(hex::encode(validate_match_id(&match_id)?)),
+            None => None,
+        };
+
+        MOVE_KEYS.save(
+            deps.storage,
+            (&grantor, &delegate),
+            &MoveKey {
+                expires,
+                match_id: match_id.clone(),
+            },
+        )?;
+
+        Ok(Response::new()
+            .add_attribute("action", "grant_move_key")
+            .add_attribute("sender", &grantor)
+            .add_event(
+                Event::new("move_key_granted")
+                    .add_attribute("grantor", grantor)
+                    .add_attribute("delegate", delegate)
+                    .add_attribute("expires", expires.to_string())
+                    .add_attribute("match_id", match_id.unwrap_or_default()),
+            ))
+    }
+
+    pub fn revoke_move_key(
+        deps: DepsMut,
+        info: MessageInfo,
+        delegate: Addr,
+    ) -> Result<Response, ContractError> {
+        let grantor = info.sender;
+        ensure!(
+            MOVE_KEYS.has(deps.storage, (&grantor, &delegate)),
+            ContractError::UnknownMoveKey {}
+        );
+        MOVE_KEYS.remove(deps.storage, (&grantor, &delegate));
+
+        Ok(Response::new()
+            .add_attribute("action", "revoke_move_key")
+            .add_attribute("sender", &grantor)
+            .add_event(
+                Event::new("move_key_revoked")
+                    .add_attribute("grantor", grantor)
+                    .add_attribute("delegate", delegate),
+            ))
+    }
+
+    pub fn register_bot_callback(
+        deps: DepsMut,
+        info: MessageInfo,
+    ) -> Result<Response, ContractError> {
+        let bot = info.sender;
+        deps.querier
+            .query_wasm_contract_info(&bot)
+            .map_err(|_| ContractError::NotAContract {})?;
+        BOT_CALLBACKS.save(deps.storage, &bot, &())?;
+
+        Ok(Response::new()
+            .add_attribute("action", "register_bot_callback")
+            .add_attribute("sender", &bot))
+    }
+
+    pub fn unregister_bot_callback(
+        deps: DepsMut,
+        info: MessageInfo,
+    ) -> Result<Response, ContractError> {
+        let bot = info.sender;
+        BOT_CALLBACKS.remove(deps.storage, &bot);
+
+        Ok(Response::new()
+            .add_attribute("action", "unregister_bot_callback")
+            .add_attribute("sender", &bot))
+    }
+
+    /// `YourTurn` notification for the side to move, if it is a registered bot.
+    /// Sent with `reply_on_error` and a gas limit so that a failing or
+    /// expensive bot can't revert the move.
+    fn notify_bot(
+        storage: &dyn cosmwasm_std::Storage,
+        match_id: MatchId,
+        chess_match: &Match,
+        last_move: Option<String>,
+    ) -> StdResult<Option<SubMsg>> {
+        let bot = match chess_match.state {
+            MatchState::OnGoing(NextMove::Whites) => &chess_match.challenger,
+            MatchState::OnGoing(NextMove::Blacks) => &chess_match.opponent,
+            _ => return Ok(None),
+        };
+        if !BOT_CALLBACKS.has(storage, bot) {
+            return Ok(None);
+        }
+
+        let msg = WasmMsg::Execute {
+            contract_addr: bot.to_string(),
+            msg: to_json_binary(&BotExecuteMsg::YourTurn {
+                match_id: hex::encode(match_id),
+                fen: chess_match.board(),
+                last_move,
+            })?,
+            funds: vec![],
+        };
+        Ok(Some(
+            SubMsg::reply_on_error(msg, BOT_CALLBACK_REPLY_ID)
+                .with_gas_limit(BOT_CALLBACK_GAS_LIMIT),
+        ))
+    }
+
+    /// The player on whose behalf `signer` acts in a match: the signer itself,
+    /// or the player who granted it a valid move key, the side to move first.
+    fn resolve_player(
+        storage: &dyn cosmwasm_std::Storage,
+        env: &Env,
+        match_id: MatchId,
+        chess_match: &Match,
+        signer: &Addr,
+    ) -> StdResult<Addr> {
+        if signer == chess_match.challenger || signer == chess_match.opponent {
+            return Ok(signer.clone());
+        }
+
+        let grantors = match chess_match.state {
+            MatchState::OnGoing(NextMove::Blacks) => {
+                [&chess_match.opponent, &chess_match.challenger]
+            }
+            _ => [&chess_match.challenger, &chess_match.opponent],
+        };
+        for grantor in grantors {
+            if let Some(key) = MOVE_KEYS.may_load(storage, (grantor, signer))? {
+                let in_scope = key
+                    .match_id
+                    .as_ref()
+                    .map_or(true, |id| *id == hex::encode(match_id));
+                if in_scope && key.expires > env.block.height {
+                    return Ok(grantor.clone());
+                }
+            }
+        }
+        Ok(signer.clone())
+    }
+
+    pub fn place_side_bet(
+        deps: DepsMut,
+        info: MessageInfo,
+        match_id: String,
+        outcome: MatchResult,
+    ) -> Result<Response, ContractError> {
+        let bettor = info.sender;
+        let match_id = validate_match_id(&match_id)?;
+        let chess_match = lookup_match(&deps, match_id)?;
+        ensure!(
+            bettor != chess_match.challenger && bettor != chess_match.opponent,
+            ContractError::PlayersCannotSideBet {}
+        );
+        // Nor can voters playing a side, or a cross-chain opponent through the
+        // address their winnings are paid to
+        let voter = VOTE_GROUPS
+            .may_load(deps.storage, match_id)?
+            .is_some_and(|group| group.voters.contains(&bettor));
+        let remote = IBC_OPPONENTS
+            .may_load(deps.storage, match_id)?
+            .is_some_and(|opponent| opponent.payout_address.as_ref() == Some(&bettor));
+        ensure!(!voter && !remote, ContractError::PlayersCannotSideBet {});
+
+        let config = SIDE_BET_CONFIG.may_load(deps.storage)?.unwrap_or_default();
+        match chess_match.state {
+            MatchState::Won | MatchState::Drawn => {
+                return Err(ContractError::MatchAlreadyFinished {});
+            }
+            MatchState::OnGoing(_) => ensure!(
+                chess_match.plies() < config.close_after_plies,
+                ContractError::BettingClosed {}
+            ),
+            MatchState::AwaitingOpponent => {}
+        }
+
+        let stake = validate_bet(&info.funds, &Coin::new(1, &chess_match.bet.denom))?;
+
+        let mut market = MARKETS
+            .may_load(deps.storage, match_id)?
+            .unwrap_or_else(|| Market::new(&chess_match.bet.denom));
+        ensure!(!market.closed, ContractError::BettingClosed {});
+        let mut bets = SIDE_BETS
+            .may_load(deps.storage, (&match_id, &bettor))?
+            .unwrap_or_default();
+        if bets.is_empty() {
+            ensure!(
+                market.bettors < MAX_SIDE_BETTORS,
+                ContractError::MarketFull {}
+            );
+            market.bettors += 1;
+        }
+        market.add(&outcome, stake.amount);
+        match bets.iter_mut().find(|bet| bet.outcome == outcome) {
+            Some(bet) => bet.amount += stake.amount,
+            None => bets.push(SideBet {
+                outcome: outcome.clone(),
+                amount: stake.amount,
+            }),
+        }
+        MARKETS.save(deps.storage, match_id, &market)?;
+        SIDE_BETS.save(deps.storage, (&match_id, &bettor), &bets)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "place_side_bet")
+            .add_attribute("sender", &bettor)
+            .add_event(
+                Event::new("side_bet_placed")
+                    .add_attribute("match_id", hex::encode(match_id))
+                    .add_attribute("bettor", bettor)
+                    .add_attribute("outcome", format!("{:?}", outcome))
+                    .add_attribute("amount", stake.to_string()),
+            ))
+    }
+
+    pub fn update_side_bet_config(
+        deps: DepsMut,
+        info: MessageInfo,
+        close_after_plies: u32,
+        house_fee_percent: u32,
+    ) -> Result<Response, ContractError> {
+        ensure_admin(&deps, &info.sender)?;
+        ensure!(
+            house_fee_percent <= HOUSE_FEE_TOTAL,
+            ContractError::InvalidHouseFee {}
+        );
+
+        SIDE_BET_CONFIG.save(
+            deps.storage,
+            &SideBetConfig {
+                close_after_plies,
+                house_fee_percent,
+            },
+        )?;
+
+        Ok(Response::new()
+            .add_attribute("action", "update_side_bet_config")
+            .add_attribute("sender", info.sender))
+    }
+
+    pub fn offer_rematch(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        match_id: String,
+    ) -> Result<Response, ContractError> {
+        let player = info.sender;
+        let match_id = validate_match_id(&match_id)?;
+        let mut finished = lookup_rematch(&deps, &env, match_id)?;
+        ensure!(
+            player == finished.white || player == finished.black,
+            ContractError::Unauthorized {}
+        );
+        ensure!(
+            finished.rematch_offer.is_none(),
+            ContractError::RematchAlreadyOffered {}
+        );
+
+        let bet = validate_bet(&info.funds, &finished.bet)?;
+        validate_opponent_bet(&finished.bet.amount, &bet.amount)?;
+
+        finished.rematch_offer = Some(player.clone());
+        FINISHED_MATCHES.save(deps.storage, match_id, &finished)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "offer_rematch")
+            .add_attribute("sender", &player)
+            .add_event(
+                Event::new("rematch_offered")
+                    .add_attribute("match_id", hex::encode(match_id))
+                    .add_attribute("player", player),
+            ))
+    }
+
+    /// Starts the rematch with swapped colours, using the offerer's escrowed
+    /// stake and the acceptor's deposit.
+    pub fn accept_rematch(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        match_id: String,
+    ) -> Result<Response, ContractError> {
+        let player = info.sender;
+        let match_id = validate_match_id(&match_id)?;
+        let finished = lookup_rematch(&deps, &env, match_id)?;
+        let offerer = finished
+            .rematch_offer
+            .clone()
+            .ok_or(ContractError::NoRematchOffer {})?;
+        ensure!(
+            player != offerer && (player == finished.white || player == finished.black),
+            ContractError::Unauthorized {}
+        );
+
+        let bet = validate_bet(&info.funds, &finished.bet)?;
+        validate_opponent_bet(&finished.bet.amount, &bet.amount)?;
+
+        let nonce = NEXT_NONCE.load(deps.storage)?;
+        let mut rematch = Match::new(
+            finished.black.clone(),
+            finished.white.clone(),
+            nonce,
+            finished.bet.clone(),
+        );
+        rematch.style = finished.style;
+        rematch.rated = finished.rated;
+        rematch.start(env.block.height);
+        let rematch_id = register_match(deps.storage, &rematch)?;
+        FINISHED_MATCHES.remove(deps.storage, match_id);
+        FINISHED_BY_HEIGHT.remove(deps.storage, (finished.finished, match_id));
+        let notification = notify_bot(deps.storage, rematch_id, &rematch, None)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "accept_rematch")
+            .add_attribute("sender", &player)
+            .add_event(
+                Event::new("match_created")
+                    .add_attribute("challenger", &rematch.challenger)
+                    .add_attribute("opponent", &rematch.opponent)
+                    .add_attribute("match_id", hex::encode(rematch_id))
+                    .add_attribute("rematch_of", hex::encode(match_id)),
+            )
+            .add_event(
+                Event::new("match_started").add_attribute("match_id", hex::encode(rematch_id)),
+            )
+            .add_submessages(notification))
+    }
+
+    /// Withdraws a rematch offer and refunds the escrowed stake. Also works
+    /// once the rematch window is over, so stakes can't get stuck.
+    pub fn cancel_rematch(
+        deps: DepsMut,
+        info: MessageInfo,
+        match_id: String,
+    ) -> Result<Response, ContractError> {
+        let player = info.sender;
+        let match_id = validate_match_id(&match_id)?;
+        let finished = FINISHED_MATCHES
+            .may_load(deps.storage, match_id)?
+            .ok_or(ContractError::UnknownMatch {})?;
+        ensure!(
+            finished.rematch_offer.as_ref() == Some(&player),
+            ContractError::NoRematchOffer {}
+        );
+        FINISHED_MATCHES.remove(deps.storage, match_id);
+        FINISHED_BY_HEIGHT.remove(deps.storage, (finished.finished, match_id));
+
+        Ok(Response::new()
+            .add_attribute("action", "cancel_rematch")
+            .add_attribute("sender", &player)
+            .add_event(
+                Event::new("rematch_cancelled").add_attribute("match_id", hex::encode(match_id)),
+            )
+            .add_message(BankMsg::Send {
+                to_address: player.to_string(),
+                amount: vec![finished.bet],
+            }))
+    }
+
+    /// Forgets up to `FINISHED_PRUNE_LIMIT` finished matches whose rematch
+    /// window is over. Those with an escrowed offer are kept until the offerer
+    /// cancels it.
+    fn prune_finished_matches(
+        storage: &mut dyn cosmwasm_std::Storage,
+        height: u64,
+    ) -> StdResult<()> {
+        let lapsed = FINISHED_BY_HEIGHT
+            .keys(storage, None, None, Order::Ascending)
+            .take(FINISHED_PRUNE_LIMIT)
+            .take_while(|key| {
+                key.as_ref()
+                    .map_or(true, |(finished, _)| finished + REMATCH_WINDOW < height)
+            })
+            .collect::<StdResult<Vec<_>>>()?;
+        for (finished, match_id) in lapsed {
+            FINISHED_BY_HEIGHT.remove(storage, (finished, match_id));
+            let offered = FINISHED_MATCHES
+                .may_load(storage, match_id)?
+                .is_some_and(|finished| finished.rematch_offer.is_some());
+            if !offered {
+                FINISHED_MATCHES.remove(storage, match_id);
+            }
+        }
+        Ok(())
+    }
+
+    fn lookup_rematch(
+        deps: &DepsMut,
+        env: &Env,
+        match_id: MatchId,
+    ) -> Result<FinishedMatch, ContractError> {
+        let finished = FINISHED_MATCHES
+            .may_load(deps.storage, match_id)?
+            .ok_or(ContractError::UnknownMatch {})?;
+        ensure!(
+            env.block.height <= finished.finished + REMATCH_WINDOW,
+            ContractError::RematchUnavailable {}
+        );
+        Ok(finished)
+    }
+
+    pub fn update_arbiter(
+        deps: DepsMut,
+        info: MessageInfo,
+        arbiter: Addr,
+    ) -> Result<Response, ContractError> {
+        ensure_admin(&deps, &info.sender)?;
+        let arbiter = validate_address(deps.api, arbiter.as_str())?;
+        ARBITER.save(deps.storage, &arbiter)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "update_arbiter")
+            .add_attribute("sender", info.sender)
+            .add_attribute("arbiter", arbiter))
+    }
+
+    /// Ends a match with the arbiter's ruling, paying out or refunding as if it
+    /// had been played to that result.
+    pub fn adjudicate(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        match_id: String,
+        result: Adjudication,
+        reason: String,
+    ) -> Result<Response, ContractError> {
+        let arbiter = info.sender;
+        let match_id = validate_match_id(&match_id)?;
+        let chess_match = lookup_match(&deps, match_id)?;
+        ensure_eq!(
+            load_arbiter(deps.storage, Some(match_id))?,
+            arbiter,
+            ContractError::Unauthorized {}
+        );
+
+        let mut msgs: Vec<SubMsg> = vec![];
+        let mut events = vec![Event::new("match_adjudicated")
+            .add_attribute("match_id", hex::encode(match_id))
+            .add_attribute("arbiter", &arbiter)
+            .add_attribute("result", format!("{result:?}"))
+            .add_attribute("reason", reason)
+            .add_attribute("board", chess_match.board())];
+
+        let result = match result {
+            Adjudication::WhiteWins => MatchResult::WhiteWins,
+            Adjudication::BlackWins => MatchResult::BlackWins,
+            Adjudication::Draw => MatchResult::Draw,
+            Adjudication::Void => {
+                // Tournament games must produce a result for their event to go on
+                ensure!(
+                    chess_match.tournament.is_none(),
+                    ContractError::InvalidAdjudication {}
+                );
+                // A void board voids its whole team match
+                if let Some((team_match_id, _)) = TEAM_BOARDS.may_load(deps.storage, match_id)? {
+                    events.push(
+                        Event::new("team_match_voided")
+                            .add_attribute("team_match_id", team_match_id.to_string()),
+                    );
+                    void_team_match(deps.storage, team_match_id, &mut msgs, &mut events)?;
+                    return Ok(adjudication_response(&arbiter, msgs, events));
+                }
+                let ibc_opponent = IBC_OPPONENTS.may_load(deps.storage, match_id)?;
+                match (&chess_match.state, ibc_opponent) {
+                    _ if chess_match.bet.amount.is_zero() => {}
+                    (MatchState::AwaitingOpponent, _) => refund_challenger(&mut msgs, &chess_match),
+                    (_, Some(opponent)) => {
+                        settle_ibc_stakes(&env, match_id, &chess_match, &opponent, None, &mut msgs)?
+                    }
+                    (_, None) => refund_players(&mut msgs, &chess_match),
+                }
+                settle_side_bets(deps.storage, match_id, None, &mut msgs, &mut events)?;
+                clean_match_state(deps.storage, match_id, &chess_match);
+                return Ok(adjudication_response(&arbiter, msgs, events));
+            }
+        };
+
+        ensure!(
+            chess_match.state != MatchState::AwaitingOpponent,
+            ContractError::InvalidAdjudication {}
+        );
+        settle_match(
+            deps.storage,
+            &env,
+            match_id,
+            &chess_match,
+            &result,
+            &mut msgs,
+            &mut events,
+        )?;
+        Ok(adjudication_response(&arbiter, msgs, events))
+    }
+
+    fn adjudication_response(arbiter: &Addr, msgs: Vec<SubMsg>, events: Vec<Event>) -> Response {
+        Response::new()
+            .add_attribute("action", "adjudicate")
+            .add_attribute("sender", arbiter)
+            .add_events(events)
+            .add_submessages(msgs)
+    }
+
+    /// Arbiter of a match if it has one, else the contract arbiter or admin.
+    pub(crate) fn load_arbiter(
+        storage: &dyn cosmwasm_std::Storage,
+        match_id: Option<MatchId>,
+    ) -> StdResult<Addr> {
+        if let Some(arbiter) = match_id
+            .map(|match_id| MATCH_ARBITERS.may_load(storage, match_id))
+            .transpose()?
+            .flatten()
+        {
+            return Ok(arbiter);
+        }
+        match ARBITER.may_load(storage)? {
+            Some(arbiter) => Ok(arbiter),
+            None => ADMIN.load(storage),
+        }
+    }
+
+    pub fn migrate_matches(deps: DepsMut, limit: Option<u32>) -> Result<Response, ContractError> {
+        let (migrated, done) =
+            migrations::migrate_matches(deps.storage, limit.unwrap_or(DEFAULT_MIGRATION_LIMIT))?;
+
+        Ok(Response::new()
+            .add_attribute("action", "migrate_matches")
+            .add_attribute("migrated_matches", migrated.to_string())
+            .add_attribute("done", done.to_string()))
+    }
+
+    pub fn add_hook(
+        deps: DepsMut,
+        info: MessageInfo,
+        addr: Addr,
+    ) -> Result<Response, ContractError> {
+        ensure_admin(&deps, &info.sender)?;
+        let addr = validate_address(deps.api, addr.as_str())?;
+        ensure!(
+            !HOOKS.has(deps.storage, &addr),
+            ContractError::HookAlreadyRegistered {}
+        );
+        HOOKS.save(deps.storage, &addr, &())?;
+
+        Ok(Response::new()
+            .add_attribute("action", "add_hook")
+            .add_attribute("sender", info.sender)
+            .add_attribute("hook", addr))
+    }
+
+    pub fn remove_hook(
+        deps: DepsMut,
+        info: MessageInfo,
+        addr: Addr,
+    ) -> Result<Response, ContractError> {
+        ensure_admin(&deps, &info.sender)?;
+        ensure!(
+            HOOKS.has(deps.storage, &addr),
+            ContractError::UnknownHook {}
+        );
+        HOOKS.remove(deps.storage, &addr);
+
+        Ok(Response::new()
+            .add_attribute("action", "remove_hook")
+            .add_attribute("sender", info.sender)
+            .add_attribute("hook", addr))
+    }
+
+    /// Tells every registered hook that a match is over. Sent with
+    /// `reply_on_error` and a gas limit so that a failing hook can't keep the
+    /// match from finishing.
+    fn notify_hooks(
+        storage: &dyn cosmwasm_std::Storage,
+        match_id: MatchId,
+        chess_match: &Match,
+        result: &MatchResult,
+        msgs: &mut Vec<SubMsg>,
+    ) -> StdResult<()> {
+        let pot = match chess_match.tournament {
+            Some(_) => Coin::new(0, &chess_match.bet.denom),
+            None => Coin::new(chess_match.bet.amount.u128() * 2, &chess_match.bet.denom),
+        };
+        let msg = to_json_binary(&HookExecuteMsg::MatchFinishedHook {
+            match_id: hex::encode(match_id),
+            white: chess_match.challenger.clone(),
+            black: chess_match.opponent.clone(),
+            result: result.clone(),
+            pot,
+        })?;
+
+        for hook in HOOKS.keys(storage, None, None, Order::Ascending) {
+            let hook_msg = WasmMsg::Execute {
+                contract_addr: hook?.to_string(),
+                msg: msg.clone(),
+                funds: vec![],
+            };
+            msgs.push(
+                SubMsg::reply_on_error(hook_msg, HOOK_REPLY_ID).with_gas_limit(HOOK_GAS_LIMIT),
+            );
+        }
+        Ok(())
+    }
+
+    /// Pays out the side-bet market of a match, or refunds it if the match was
+    /// aborted (`result` is `None`), and removes it from storage.
+    fn settle_side_bets(
+        storage: &mut dyn cosmwasm_std::Storage,
+        match_id: MatchId,
+        result: Option<&MatchResult>,
+        msgs: &mut Vec<SubMsg>,
+        events: &mut Vec<Event>,
+    ) -> StdResult<()> {
+        let market = match MARKETS.may_load(storage, match_id)? {
+            Some(market) => market,
+            None => return Ok(()),
+        };
+        let bets = SIDE_BETS
+            .prefix(&match_id)
+            .range(storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<(Addr, Vec<SideBet>)>>>()?;
+
+        let config = SIDE_BET_CONFIG.may_load(storage)?.unwrap_or_default();
+        let (payouts, fee) = settle(&market, &bets, result, config.house_fee_percent);
+        for (bettor, amount) in payouts {
+            if !amount.is_zero() {
+                msgs.push(SubMsg::new(BankMsg::Send {
+                    to_address: bettor.to_string(),
+                    amount: vec![Coin::new(amount.u128(), &market.denom)],
+                }));
+            }
+        }
+        if !fee.is_zero() {
+            msgs.push(SubMsg::new(BankMsg::Send {
+                to_address: ADMIN.load(storage)?.to_string(),
+                amount: vec![Coin::new(fee.u128(), &market.denom)],
+            }));
+        }
+
+        let event = match result {
+            Some(result) => Event::new("side_bets_settled")
+                .add_attribute("match_id", hex::encode(match_id))
+                .add_attribute("outcome", format!("{:?}", result))
+                .add_attribute(
+                    "house_fee",
+                    Coin::new(fee.u128(), &market.denom).to_string(),
+                ),
+            None => Event::new("side_bets_voided").add_attribute("match_id", hex::encode(match_id)),
+        };
+        events.push(event);
+
+        for (bettor, _) in bets.iter() {
+            SIDE_BETS.remove(storage, (&match_id, bettor));
+        }
+        MARKETS.remove(storage, match_id);
+        Ok(())
+    }
+
+    pub fn set_profile(
+        deps: DepsMut,
+        info: MessageInfo,
+        profile: Profile,
+    ) -> Result<Response, ContractError> {
+        let player = info.sender;
+        profile
+            .validate()
+            .map_err(|reason| ContractError::InvalidProfile { reason })?;
+
+        let key = Profile::nickname_key(&profile.nickname);
+        if let Some(owner) = NICKNAMES.may_load(deps.storage, &key)? {
+            ensure_eq!(owner, player, ContractError::NicknameTaken {});
+        }
+        if let Some(previous) = PROFILES.may_load(deps.storage, &player)? {
+            NICKNAMES.remove(deps.storage, &Profile::nickname_key(&previous.nickname));
+        }
+        NICKNAMES.save(deps.storage, &key, &player)?;
+        PROFILES.save(deps.storage, &player, &profile)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "set_profile")
+            .add_attribute("sender", &player)
+            .add_event(
+                Event::new("profile_updated")
+                    .add_attribute("player", player)
+                    .add_attribute("nickname", profile.nickname),
+            ))
+    }
+
+    pub fn set_challenge_policy(
+        deps: DepsMut,
+        info: MessageInfo,
+        policy: ChallengePolicy,
+    ) -> Result<Response, ContractError> {
+        let player = info.sender;
+        for addr in policy
+            .blocked
+            .iter()
+            .chain(policy.allow_only.iter().flatten())
+        {
+            validate_address(deps.api, addr.as_str())?;
+        }
+
+        // The default policy accepts everything, no need to keep it around
+        if policy == ChallengePolicy::default() {
+            CHALLENGE_POLICIES.remove(deps.storage, &player);
+        } else {
+            CHALLENGE_POLICIES.save(deps.storage, &player, &policy)?;
+        }
+
+        Ok(Response::new()
+            .add_attribute("action", "set_challenge_policy")
+            .add_attribute("sender", &player)
+            .add_event(Event::new("challenge_policy_updated").add_attribute("player", player)))
+    }
+
+    /// Resolves a nickname to its owner, anything else must be an address.
+    fn resolve_opponent(deps: &DepsMut, opponent: &str) -> Result<Addr, ContractError> {
+        match NICKNAMES.may_load(deps.storage, &Profile::nickname_key(opponent))? {
+            Some(owner) => Ok(owner),
+            None => validate_address(deps.api, opponent),
+        }
+    }
+
+    pub fn start_season(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        prize_pool: Coin,
+        prize_split: Vec<u32>,
+    ) -> Result<Response, ContractError> {
+        ensure_admin(&deps, &info.sender)?;
+        ensure!(
+            !CURRENT_SEASON.exists(deps.storage) && !SEASON_CLOSE.exists(deps.storage),
+            ContractError::SeasonInProgress {}
+        );
+        ensure_eq!(
+            prize_split.iter().sum::<u32>(),
+            PRIZE_SPLIT_TOTAL,
+            ContractError::InvalidPrizeSplit {}
+        );
+        validate_entry_fee(&info.funds, &prize_pool)?;
+
+        let season_id = next_season_id(deps.storage)?;
+        SEASONS.save(
+            deps.storage,
+            season_id,
+            &Season::new(env.block.height, prize_pool, prize_split),
+        )?;
+        CURRENT_SEASON.save(deps.storage, &season_id)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "start_season")
+            .add_attribute("sender", info.sender)
+            .add_event(
+                Event::new("season_started").add_attribute("season_id", season_id.to_string()),
+            ))
+    }
+
+    /// Ranks a page of the season's standings, and pays the prizes once all
+    /// have been ranked. Results stop counting from the first page on.
+    pub fn end_season(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        limit: Option<u32>,
+    ) -> Result<Response, ContractError> {
+        ensure_admin(&deps, &info.sender)?;
+        let mut close = match SEASON_CLOSE.may_load(deps.storage)? {
+            Some(close) => close,
+            None => {
+                let season_id = CURRENT_SEASON
+                    .may_load(deps.storage)?
+                    .ok_or(ContractError::NoSeasonInProgress {})?;
+                CURRENT_SEASON.remove(deps.storage);
+                let mut season = SEASONS.load(deps.storage, season_id)?;
+                season.end = Some(env.block.height);
+                SEASONS.save(deps.storage, season_id, &season)?;
+                SeasonClose {
+                    season_id,
+                    cursor: None,
+                    leaders: vec![],
+                }
+            }
+        };
+        let season_id = close.season_id;
+        let mut season = SEASONS.load(deps.storage, season_id)?;
+
+        let limit = limit.unwrap_or(DEFAULT_END_SEASON_LIMIT) as usize;
+        let page = SEASON_STANDINGS
+            .prefix(season_id)
+            .range(
+                deps.storage,
+                close.cursor.as_ref().map(Bound::exclusive),
+                None,
+                Order::Ascending,
+            )
+            .take(limit)
+            .collect::<StdResult<Vec<_>>>()?;
+        let done = page.len() < limit;
+        if let Some((player, _)) = page.last() {
+            close.cursor = Some(player.clone());
+        }
+        close
+            .leaders
+            .extend(page.into_iter().map(|(_, standing)| standing));
+        close.leaders = keep_leaders(close.leaders, season.prize_split.len());
+        if !done {
+            SEASON_CLOSE.save(deps.storage, &close)?;
+            return Ok(Response::new()
+                .add_attribute("action", "end_season")
+                .add_attribute("sender", info.sender)
+                .add_attribute("done", done.to_string()));
+        }
+        SEASON_CLOSE.remove(deps.storage);
+
+        let ranking: Vec<Vec<Addr>> = rank_season(close.leaders)
+            .into_iter()
+            .map(|group| group.into_iter().map(|standing| standing.player).collect())
+            .collect();
+        let mut event =
+            Event::new("season_finished").add_attribute("season_id", season_id.to_string());
+        let mut rank = 1;
+        for group in ranking.iter() {
+            for player in group {
+                event = event.add_attribute(format!("rank_{}", rank), player);
+            }
+            rank += group.len();
+        }
+
+        let pool = season.prize_pool.amount;
+        season.prizes = match ranking.is_empty() {
+            // Nobody played, the pool goes back to the admin
+            true => vec![(info.sender.clone(), pool)],
+            false => prize_shares(pool, &season.prize_split, &ranking),
+        };
+        season.prizes.retain(|(_, amount)| !amount.is_zero());
+        let msgs: Vec<CosmosMsg> = season
+            .prizes
+            .iter()
+            .map(|(player, amount)| {
+                BankMsg::Send {
+                    to_address: player.to_string(),
+                    amount: vec![Coin::new(amount.u128(), &season.prize_pool.denom)],
+                }
+                .into()
+            })
+            .collect();
+
+        SEASONS.save(deps.storage, season_id, &season)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "end_season")
+            .add_attribute("sender", info.sender)
+            .add_attribute("done", done.to_string())
+            .add_event(event)
+            .add_messages(msgs))
+    }
+
+    /// Records a finished match in the stats of its players. The contract,
+    /// standing in for vote groups and players on other chains, has none.
+    fn record_player_stats(
+        storage: &mut dyn cosmwasm_std::Storage,
+        contract: &Addr,
+        chess_match: &Match,
+        result: &MatchResult,
+    ) -> StdResult<()> {
+        let (white, black) = (&chess_match.challenger, &chess_match.opponent);
+        let plies = chess_match.plies();
+        let stake = chess_match.tournament.is_none().then_some(&chess_match.bet);
+        for (player, color) in [(white, Color::White), (black, Color::Black)] {
+            if player == contract {
+                continue;
+            }
+            let mut stats = PLAYER_STATS.may_load(storage, player)?.unwrap_or_default();
+            stats.record(color, result, plies, stake)?;
+            PLAYER_STATS.save(storage, player, &stats)?;
+        }
+
+        if white == contract || black == contract {
+            return Ok(());
+        }
+        let key = pair_key(white, black);
+        let mut record = HEAD_TO_HEAD.may_load(storage, key)?.unwrap_or_default();
+        record.record(match result {
+            MatchResult::WhiteWins => Some(key.0 == white),
+            MatchResult::BlackWins => Some(key.0 == black),
+            MatchResult::Draw => None,
+        });
+        HEAD_TO_HEAD.save(storage, key, &record)
+    }
+
+    /// Adds a rated result to the season in progress, if any.
+    fn record_rating(
+        storage: &mut dyn cosmwasm_std::Storage,
+        chess_match: &Match,
+        result: &MatchResult,
+    ) -> StdResult<()> {
+        let (white, black) = (&chess_match.challenger, &chess_match.opponent);
+        let (white_rating, black_rating) = update_ratings(
+            load_rating(storage, white)?,
+            load_rating(storage, black)?,
+            result,
+        );
+        RATINGS.save(storage, white, &white_rating)?;
+        RATINGS.save(storage, black, &black_rating)
+    }
+
+    fn record_season_result(
+        storage: &mut dyn cosmwasm_std::Storage,
+        chess_match: &Match,
+        result: &MatchResult,
+    ) -> StdResult<()> {
+        let Some(season_id) = CURRENT_SEASON.may_load(storage)? else {
+            return Ok(());
+        };
+        let (white, black) = (&chess_match.challenger, &chess_match.opponent);
+        let load = |player: &Addr| -> StdResult<SeasonStanding> {
+            Ok(SEASON_STANDINGS
+                .may_load(storage, (season_id, player))?
+                .unwrap_or_else(|| SeasonStanding::new(player.clone())))
+        };
+
+        let (white_standing, black_standing) = (load(white)?, load(black)?);
+
+        let (white_score, black_score) = game_scores(result);
+        let (white_rating, black_rating) =
+            update_ratings(white_standing.rating, black_standing.rating, result);
+        for (mut standing, score, rating) in [
+            (white_standing, white_score, white_rating),
+            (black_standing, black_score, black_rating),
+        ] {
+            let player = standing.player.clone();
+            SEASON_RANKING.remove(storage, (season_id, rank_key(&standing), &player));
+            standing.record(score, rating);
+            SEASON_RANKING.save(storage, (season_id, rank_key(&standing), &player), &())?;
+            SEASON_STANDINGS.save(storage, (season_id, &player), &standing)?;
+        }
+        Ok(())
+    }
+
+    pub fn post_puzzle(
+        deps: DepsMut,
+        info: MessageInfo,
+        fen: String,
+        mate_in: u32,
+        reward: Coin,
+    ) -> Result<Response, ContractError> {
+        let poster = info.sender;
+        let board = Match::decode_board(&fen).map_err(|_| ContractError::InvalidPuzzle {
+            reason: InvalidPuzzleReason::InvalidBoard,
+        })?;
+        ensure_eq!(
+            board.status(),
+            GameStatus::Ongoing,
+            ContractError::InvalidPuzzle {
+                reason: InvalidPuzzleReason::GameOver,
+            }
+        );
+        ensure!(
+            (1..=MAX_MATE_IN).contains(&mate_in),
+            ContractError::InvalidPuzzle {
+                reason: InvalidPuzzleReason::MateInOutOfRange,
+            }
+        );
+        let escrow = validate_bet(&info.funds, &reward)?;
+        validate_opponent_bet(&reward.amount, &escrow.amount)?;
+
+        let puzzle = Puzzle {
+            poster: poster.clone(),
+            fen: Match::encode_board(&board),
+            mate_in,
+            reward,
+            solver: None,
+            withdrawal_requested: None,
+        };
+        let puzzle_id = next_puzzle_id(deps.storage)?;
+        PUZZLES.save(deps.storage, puzzle_id, &puzzle)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "post_puzzle")
+            .add_attribute("sender", &poster)
+            .add_event(
+                Event::new("puzzle_posted")
+                    .add_attribute("puzzle_id", puzzle_id.to_string())
+                    .add_attribute("poster", poster)
+                    .add_attribute("mate_in", mate_in.to_string()),
+            ))
+    }
+
+    pub fn commit_solution(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        puzzle_id: PuzzleId,
+        commitment: String,
+    ) -> Result<Response, ContractError> {
+        let solver = info.sender;
+        let puzzle = lookup_puzzle(&deps, puzzle_id)?;
+        ensure!(
+            puzzle.solver.is_none(),
+            ContractError::PuzzleAlreadySolved {}
+        );
+        ensure!(
+            puzzle.withdrawal_requested.is_none(),
+            ContractError::PuzzleWithdrawing {}
+        );
+
+        // Committing again replaces the previous commitment, and restarts its clock
+        SOLUTION_COMMITMENTS.save(
+            deps.storage,
+            (puzzle_id, &solver),
+            &SolutionCommitment {
+                hash: commitment.to_lowercase(),
+                block_height: env.block.height,
+            },
+        )?;
+
+        Ok(Response::new()
+            .add_attribute("action", "commit_solution")
+            .add_attribute("sender", solver)
+            .add_attribute("puzzle_id", puzzle_id.to_string()))
+    }
+
+    pub fn solve_puzzle(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        puzzle_id: PuzzleId,
+        line: Vec<String>,
+        salt: String,
+    ) -> Result<Response, ContractError> {
+        let solver = info.sender;
+        let mut puzzle = lookup_puzzle(&deps, puzzle_id)?;
+        ensure!(
+            puzzle.solver.is_none(),
+            ContractError::PuzzleAlreadySolved {}
+        );
+
+        // Only a commitment from an earlier block proves the solution wasn't
+        // copied from a pending reveal
+        let commitment = SOLUTION_COMMITMENTS
+            .may_load(deps.storage, (puzzle_id, &solver))?
+            .filter(|commitment| commitment.block_height < env.block.height)
+            .ok_or(ContractError::NoSolutionCommitment {})?;
+        ensure_eq!(
+            commitment.hash,
+            solution_commitment(&solver, puzzle_id, &line, &salt),
+            ContractError::CommitmentMismatch {}
+        );
+        validate_solution(&puzzle, &line)?;
+
+        puzzle.solver = Some(solver.clone());
+        PUZZLES.save(deps.storage, puzzle_id, &puzzle)?;
+        clear_solution_commitments(deps.storage, puzzle_id)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "solve_puzzle")
+            .add_attribute("sender", &solver)
+            .add_event(
+                Event::new("puzzle_solved")
+                    .add_attribute("puzzle_id", puzzle_id.to_string())
+                    .add_attribute("solver", &solver)
+                    .add_attribute("line", line.join(" ")),
+            )
+            .add_message(BankMsg::Send {
+                to_address: solver.into_string(),
+                amount: vec![puzzle.reward],
+            }))
+    }
+
+    /// Refunds an unsolved puzzle to its poster, once no solver committed to
+    /// it within the last `REVEAL_WINDOW` blocks. While some did, the first
+    /// call closes the puzzle to new commitments, so that the pending ones
+    /// run out within `REVEAL_WINDOW` blocks.
+    pub fn withdraw_puzzle(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        puzzle_id: PuzzleId,
+    ) -> Result<Response, ContractError> {
+        let poster = info.sender;
+        let mut puzzle = lookup_puzzle(&deps, puzzle_id)?;
+        ensure_eq!(puzzle.poster, poster, ContractError::NotPuzzlePoster {});
+        ensure!(
+            puzzle.solver.is_none(),
+            ContractError::PuzzleAlreadySolved {}
+        );
+        let mut pending = false;
+        for commitment in
+            SOLUTION_COMMITMENTS
+                .prefix(puzzle_id)
+                .range(deps.storage, None, None, Order::Ascending)
+        {
+            let (_, commitment) = commitment?;
+            pending |= commitment.block_height + REVEAL_WINDOW >= env.block.height;
+        }
+        if pending {
+            ensure!(
+                puzzle.withdrawal_requested.is_none(),
+                ContractError::PendingSolutions {}
+            );
+            puzzle.withdrawal_requested = Some(env.block.height);
+            PUZZLES.save(deps.storage, puzzle_id, &puzzle)?;
+            return Ok(Response::new()
+                .add_attribute("action", "withdraw_puzzle")
+                .add_attribute("sender", &poster)
+                .add_attribute("puzzle_id", puzzle_id.to_string())
+                .add_event(
+                    Event::new("puzzle_withdrawal_requested")
+                        .add_attribute("puzzle_id", puzzle_id.to_string())
+                        .add_attribute(
+                            "withdrawable",
+                            (env.block.height + REVEAL_WINDOW + 1).to_string(),
+                        ),
+                ));
+        }
+        PUZZLES.remove(deps.storage, puzzle_id);
+        clear_solution_commitments(deps.storage, puzzle_id)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "withdraw_puzzle")
+            .add_attribute("sender", &poster)
+            .add_attribute("puzzle_id", puzzle_id.to_string())
+            .add_message(BankMsg::Send {
+                to_address: poster.into_string(),
+                amount: vec![puzzle.reward],
+            }))
+    }
+
+    fn clear_solution_commitments(
+        storage: &mut dyn cosmwasm_std::Storage,
+        puzzle_id: PuzzleId,
+    ) -> StdResult<()> {
+        let solvers = SOLUTION_COMMITMENTS
+            .prefix(puzzle_id)
+            .keys(storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+        for solver in solvers {
+            SOLUTION_COMMITMENTS.remove(storage, (puzzle_id, &solver));
+        }
+        Ok(())
+    }
+
+    /// A solution is a legal line that ends in mate within the puzzle's
+    /// depth, and whose first move forces mate against every defence.
+    fn validate_solution(puzzle: &Puzzle, line: &[String]) -> Result<(), ContractError> {
+        ensure!(
+            line.len() % 2 == 1 && line.len() < 2 * puzzle.mate_in as usize,
+            ContractError::IncorrectSolution {}
+        );
+        let start =
+            Match::decode_board(&puzzle.fen).expect("Puzzle boards are always encoded by Match.");
+
+        let mut board = start.clone();
+        for move_fen in line {
+            validate_fen_move(move_fen)?;
+            board
+                .try_play(decode_move(move_fen)?)
+                .map_err(|_| ContractError::IncorrectSolution {})?;
+        }
+        ensure_eq!(
+            board.status(),
+            GameStatus::Won,
+            ContractError::IncorrectSolution {}
+        );
+
+        let key = decode_move(&line[0])?;
+        match key_move_forces_mate(&start, key, puzzle.mate_in, MAX_SEARCH_NODES) {
+            Ok(true) => Ok(()),
+            Ok(false) => Err(ContractError::IncorrectSolution {}),
+            Err(SearchLimitExceeded) => Err(ContractError::SearchLimitExceeded {}),
+        }
+    }
+
+    fn lookup_puzzle(deps: &DepsMut, puzzle_id: PuzzleId) -> Result<Puzzle, ContractError> {
+        PUZZLES
+            .load(deps.storage, puzzle_id)
+            .map_err(|_| ContractError::UnknownPuzzle {})
+    }
+
+    /// Challenges team B to a match on `boards` boards, escrowing team A's stake.
+    pub fn create_team_match(
+        deps: DepsMut,
+        info: MessageInfo,
+        team_a: Vec<Addr>,
+        team_b: Vec<Addr>,
+        boards: u32,
+        stake: Coin,
+        tiebreak: TeamTiebreak,
+    ) -> Result<Response, ContractError> {
+        let captain = info.sender;
+        let team_a = validate_team(deps.api, team_a)?;
+        let team_b = validate_team(deps.api, team_b)?;
+        validate_team_match(&team_a, &team_b, boards)?;
+        ensure!(team_a.contains(&captain), ContractError::Unauthorized {});
+        validate_entry_fee(&info.funds, &stake)?;
+
+        let team_match = TeamMatch::new(team_a, team_b, captain.clone(), boards, stake, tiebreak);
+        let team_match_id = next_team_match_id(deps.storage)?;
+        TEAM_MATCHES.save(deps.storage, team_match_id, &team_match)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "create_team_match")
+            .add_attribute("sender", &captain)
+            .add_event(
+                Event::new("team_match_created")
+                    .add_attribute("team_match_id", team_match_id.to_string())
+                    .add_attribute("captain", captain),
+            ))
+    }
+
+    /// Matches team A's stake and sets up a match on every board, team A
+    /// having white on odd boards. Every board player is challenged by their
+    /// board opponent, as in `CreateMatch`.
+    pub fn accept_team_match(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        team_match_id: TeamMatchId,
+    ) -> Result<Response, ContractError> {
+        let captain = info.sender;
+        let mut team_match = lookup_team_match(&deps, team_match_id)?;
+        ensure_eq!(
+            team_match.state,
+            TeamMatchState::AwaitingTeamB,
+            ContractError::NotAwaitingOpponent {}
+        );
+        ensure!(
+            team_match.team_b.contains(&captain),
+            ContractError::Unauthorized {}
+        );
+        validate_entry_fee(&info.funds, &team_match.stake)?;
+
+        let mut event = Event::new("team_match_accepted")
+            .add_attribute("team_match_id", team_match_id.to_string());
+        for board in 0..team_match.boards as usize {
+            let team_a_white = board % 2 == 0;
+            let (white, black) = match team_a_white {
+                true => (&team_match.team_a[board], &team_match.team_b[board]),
+                false => (&team_match.team_b[board], &team_match.team_a[board]),
+            };
+            check_challenge_policy(deps.storage, white, black, team_match.stake.amount)?;
+            check_challenge_policy(deps.storage, black, white, team_match.stake.amount)?;
+            let nonce = NEXT_NONCE.load(deps.storage)?;
+            let chess_match = Match::new(
+                white.clone(),
+                black.clone(),
+                nonce,
+                Coin::new(0, &team_match.stake.denom),
+            );
+            let match_id = register_match(deps.storage, &chess_match)?;
+            TEAM_BOARDS.save(deps.storage, match_id, &(team_match_id, board as u32))?;
+
+            event = event.add_attribute(format!("board_{}", board + 1), hex::encode(match_id));
+            team_match.board_matches.push(TeamBoard {
+                match_id: hex::encode(match_id),
+                team_a_white,
+                result: None,
+            });
+        }
+        team_match.captain_b = Some(captain.clone());
+        team_match.state = TeamMatchState::AwaitingPlayers;
+        // Captains agreed to play their own board
+        team_match.confirmed = [&team_match.captain_a, &captain]
+            .into_iter()
+            .filter(|captain| team_match.board_players().any(|player| player == *captain))
+            .cloned()
+            .collect();
+        let (events, msgs) = start_team_boards(deps.storage, &env, team_match_id, &mut team_match)?;
+        TEAM_MATCHES.save(deps.storage, team_match_id, &team_match)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "accept_team_match")
+            .add_attribute("sender", &captain)
+            .add_event(event)
+            .add_events(events)
+            .add_submessages(msgs))
+    }
+
+    /// Agrees to play the sender's board, starting every board once all
+    /// board players did.
+    pub fn confirm_team_board(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        team_match_id: TeamMatchId,
+    ) -> Result<Response, ContractError> {
+        let player = info.sender;
+        let mut team_match = lookup_team_match(&deps, team_match_id)?;
+        ensure_eq!(
+            team_match.state,
+            TeamMatchState::AwaitingPlayers,
+            ContractError::NotAwaitingOpponent {}
+        );
+        ensure!(
+            team_match
+                .board_players()
+                .any(|board_player| *board_player == player),
+            ContractError::Unauthorized {}
+        );
+        if !team_match.confirmed.contains(&player) {
+            team_match.confirmed.push(player.clone());
+        }
+        let (events, msgs) = start_team_boards(deps.storage, &env, team_match_id, &mut team_match)?;
+        TEAM_MATCHES.save(deps.storage, team_match_id, &team_match)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "confirm_team_board")
+            .add_attribute("sender", &player)
+            .add_event(
+                Event::new("team_board_confirmed")
+                    .add_attribute("team_match_id", team_match_id.to_string())
+                    .add_attribute("player", player),
+            )
+            .add_events(events)
+            .add_submessages(msgs))
+    }
+
+    /// Starts every board of a team match whose board players all confirmed.
+    fn start_team_boards(
+        storage: &mut dyn cosmwasm_std::Storage,
+        env: &Env,
+        team_match_id: TeamMatchId,
+        team_match: &mut TeamMatch,
+    ) -> StdResult<(Vec<Event>, Vec<SubMsg>)> {
+        if !team_match.is_confirmed() {
+            return Ok((vec![], vec![]));
+        }
+        let mut msgs: Vec<SubMsg> = vec![];
+        for board in &team_match.board_matches {
+            let match_id = validate_match_id(&board.match_id)
+                .map_err(|_| StdError::generic_err("Invalid board match ID"))?;
+            let mut chess_match = MATCHES.load(storage, match_id)?;
+            chess_match.start(env.block.height);
+            save_match_state(storage, match_id, &chess_match)?;
+            msgs.extend(notify_bot(storage, match_id, &chess_match, None)?);
+        }
+        team_match.state = TeamMatchState::OnGoing;
+        let event = Event::new("team_match_started")
+            .add_attribute("team_match_id", team_match_id.to_string());
+        Ok((vec![event], msgs))
+    }
+
+    /// Withdraws a team match: team A's captain until team B accepts, either
+    /// captain until its boards start.
+    pub fn abort_team_match(
+        deps: DepsMut,
+        info: MessageInfo,
+        team_match_id: TeamMatchId,
+    ) -> Result<Response, ContractError> {
+        let captain = info.sender;
+        let team_match = lookup_team_match(&deps, team_match_id)?;
+        let captains = [Some(&team_match.captain_a), team_match.captain_b.as_ref()];
+        ensure!(
+            captains.contains(&Some(&captain)),
+            ContractError::Unauthorized {}
+        );
+        ensure!(
+            matches!(
+                team_match.state,
+                TeamMatchState::AwaitingTeamB | TeamMatchState::AwaitingPlayers
+            ),
+            ContractError::NotAwaitingOpponent {}
+        );
+
+        let mut msgs: Vec<SubMsg> = vec![];
+        let mut events = vec![Event::new("team_match_aborted")
+            .add_attribute("team_match_id", team_match_id.to_string())];
+        void_team_match(deps.storage, team_match_id, &mut msgs, &mut events)?;
+        Ok(Response::new()
+            .add_attribute("action", "abort_team_match")
+            .add_attribute("sender", &captain)
+            .add_events(events)
+            .add_submessages(msgs))
+    }
+
+    /// Calls off a team match, refunding the captains' stakes and the side
+    /// bets of its unfinished boards.
+    fn void_team_match(
+        storage: &mut dyn cosmwasm_std::Storage,
+        team_match_id: TeamMatchId,
+        msgs: &mut Vec<SubMsg>,
+        events: &mut Vec<Event>,
+    ) -> StdResult<()> {
+        let team_match = TEAM_MATCHES.load(storage, team_match_id)?;
+        for board in team_match
+            .board_matches
+            .iter()
+            .filter(|board| board.result.is_none())
+        {
+            let match_id = validate_match_id(&board.match_id)
+                .map_err(|_| StdError::generic_err("Invalid board match ID"))?;
+            let chess_match = MATCHES.load(storage, match_id)?;
+            TEAM_BOARDS.remove(storage, match_id);
+            settle_side_bets(storage, match_id, None, msgs, events)?;
+            clean_match_state(storage, match_id, &chess_match);
+        }
+        TEAM_MATCHES.remove(storage, team_match_id);
+
+        let stake = &team_match.stake;
+        if !stake.amount.is_zero() {
+            for captain in [Some(&team_match.captain_a), team_match.captain_b.as_ref()]
+                .into_iter()
+                .flatten()
+            {
+                msgs.push(SubMsg::new(BankMsg::Send {
+                    to_address: captain.to_string(),
+                    amount: vec![stake.clone()],
+                }));
+            }
+        }
+        Ok(())
+    }
+
+    /// Adds the result of a board to its team match, settling the stakes
+    /// once every board is over.
+    fn record_team_board(
+        storage: &mut dyn cosmwasm_std::Storage,
+        match_id: MatchId,
+        result: &MatchResult,
+        msgs: &mut Vec<SubMsg>,
+        events: &mut Vec<Event>,
+    ) -> StdResult<()> {
+        let Some((team_match_id, board)) = TEAM_BOARDS.may_load(storage, match_id)? else {
+            return Ok(());
+        };
+        TEAM_BOARDS.remove(storage, match_id);
+        let mut team_match = TEAM_MATCHES.load(storage, team_match_id)?;
+        team_match.record(board as usize, result.clone());
+        events.push(
+            Event::new("team_board_finished")
+                .add_attribute("team_match_id", team_match_id.to_string())
+                .add_attribute("board", (board + 1).to_string())
+                .add_attribute("score_a", team_match.score_a.to_string())
+                .add_attribute("score_b", team_match.score_b.to_string()),
+        );
+
+        if team_match.is_over() {
+            let winner = team_match.winner();
+            let captain_b = team_match
+                .captain_b
+                .clone()
+                .expect("Boards are only played once team B accepted.");
+            let stake = &team_match.stake;
+            if !stake.amount.is_zero() {
+                let pot = Coin::new(stake.amount.u128() * 2, &stake.denom);
+                let payouts = match winner {
+                    Some(Team::A) => vec![(&team_match.captain_a, pot)],
+                    Some(Team::B) => vec![(&captain_b, pot)],
+                    None => vec![
+                        (&team_match.captain_a, stake.clone()),
+                        (&captain_b, stake.clone()),
+                    ],
+                };
+                for (captain, amount) in payouts {
+                    msgs.push(SubMsg::new(BankMsg::Send {
+                        to_address: captain.to_string(),
+                        amount: vec![amount],
+                    }));
+                }
+            }
+            let winner = match winner {
+                Some(Team::A) => "team_a",
+                Some(Team::B) => "team_b",
+                None => "none",
+            };
+            events.push(
+                Event::new("team_match_finished")
+                    .add_attribute("team_match_id", team_match_id.to_string())
+                    .add_attribute("winner", winner)
+                    .add_attribute("score_a", team_match.score_a.to_string())
+                    .add_attribute("score_b", team_match.score_b.to_string()),
+            );
+            team_match.state = TeamMatchState::Finished;
+        }
+        TEAM_MATCHES.save(storage, team_match_id, &team_match)
+    }
+
+    fn lookup_team_match(
+        deps: &DepsMut,
+        team_match_id: TeamMatchId,
+    ) -> Result<TeamMatch, ContractError> {
+        TEAM_MATCHES
+            .load(deps.storage, team_match_id)
+            .map_err(|_| ContractError::UnknownTeamMatch {})
+    }
+
+    /// Hosts a match against a player on the chain across `channel_id`, who
+    /// plays black under the contract's address. The challenger trusts the
+    /// peer contract on that channel to escrow the opponent's stake.
+    /// Cross-chain matches are unrated.
+    pub fn create_ibc_match(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        channel_id: String,
+        opponent: String,
+        opponent_bet: Coin,
+        payout_address: String,
+    ) -> Result<Response, ContractError> {
+        let challenger = info.sender;
+        ensure_channel(deps.storage, &channel_id)?;
+        // Addresses on the opponent's chain can only be checked in full there
+        ensure!(
+            is_bech32_address(&opponent) && is_bech32_address(&payout_address),
+            ContractError::InvalidAddress {}
+        );
+        ensure!(
+            !opponent_bet.amount.is_zero(),
+            ContractError::InvalidBet {
+                reason: InvalidBetReason::AmountTooLow,
+            }
+        );
+
+        let min_bet = MIN_BET.load(deps.storage)?;
+        let bet = validate_bet(&info.funds, &Coin::new(min_bet.0.into(), min_bet.1))?;
+        let nonce = NEXT_NONCE.load(deps.storage)?;
+        let mut ibc_match =
+            Match::new(challenger.clone(), env.contract.address.clone(), nonce, bet);
+        ibc_match.rated = false;
+        let match_id = register_match(deps.storage, &ibc_match)?;
+        IBC_OPPONENTS.save(
+            deps.storage,
+            match_id,
+            &IbcOpponent {
+                channel_id: channel_id.clone(),
+                player: opponent.clone(),
+                stake: opponent_bet,
+                challenger_payout: payout_address,
+                payout_address: None,
+            },
+        )?;
+
+        Ok(Response::new()
+            .add_attribute("action", "create_ibc_match")
+            .add_attribute("sender", &challenger)
+            .add_event(
+                Event::new("match_created")
+                    .add_attribute("challenger", challenger)
+                    .add_attribute("opponent", opponent)
+                    .add_attribute("channel_id", channel_id)
+                    .add_attribute("match_id", hex::encode(match_id)),
+            ))
+    }
+
+    /// Escrows the sender's stake and asks the host across `channel_id` to
+    /// start the match. The stake is refunded if the host turns them down.
+    pub fn join_ibc_match(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        channel_id: String,
+        match_id: String,
+        payout_address: String,
+    ) -> Result<Response, ContractError> {
+        let player = info.sender;
+        ensure_channel(deps.storage, &channel_id)?;
+        let match_id = hex::encode(validate_match_id(&match_id)?);
+        let stake = match info.funds.as_slice() {
+            [stake] if !stake.amount.is_zero() => stake.clone(),
+            [] | [_] => {
+                return Err(ContractError::InvalidBet {
+                    reason: InvalidBetReason::MissingBet,
+                })
+            }
+            _ => {
+                return Err(ContractError::InvalidBet {
+                    reason: InvalidBetReason::TooManyCoins,
+                })
+            }
+        };
+        ensure!(
+            !IBC_ESCROWS.has(deps.storage, (&channel_id, &match_id, &player)),
+            ContractError::NotAwaitingOpponent {}
+        );
+
+        IBC_ESCROWS.save(
+            deps.storage,
+            (&channel_id, &match_id, &player),
+            &IbcEscrow {
+                player: player.clone(),
+                stake: stake.clone(),
+                joined: false,
+            },
+        )?;
+        let packet = ChessPacket::JoinMatch {
+            match_id: match_id.clone(),
+            player: player.to_string(),
+            stake,
+            payout_address,
+        };
+
+        Ok(Response::new()
+            .add_attribute("action", "join_ibc_match")
+            .add_attribute("sender", &player)
+            .add_message(IbcMsg::SendPacket {
+                channel_id: channel_id.clone(),
+                data: to_json_binary(&packet)?,
+                timeout: ibc_timeout(&env),
+            })
+            .add_event(
+                Event::new("ibc_join_sent")
+                    .add_attribute("channel_id", channel_id)
+                    .add_attribute("match_id", match_id),
+            ))
+    }
+
+    /// Sends the sender's move to a match hosted across `channel_id`. The host
+    /// checks it, a rejected move comes back as an `ibc_move_failed` event.
+    pub fn relay_move(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        channel_id: String,
+        match_id: String,
+        move_fen: String,
+    ) -> Result<Response, ContractError> {
+        let player = info.sender;
+        let match_id = hex::encode(validate_match_id(&match_id)?);
+        validate_fen_move(&move_fen)?;
+        let escrow = IBC_ESCROWS.may_load(deps.storage, (&channel_id, &match_id, &player))?;
+        ensure!(
+            escrow.is_some_and(|escrow| escrow.joined),
+            ContractError::Unauthorized {}
+        );
+
+        let packet = ChessPacket::MakeMove {
+            match_id: match_id.clone(),
+            player: player.to_string(),
+            move_fen: move_fen.clone(),
+        };
+
+        Ok(Response::new()
+            .add_attribute("action", "relay_move")
+            .add_attribute("sender", &player)
+            .add_message(IbcMsg::SendPacket {
+                channel_id: channel_id.clone(),
+                data: to_json_binary(&packet)?,
+                timeout: ibc_timeout(&env),
+            })
+            .add_event(
+                Event::new("ibc_move_sent")
+                    .add_attribute("channel_id", channel_id)
+                    .add_attribute("match_id", match_id)
+                    .add_attribute("move", move_fen),
+            ))
+    }
+
+    /// Starts a hosted match once its opponent escrowed their stake.
+    pub fn receive_ibc_join(
+        deps: DepsMut,
+        env: Env,
+        channel_id: String,
+        match_id: String,
+        player: String,
+        stake: Coin,
+        payout_address: String,
+    ) -> Result<Response, ContractError> {
+        let match_id = validate_match_id(&match_id)?;
+        let mut chess_match = lookup_match(&deps, match_id)?;
+        let mut opponent = lookup_ibc_opponent(&deps, match_id)?;
+        ensure!(
+            opponent.channel_id == channel_id && opponent.player == player,
+            ContractError::InvalidOpponent {}
+        );
+        ensure_eq!(
+            stake.denom,
+            opponent.stake.denom,
+            ContractError::InvalidBet {
+                reason: InvalidBetReason::WrongDenom,
+            }
+        );
+        validate_opponent_bet(&opponent.stake.amount, &stake.amount)?;
+        ensure_awaiting_opponent(&chess_match)?;
+        let payout_address = validate_address(deps.api, &payout_address)?;
+
+        chess_match.start(env.block.height);
+        save_match_state(deps.storage, match_id, &chess_match)?;
+        opponent.payout_address = Some(payout_address);
+        IBC_OPPONENTS.save(deps.storage, match_id, &opponent)?;
+        let notification = notify_bot(deps.storage, match_id, &chess_match, None)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "receive_ibc_join")
+            .add_attribute("player", player)
+            .add_event(Event::new("match_started").add_attribute("match_id", hex::encode(match_id)))
+            .add_submessages(notification))
+    }
+
+    /// Plays a move relayed from the opponent's chain.
+    pub fn receive_ibc_move(
+        deps: DepsMut,
+        env: Env,
+        channel_id: String,
+        match_id: String,
+        player: String,
+        move_fen: String,
+    ) -> Result<Response, ContractError> {
+        let match_id = validate_match_id(&match_id)?;
+        validate_fen_move(&move_fen)?;
+        let mut chess_match = lookup_match(&deps, match_id)?;
+        let opponent = lookup_ibc_opponent(&deps, match_id)?;
+        ensure!(
+            opponent.channel_id == channel_id && opponent.player == player,
+            ContractError::Unauthorized {}
+        );
+        let remote = env.contract.address.clone();
+        validate_match_state(&chess_match, &remote)?;
+
+        let mov = decode_move(&move_fen)?;
+        record_move(deps.storage, &env, match_id, &mut chess_match, &mov)?;
+
+        let (events, submsgs) = conclude_move(
+            deps.storage,
+            &env,
+            match_id,
+            chess_match,
+            remote.clone(),
+            &remote,
+            move_fen,
+        )?;
+        Ok(Response::new()
+            .add_attribute("action", "receive_ibc_move")
+            .add_attribute("player", player)
+            .add_events(events)
+            .add_submessages(submsgs))
+    }
+
+    /// Releases an escrowed stake as the host of its match settled it.
+    pub fn receive_ibc_settlement(
+        deps: DepsMut,
+        channel_id: String,
+        match_id: String,
+        player: String,
+        pay_to: String,
+    ) -> Result<Response, ContractError> {
+        let match_id = hex::encode(validate_match_id(&match_id)?);
+        let player = validate_address(deps.api, &player)?;
+        // The host only settles matches it accepted the player into, even if
+        // its acknowledgement of the join is yet to be relayed
+        let escrow = lookup_escrow(&deps, &channel_id, &match_id, &player)?;
+        // The stake is refunded rather than stuck if the payee can't be paid here
+        let pay_to = validate_address(deps.api, &pay_to).unwrap_or_else(|_| player.clone());
+        IBC_ESCROWS.remove(deps.storage, (&channel_id, &match_id, &player));
+
+        Ok(Response::new()
+            .add_attribute("action", "receive_ibc_settlement")
+            .add_message(BankMsg::Send {
+                to_address: pay_to.to_string(),
+                amount: vec![escrow.stake],
+            })
+            .add_event(
+                Event::new("ibc_match_settled")
+                    .add_attribute("channel_id", channel_id)
+                    .add_attribute("match_id", match_id)
+                    .add_attribute("pay_to", pay_to),
+            ))
+    }
+
+    pub fn ibc_join_accepted(
+        deps: DepsMut,
+        channel_id: String,
+        match_id: String,
+        player: String,
+    ) -> Result<Response, ContractError> {
+        let player = validate_address(deps.api, &player)?;
+        let key = (channel_id.as_str(), match_id.as_str(), &player);
+        // Already gone if the match was settled before this acknowledgement
+        if let Some(mut escrow) = IBC_ESCROWS.may_load(deps.storage, key)? {
+            escrow.joined = true;
+            IBC_ESCROWS.save(deps.storage, key, &escrow)?;
+        }
+
+        Ok(Response::new().add_event(
+            Event::new("ibc_join_accepted")
+                .add_attribute("channel_id", channel_id)
+                .add_attribute("match_id", match_id),
+        ))
+    }
+
+    /// Refunds the stake of a player the host didn't accept into its match.
+    pub fn ibc_join_failed(
+        deps: DepsMut,
+        channel_id: String,
+        match_id: String,
+        player: String,
+        error: String,
+    ) -> Result<Response, ContractError> {
+        let player = validate_address(deps.api, &player)?;
+        let escrow = lookup_escrow(&deps, &channel_id, &match_id, &player)?;
+        IBC_ESCROWS.remove(deps.storage, (&channel_id, &match_id, &player));
+
+        Ok(Response::new()
+            .add_message(BankMsg::Send {
+                to_address: escrow.player.to_string(),
+                amount: vec![escrow.stake],
+            })
+            .add_event(
+                Event::new("ibc_join_failed")
+                    .add_attribute("channel_id", channel_id)
+                    .add_attribute("match_id", match_id)
+                    .add_attribute("player", escrow.player)
+                    .add_attribute("error", error),
+            ))
+    }
+
+    /// Pays each stake of a cross-chain match on the chain holding it: the
+    /// challenger's here, the opponent's through a `SettleMatch` packet. No
+    /// result voids the match.
+    fn settle_ibc_stakes(
+        env: &Env,
+        match_id: MatchId,
+        chess_match: &Match,
+        opponent: &IbcOpponent,
+        result: Option<&MatchResult>,
+        msgs: &mut Vec<SubMsg>,
+    ) -> StdResult<()> {
+        let (local_payee, remote_payee) = match (result, &opponent.payout_address) {
+            (Some(MatchResult::WhiteWins), _) => (
+                chess_match.challenger.clone(),
+                opponent.challenger_payout.clone(),
+            ),
+            (Some(MatchResult::BlackWins), Some(payout_address)) => {
+                (payout_address.clone(), opponent.player.clone())
+            }
+            // Draws and voided matches refund both players
+            _ => (chess_match.challenger.clone(), opponent.player.clone()),
+        };
+
+        msgs.push(SubMsg::new(BankMsg::Send {
+            to_address: local_payee.to_string(),
+            amount: vec![chess_match.bet.clone()],
+        }));
+        msgs.push(SubMsg::new(IbcMsg::SendPacket {
+            channel_id: opponent.channel_id.clone(),
+            data: to_json_binary(&ChessPacket::SettleMatch {
+                match_id: hex::encode(match_id),
+                player: opponent.player.clone(),
+                pay_to: remote_payee,
+            })?,
+            timeout: ibc_timeout(env),
+        }));
+        Ok(())
+    }
+
+    pub fn add_ibc_peer(
+        deps: DepsMut,
+        info: MessageInfo,
+        connection_id: String,
+        port_id: String,
+    ) -> Result<Response, ContractError> {
+        ensure_admin(&deps, &info.sender)?;
+        IBC_PEERS.save(deps.storage, (&connection_id, &port_id), &())?;
+
+        Ok(Response::new()
+            .add_attribute("action", "add_ibc_peer")
+            .add_attribute("sender", info.sender)
+            .add_attribute("connection_id", connection_id)
+            .add_attribute("port_id", port_id))
+    }
+
+    pub fn remove_ibc_peer(
+        deps: DepsMut,
+        info: MessageInfo,
+        connection_id: String,
+        port_id: String,
+    ) -> Result<Response, ContractError> {
+        ensure_admin(&deps, &info.sender)?;
+        ensure!(
+            IBC_PEERS.has(deps.storage, (&connection_id, &port_id)),
+            ContractError::UnknownIbcPeer {}
+        );
+        IBC_PEERS.remove(deps.storage, (&connection_id, &port_id));
+
+        Ok(Response::new()
+            .add_attribute("action", "remove_ibc_peer")
+            .add_attribute("sender", info.sender)
+            .add_attribute("connection_id", connection_id)
+            .add_attribute("port_id", port_id))
+    }
+
+    fn ensure_channel(
+        storage: &dyn cosmwasm_std::Storage,
+        channel_id: &str,
+    ) -> Result<(), ContractError> {
+        ensure!(
+            IBC_CHANNELS.has(storage, channel_id),
+            ContractError::UnknownChannel {}
+        );
+        Ok(())
+    }
+
+    fn lookup_ibc_opponent(
+        deps: &DepsMut,
+        match_id: MatchId,
+    ) -> Result<IbcOpponent, ContractError> {
+        IBC_OPPONENTS
+            .may_load(deps.storage, match_id)?
+            .ok_or(ContractError::NotAnIbcMatch {})
+    }
+
+    fn lookup_escrow(
+        deps: &DepsMut,
+        channel_id: &str,
+        match_id: &str,
+        player: &Addr,
+    ) -> Result<IbcEscrow, ContractError> {
+        IBC_ESCROWS
+            .may_load(deps.storage, (channel_id, match_id, player))?
+            .ok_or(ContractError::UnknownEscrow {})
+    }
+
+    pub fn create_tournament(
+        deps: DepsMut,
+        info: MessageInfo,
+        kind: TournamentKind,
+        entry_fee: Coin,
+        max_players: u32,
+        time_control: MatchStyle,
+        prize_split: Vec<u32>,
+    ) -> Result<Response, ContractError> {
+        let organizer = info.sender;
+        validate_tournament(&kind, max_players, &prize_split)?;
+
+        let tournament = Tournament::new(
+            organizer.clone(),
+            kind,
+            entry_fee,
+            max_players,
+            time_control,
+            prize_split,
+        );
+        let tournament_id = next_tournament_id(deps.storage)?;
+        TOURNAMENTS.save(deps.storage, tournament_id, &tournament)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "create_tournament")
+            .add_attribute("sender", &organizer)
+            .add_event(
+                Event::new("tournament_created")
+                    .add_attribute("tournament_id", tournament_id.to_string())
+                    .add_attribute("organizer", organizer),
+            ))
+    }
+
+    pub fn register_tournament(
+        deps: DepsMut,
+        info: MessageInfo,
+        tournament_id: TournamentId,
+    ) -> Result<Response, ContractError> {
+        let player = info.sender;
+        let mut tournament = lookup_tournament(&deps, tournament_id)?;
+        ensure_eq!(
+            tournament.state,
+            TournamentState::Registration,
+            ContractError::RegistrationClosed {}
+        );
+        ensure!(!tournament.is_full(), ContractError::TournamentFull {});
+        ensure!(
+            !tournament.players.contains(&player),
+            ContractError::AlreadyRegistered {}
+        );
+        validate_entry_fee(&info.funds, &tournament.entry_fee)?;
+
+        tournament.players.push(player.clone());
+        TOURNAMENTS.save(deps.storage, tournament_id, &tournament)?;
+        STANDINGS.save(
+            deps.storage,
+            (tournament_id, &player),
+            &Standing::new(player.clone()),
+        )?;
+
+        Ok(Response::new()
+            .add_attribute("action", "register_tournament")
+            .add_attribute("sender", &player)
+            .add_event(
+                Event::new("tournament_registered")
+                    .add_attribute("tournament_id", tournament_id.to_string())
+                    .add_attribute("player", player),
+            ))
+    }
+
+    pub fn cancel_tournament(
+        deps: DepsMut,
+        info: MessageInfo,
+        tournament_id: TournamentId,
+    ) -> Result<Response, ContractError> {
+        let sender = info.sender;
+        let mut tournament = lookup_tournament(&deps, tournament_id)?;
+        ensure!(
+            tournament.organizer == sender || ADMIN.load(deps.storage)? == sender,
+            ContractError::NotTournamentOrganizer {}
+        );
+        ensure_eq!(
+            tournament.state,
+            TournamentState::Registration,
+            ContractError::RegistrationClosed {}
+        );
+
+        let mut msgs: Vec<SubMsg> = vec![];
+        for player in &tournament.players {
+            STANDINGS.remove(deps.storage, (tournament_id, player));
+            if !tournament.entry_fee.amount.is_zero() {
+                msgs.push(SubMsg::new(BankMsg::Send {
+                    to_address: player.to_string(),
+                    amount: vec![tournament.entry_fee.clone()],
+                }));
+            }
+        }
+        tournament.state = TournamentState::Cancelled;
+        TOURNAMENTS.save(deps.storage, tournament_id, &tournament)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "cancel_tournament")
+            .add_attribute("sender", &sender)
+            .add_event(
+                Event::new("tournament_cancelled")
+                    .add_attribute("tournament_id", tournament_id.to_string())
+                    .add_attribute("entrants", tournament.players.len().to_string()),
+            )
+            .add_submessages(msgs))
+    }
+
+    pub fn start_tournament(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        tournament_id: TournamentId,
+        seed: Option<String>,
+    ) -> Result<Response, ContractError> {
+        let mut tournament = lookup_tournament(&deps, tournament_id)?;
+        ensure_eq!(
+            tournament.organizer,
+            info.sender,
+            ContractError::NotTournamentOrganizer {}
+        );
+        ensure_eq!(
+            tournament.state,
+            TournamentState::Registration,
+            ContractError::RegistrationClosed {}
+        );
+        ensure!(
+            tournament.players.len() >= 2,
+            ContractError::NotEnoughPlayers {}
+        );
+        match tournament.kind {
+            TournamentKind::Swiss { rounds } => ensure!(
+                (rounds as usize) < tournament.players.len(),
+                ContractError::NotEnoughPlayers {}
+            ),
+            TournamentKind::Knockout { .. } => ensure!(
+                bracket_rounds(tournament.players.len()).is_some(),
+                ContractError::InvalidTournament {
+                    reason: InvalidTournamentReason::BracketSize,
+                }
+            ),
+            TournamentKind::RoundRobin => {}
+        }
+        let draw_seed = match &tournament.kind {
+            TournamentKind::Knockout {
+                seeding:
+                    Seeding::Random {
+                        seed_commitment: commitment,
+                    },
+            } => {
+                let seed = seed.ok_or(ContractError::InvalidSeed {})?;
+                ensure_eq!(
+                    &seed_commitment(&seed),
+                    commitment,
+                    ContractError::InvalidSeed {}
+                );
+                Some(
+                    Sha256::digest([seed.as_bytes(), &tournament_id.to_be_bytes()].concat()).into(),
+                )
+            }
+            _ => None,
+        };
+
+        let mut events = vec![Event::new("tournament_started")
+            .add_attribute("tournament_id", tournament_id.to_string())];
+        tournament.state = TournamentState::OnGoing;
+        let mut msgs: Vec<SubMsg> = vec![];
+        pair_round(
+            deps.storage,
+            &env,
+            tournament_id,
+            &mut tournament,
+            draw_seed,
+            &mut msgs,
+            &mut events,
+        )?;
+        TOURNAMENTS.save(deps.storage, tournament_id, &tournament)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "start_tournament")
+            .add_attribute("sender", info.sender)
+            .add_events(events))
+    }
+
+    pub fn pair_next_round(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        tournament_id: TournamentId,
+    ) -> Result<Response, ContractError> {
+        let mut tournament = lookup_tournament(&deps, tournament_id)?;
+        ensure_eq!(
+            tournament.state,
+            TournamentState::OnGoing,
+            ContractError::TournamentNotOnGoing {}
+        );
+        ensure!(
+            tournament.needs_pairing(),
+            ContractError::RoundInProgress {}
+        );
+
+        let mut msgs: Vec<SubMsg> = vec![];
+        let mut events = vec![];
+        pair_round(
+            deps.storage,
+            &env,
+            tournament_id,
+            &mut tournament,
+            None,
+            &mut msgs,
+            &mut events,
+        )?;
+        TOURNAMENTS.save(deps.storage, tournament_id, &tournament)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "pair_next_round")
+            .add_attribute("sender", info.sender)
+            .add_events(events)
+            .add_submessages(msgs))
+    }
+
+    /// Creates the matches of the next round. Round robin tournaments are played
+    /// in a single round, Swiss rounds are paired from the current standings and
+    /// knockout brackets are seeded once, then advance as ties are decided.
+    /// `draw_seed` drives the draw of randomly seeded brackets.
+    fn pair_round(
+        storage: &mut dyn cosmwasm_std::Storage,
+        env: &Env,
+        tournament_id: TournamentId,
+        tournament: &mut Tournament,
+        draw_seed: Option<[u8; 32]>,
+        msgs: &mut Vec<SubMsg>,
+        events: &mut Vec<Event>,
+    ) -> Result<(), ContractError> {
+        tournament.round += 1;
+        let mut round_event = Event::new("tournament_round")
+            .add_attribute("tournament_id", tournament_id.to_string())
+            .add_attribute("round", tournament.round.to_string());
+
+        let pairings = match tournament.kind.clone() {
+            TournamentKind::Knockout { seeding } => {
+                events.push(round_event);
+                return seed_bracket(
+                    storage,
+                    env,
+                    tournament_id,
+                    tournament,
+                    &seeding,
+                    draw_seed,
+                    events,
+                );
+            }
+            TournamentKind::RoundRobin => round_robin_pairings(&tournament.players),
+            TournamentKind::Swiss { .. } => {
+                let standings = load_standings(storage, tournament_id)?;
+                let paired = match swiss_pairings(&tournament.players, &standings, tournament.round)
+                {
+                    Ok(paired) => paired,
+                    // The search gave up before finding a pairing, rather than
+                    // ending early settle for the fewest rematches
+                    Err(PairingLimitExceeded) => {
+                        round_event = round_event.add_attribute("pairing", "greedy");
+                        Some(greedy_swiss_pairings(
+                            &tournament.players,
+                            &standings,
+                            tournament.round,
+                        ))
+                    }
+                };
+                match paired {
+                    Some((pairings, bye)) => {
+                        if let Some(player) = bye {
+                            let mut standing = STANDINGS.load(storage, (tournament_id, &player))?;
+                            standing.record_bye();
+                            STANDINGS.save(storage, (tournament_id, &player), &standing)?;
+                            round_event = round_event.add_attribute("bye", player);
+                        }
+                        pairings
+                    }
+                    None => {
+                        // Every remaining pairing would be a rematch, end the tournament early
+                        events.push(round_event.add_attribute("pairing", "exhausted"));
+                        return Ok(finish_tournament(
+                            storage,
+                            tournament_id,
+                            tournament,
+                            msgs,
+                            events,
+                        )?);
+                    }
+                }
+            }
+        };
+        events.push(round_event);
+
+        for (white, black) in pairings {
+            let match_id =
+                start_tournament_match(storage, env, tournament_id, tournament, &white, &black)?;
+            events.push(
+                Event::new("match_started")
+                    .add_attribute("match_id", hex::encode(match_id))
+                    .add_attribute("tournament_id", tournament_id.to_string()),
+            );
+            tournament.pending_games += 1;
+        }
+        Ok(())
+    }
+
+    fn seed_bracket(
+        storage: &mut dyn cosmwasm_std::Storage,
+        env: &Env,
+        tournament_id: TournamentId,
+        tournament: &mut Tournament,
+        seeding: &Seeding,
+        draw_seed: Option<[u8; 32]>,
+        events: &mut Vec<Event>,
+    ) -> Result<(), ContractError> {
+        let mut seeds = tournament.players.clone();
+        match seeding {
+            Seeding::Rating => {
+                let mut ratings = vec![];
+                for player in seeds.iter() {
+                    ratings.push(load_rating(storage, player)?);
+                }
+                // Stable sort, equally rated players keep their registration order
+                let mut rated: Vec<(u32, Addr)> = ratings.into_iter().zip(seeds).collect();
+                rated.sort_by(|(a, _), (b, _)| b.cmp(a));
+                seeds = rated.into_iter().map(|(_, player)| player).collect();
+            }
+            Seeding::Random { .. } => {
+                let seed = draw_seed.ok_or(ContractError::InvalidSeed {})?;
+                shuffle(&mut seeds, seed);
+            }
+        }
+
+        let rounds = bracket_rounds(seeds.len()).ok_or(ContractError::InvalidTournament {
+            reason: InvalidTournamentReason::BracketSize,
+        })?;
+        let order = bracket_order(seeds.len());
+        for round in 1..=rounds {
+            for slot in 0..(seeds.len() >> round) as u32 {
+                let mut tie = BracketTie::new(round, slot);
+                if round == 1 {
+                    tie.top = Some(seeds[order[2 * slot as usize]].clone());
+                    tie.bottom = Some(seeds[order[2 * slot as usize + 1]].clone());
+                    start_tie_game(storage, env, tournament_id, tournament, &mut tie, events)?;
+                }
+                BRACKET.save(storage, (tournament_id, round, slot), &tie)?;
+            }
+        }
+        Ok(())
+    }
 
-        let submsgs: Vec<SubMsg<_>> = msgs.into_iter().map(SubMsg::new).collect();
-        Ok(Response::new()
-            .add_attribute("action", "abort_match")
-            .add_attribute("sender", &challenger)
-            .add_event(Event::new("match_aborted").add_attribute("match_id", hex::encode(match_id)))
-            .add_submessages(submsgs))
+    fn start_tie_game(
+        storage: &mut dyn cosmwasm_std::Storage,
+        env: &Env,
+        tournament_id: TournamentId,
+        tournament: &mut Tournament,
+        tie: &mut BracketTie,
+        events: &mut Vec<Event>,
+    ) -> StdResult<()> {
+        if let Some((kind, white, black)) = tie.next_game() {
+            let match_id =
+                start_tournament_match(storage, env, tournament_id, tournament, &white, &black)?;
+            BRACKET_MATCHES.save(storage, match_id, &(tie.round, tie.slot))?;
+            tie.games.push(TieGame {
+                match_id: hex::encode(match_id),
+                kind,
+                white,
+                black,
+                result: None,
+            });
+            events.push(
+                Event::new("match_started")
+                    .add_attribute("match_id", hex::encode(match_id))
+                    .add_attribute("tournament_id", tournament_id.to_string()),
+            );
+            tournament.round = tournament.round.max(tie.round);
+            tournament.pending_games += 1;
+        }
+        Ok(())
     }
 
-    pub fn join_match(
-        deps: DepsMut,
-        env: Env,
-        info: MessageInfo,
-        match_id: String,
-    ) -> Result<Response, ContractError> {
-        let opponent = info.sender;
+    /// Records a knockout game: a drawn game is followed by a tiebreak game,
+    /// a decided tie sends its winner to the next round or ends the tournament.
+    #[allow(clippy::too_many_arguments)]
+    fn advance_bracket(
+        storage: &mut dyn cosmwasm_std::Storage,
+        env: &Env,
+        tournament_id: TournamentId,
+        tournament: &mut Tournament,
+        match_id: MatchId,
+        result: &MatchResult,
+        msgs: &mut Vec<SubMsg>,
+        events: &mut Vec<Event>,
+    ) -> Result<(), ContractError> {
+        let (round, slot) = BRACKET_MATCHES.load(storage, match_id)?;
+        BRACKET_MATCHES.remove(storage, match_id);
 
-        let match_id = validate_match_id(&match_id)?;
-        let mut chess_match = lookup_match(&deps, match_id)?;
-        validate_match_opponent(&opponent, &chess_match.opponent)?;
+        let mut tie = BRACKET.load(storage, (tournament_id, round, slot))?;
+        match tie.record(result) {
+            None => start_tie_game(storage, env, tournament_id, tournament, &mut tie, events)?,
+            Some(winner) => {
+                events.push(
+                    Event::new("bracket_tie_decided")
+                        .add_attribute("tournament_id", tournament_id.to_string())
+                        .add_attribute("round", round.to_string())
+                        .add_attribute("slot", slot.to_string())
+                        .add_attribute("winner", &winner),
+                );
 
-        let bet = validate_bet(&info.funds, &chess_match.bet)?;
-        validate_opponent_bet(&chess_match.bet.amount, &bet.amount)?;
-        ensure_awaiting_opponent(&chess_match)?;
+                let next = (tournament_id, round + 1, slot / 2);
+                match BRACKET.may_load(storage, next)? {
+                    Some(mut next_tie) => {
+                        if slot % 2 == 0 {
+                            next_tie.top = Some(winner);
+                        } else {
+                            next_tie.bottom = Some(winner);
+                        }
+                        start_tie_game(
+                            storage,
+                            env,
+                            tournament_id,
+                            tournament,
+                            &mut next_tie,
+                            events,
+                        )?;
+                        BRACKET.save(storage, next, &next_tie)?;
+                    }
+                    None => {
+                        // The final was just decided
+                        BRACKET.save(storage, (tournament_id, round, slot), &tie)?;
+                        return Ok(finish_tournament(
+                            storage,
+                            tournament_id,
+                            tournament,
+                            msgs,
+                            events,
+                        )?);
+                    }
+                }
+            }
+        }
+        BRACKET.save(storage, (tournament_id, round, slot), &tie)?;
+        Ok(())
+    }
 
+    fn start_tournament_match(
+        storage: &mut dyn cosmwasm_std::Storage,
+        env: &Env,
+        tournament_id: TournamentId,
+        tournament: &Tournament,
+        white: &Addr,
+        black: &Addr,
+    ) -> StdResult<MatchId> {
+        let nonce = NEXT_NONCE.load(storage)?;
+        let mut chess_match = Match::new(
+            white.clone(),
+            black.clone(),
+            nonce,
+            Coin::new(0, &tournament.entry_fee.denom),
+        );
+        chess_match.style = tournament.time_control.clone();
+        chess_match.tournament = Some(tournament_id);
         chess_match.start(env.block.height);
-        save_match_state(deps.storage, match_id, &chess_match)?;
+        register_match(storage, &chess_match)
+    }
 
-        Ok(Response::new()
-            .add_attribute("action", "join_match")
-            .add_attribute("sender", &opponent)
-            .add_event(
-                Event::new("match_started").add_attribute("match_id", hex::encode(match_id)),
-            ))
+    /// Pays out a finished match (or records it in its tournament) and cleans up storage.
+    fn settle_match(
+        storage: &mut dyn cosmwasm_std::Storage,
+        env: &Env,
+        match_id: MatchId,
+        chess_match: &Match,
+        result: &MatchResult,
+        msgs: &mut Vec<SubMsg>,
+        events: &mut Vec<Event>,
+    ) -> Result<(), ContractError> {
+        // Only plain matches between two players can be rematched, checked
+        // before the links of the others are cleared
+        let rematchable = chess_match.tournament.is_none()
+            && !VOTE_GROUPS.has(storage, match_id)
+            && !IBC_OPPONENTS.has(storage, match_id)
+            && !TEAM_BOARDS.has(storage, match_id);
+        match chess_match.tournament {
+            Some(tournament_id) => record_tournament_result(
+                storage,
+                env,
+                tournament_id,
+                match_id,
+                chess_match,
+                result,
+                msgs,
+                events,
+            )?,
+            // Vote matches and team boards are unstaked
+            None if chess_match.bet.amount.is_zero() => {}
+            // The opponent's stake of a cross-chain match is on their chain
+            None if IBC_OPPONENTS.has(storage, match_id) => {
+                let opponent = IBC_OPPONENTS.load(storage, match_id)?;
+                settle_ibc_stakes(env, match_id, chess_match, &opponent, Some(result), msgs)?
+            }
+            // Winner gets both deposits
+            // TODO: contract should take a fee (e.g. 1% of the total bet),
+            // to be sent to the contract owner (most likely a DAO treasury),
+            // and send the rest to the winner.
+            None => match result {
+                MatchResult::WhiteWins => {
+                    transfer_pot_to_winner(msgs, chess_match, &chess_match.challenger)
+                }
+                MatchResult::BlackWins => {
+                    transfer_pot_to_winner(msgs, chess_match, &chess_match.opponent)
+                }
+                MatchResult::Draw => refund_players(msgs, chess_match),
+            },
+        }
+
+        if chess_match.rated {
+            record_rating(storage, chess_match, result)?;
+            record_season_result(storage, chess_match, result)?;
+        }
+        record_player_stats(storage, &env.contract.address, chess_match, result)?;
+        record_team_board(storage, match_id, result, msgs, events)?;
+        settle_side_bets(storage, match_id, Some(result), msgs, events)?;
+        notify_hooks(storage, match_id, chess_match, result, msgs)?;
+        if rematchable {
+            prune_finished_matches(storage, env.block.height)?;
+            FINISHED_MATCHES.save(
+                storage,
+                match_id,
+                &FinishedMatch {
+                    white: chess_match.challenger.clone(),
+                    black: chess_match.opponent.clone(),
+                    bet: chess_match.bet.clone(),
+                    style: chess_match.style.clone(),
+                    rated: chess_match.rated,
+                    finished: env.block.height,
+                    rematch_offer: None,
+                },
+            )?;
+            FINISHED_BY_HEIGHT.save(storage, (env.block.height, match_id), &())?;
+        }
+
+        // Match is over, clean up storage
+        clean_match_state(storage, match_id, chess_match);
+        Ok(())
     }
 
-    pub fn make_move(
-        deps: DepsMut,
-        env: Env,
-        info: MessageInfo,
-        match_id: String,
-        move_fen: String,
-    ) -> Result<Response, ContractError> {
-        let player = info.sender;
-        let match_id = validate_match_id(&match_id)?;
-        validate_fen_move(&move_fen)?;
+    #[allow(clippy::too_many_arguments)]
+    fn record_tournament_result(
+        storage: &mut dyn cosmwasm_std::Storage,
+        env: &Env,
+        tournament_id: TournamentId,
+        match_id: MatchId,
+        chess_match: &Match,
+        result: &MatchResult,
+        msgs: &mut Vec<SubMsg>,
+        events: &mut Vec<Event>,
+    ) -> Result<(), ContractError> {
+        let mut tournament = TOURNAMENTS.load(storage, tournament_id)?;
+        let (white, black) = (&chess_match.challenger, &chess_match.opponent);
+        let (white_score, black_score) = game_scores(result);
 
-        let mut chess_match = lookup_match(&deps, match_id)?;
-        validate_match_state(&chess_match, &player)?;
+        let mut standing = STANDINGS.load(storage, (tournament_id, white))?;
+        standing.record(black, Color::White, white_score);
+        STANDINGS.save(storage, (tournament_id, white), &standing)?;
 
-        let mov = decode_move(&move_fen)?;
-        let chess_match = chess_match
-            .play_move(&mov, env.block.height)
-            .map_err(|_| ContractError::IllegalMove {})?;
+        let mut standing = STANDINGS.load(storage, (tournament_id, black))?;
+        standing.record(white, Color::Black, black_score);
+        STANDINGS.save(storage, (tournament_id, black), &standing)?;
 
-        let mut msgs: Vec<CosmosMsg> = vec![];
-        let mut events = vec![Event::new("move_executed")
-            .add_attribute("match_id", hex::encode(match_id))
-            .add_attribute("player", &player)
-            .add_attribute("move", move_fen)];
+        tournament.pending_games = tournament.pending_games.saturating_sub(1);
+        if let TournamentKind::Knockout { .. } = tournament.kind {
+            advance_bracket(
+                storage,
+                env,
+                tournament_id,
+                &mut tournament,
+                match_id,
+                result,
+                msgs,
+                events,
+            )?;
+        } else if tournament.pending_games == 0 && !tournament.needs_pairing() {
+            finish_tournament(storage, tournament_id, &mut tournament, msgs, events)?;
+        }
+        TOURNAMENTS.save(storage, tournament_id, &tournament)?;
+        Ok(())
+    }
 
-        if chess_match.state == MatchState::Won {
-            // Match was won with move that was just executed
-            events.push(
-                Event::new("match_won")
-                    .add_attribute("match_id", hex::encode(match_id))
-                    .add_attribute("winner", &player)
-                    .add_attribute("board", chess_match.board()),
-            );
+    /// A player's all-time rating, see `RATINGS`.
+    pub(crate) fn load_rating(
+        storage: &dyn cosmwasm_std::Storage,
+        player: &Addr,
+    ) -> StdResult<u32> {
+        Ok(RATINGS.may_load(storage, player)?.unwrap_or(DEFAULT_RATING))
+    }
 
-            // Winner gets both deposits
-            // TODO: contract should take a fee (e.g. 1% of the total bet),
-            // to be sent to the contract owner (most likely a DAO treasury),
-            // and send the rest to the winner.
-            transfer_pot_to_winner(&mut msgs, chess_match, &player);
+    fn finish_tournament(
+        storage: &dyn cosmwasm_std::Storage,
+        tournament_id: TournamentId,
+        tournament: &mut Tournament,
+        msgs: &mut Vec<SubMsg>,
+        events: &mut Vec<Event>,
+    ) -> StdResult<()> {
+        let ranking: Vec<Vec<Addr>> = match tournament.kind {
+            TournamentKind::Knockout { .. } => {
+                let rounds = bracket_rounds(tournament.players.len()).unwrap_or_default();
+                knockout_ranking(&load_bracket(storage, tournament_id)?, rounds)
+            }
+            TournamentKind::RoundRobin | TournamentKind::Swiss { .. } => {
+                let standings = load_standings(storage, tournament_id)?;
+                rank_standings(&tournament.kind, &tournament.players, &standings)
+                    .into_iter()
+                    .map(|standing| vec![standing.player])
+                    .collect()
+            }
+        };
+        let shares = prize_shares(tournament.prize_pool(), &tournament.prize_split, &ranking);
 
-            // TODO: update elo rating
+        let mut event = Event::new("tournament_finished")
+            .add_attribute("tournament_id", tournament_id.to_string());
+        let mut rank = 1;
+        for group in ranking.iter() {
+            for player in group {
+                event = event.add_attribute(format!("rank_{}", rank), player);
+            }
+            rank += group.len();
+        }
+        for (player, amount) in shares {
+            if !amount.is_zero() {
+                msgs.push(SubMsg::new(BankMsg::Send {
+                    to_address: player.to_string(),
+                    amount: vec![Coin::new(amount.u128(), &tournament.entry_fee.denom)],
+                }));
+            }
+        }
+        events.push(event);
 
-            // Match is over, clean up storage
-            clean_match_state(deps.storage, match_id, chess_match);
-        } else if chess_match.state == MatchState::Drawn {
-            // Match drawn, refund deposits to both players
-            events.push(
-                Event::new("match_drawn")
-                    .add_attribute("match_id", hex::encode(match_id))
-                    .add_attribute("board", chess_match.board()),
-            );
+        tournament.state = TournamentState::Finished;
+        Ok(())
+    }
 
-            refund_players(&mut msgs, chess_match);
+    pub(crate) fn load_standings(
+        storage: &dyn cosmwasm_std::Storage,
+        tournament_id: TournamentId,
+    ) -> StdResult<Vec<Standing>> {
+        STANDINGS
+            .prefix(tournament_id)
+            .range(storage, None, None, Order::Ascending)
+            .map(|item| item.map(|(_, standing)| standing))
+            .collect()
+    }
 
-            // TODO: update elo rating
+    pub(crate) fn load_bracket(
+        storage: &dyn cosmwasm_std::Storage,
+        tournament_id: TournamentId,
+    ) -> StdResult<Vec<BracketTie>> {
+        BRACKET
+            .sub_prefix(tournament_id)
+            .range(storage, None, None, Order::Ascending)
+            .map(|item| item.map(|(_, tie)| tie))
+            .collect()
+    }
 
-            // Match is over, clean up storage
-            clean_match_state(deps.storage, match_id, chess_match);
-        } else {
-            // match still ongoing, update on-chain board
-            save_match_state(deps.storage, match_id, chess_match)?;
+    fn lookup_tournament(
+        deps: &DepsMut,
+        tournament_id: TournamentId,
+    ) -> Result<Tournament, ContractError> {
+        match TOURNAMENTS.load(deps.storage, tournament_id) {
+            Ok(t) => Ok(t),
+            Err(_) => Err(ContractError::UnknownTournament {}),
         }
+    }
 
-        let submsgs: Vec<SubMsg<_>> = msgs.into_iter().map(SubMsg::new).collect();
-        Ok(Response::new()
-            .add_attribute("action", "make_move")
-            .add_attribute("sender", &player)
-            .add_events(events)
-            .add_submessages(submsgs))
+    /// Stores a new match under a fresh nonce-derived ID and indexes it for both players.
+    fn register_match(
+        storage: &mut dyn cosmwasm_std::Storage,
+        chess_match: &Match,
+    ) -> StdResult<MatchId> {
+        let match_id = match_id(
+            &chess_match.challenger,
+            &chess_match.opponent,
+            chess_match.nonce,
+        );
+        save_match_state(storage, match_id, chess_match)?;
+        save_player_match(storage, &chess_match.challenger, match_id)?;
+        save_player_match(storage, &chess_match.opponent, match_id)?;
+        save_match_id(storage, chess_match.nonce, match_id)?;
+        increment_nonce(storage)?;
+        Ok(match_id)
     }
 
     fn clean_match_state(
@@ -217,9 +3709,27 @@ pub(crate) mod exec {
         chess_match: &Match,
     ) {
         MATCHES.remove(storage, match_id);
+        for ply in 0..chess_match.plies() {
+            MATCH_MOVES.remove(storage, (match_id.as_slice(), ply));
+        }
         PLAYER_MATCHES.remove(storage, (&chess_match.challenger, match_id));
         PLAYER_MATCHES.remove(storage, (&chess_match.opponent, match_id));
         MATCH_IDS.remove(storage, chess_match.nonce);
+        DRAW_OFFERS.remove(storage, match_id);
+        TAKEBACK_REQUESTS.remove(storage, match_id);
+        MATCH_ARBITERS.remove(storage, match_id);
+        VOTE_GROUPS.remove(storage, match_id);
+        IBC_OPPONENTS.remove(storage, match_id);
+        clear_conditional_moves(storage, match_id, chess_match);
+    }
+
+    fn clear_conditional_moves(
+        storage: &mut dyn cosmwasm_std::Storage,
+        match_id: MatchId,
+        chess_match: &Match,
+    ) {
+        CONDITIONAL_MOVES.remove(storage, (match_id.as_slice(), &chess_match.challenger));
+        CONDITIONAL_MOVES.remove(storage, (match_id.as_slice(), &chess_match.opponent));
     }
 
     fn save_match_id(
@@ -291,26 +3801,26 @@ pub(crate) mod exec {
         }
     }
 
-    fn refund_challenger(msgs: &mut Vec<CosmosMsg>, chess_match: &Match) {
-        msgs.push(CosmosMsg::Bank(BankMsg::Send {
+    fn refund_challenger(msgs: &mut Vec<SubMsg>, chess_match: &Match) {
+        msgs.push(SubMsg::new(BankMsg::Send {
             to_address: chess_match.challenger.to_string(),
             amount: vec![chess_match.bet.clone()],
         }));
     }
 
-    fn refund_players(msgs: &mut Vec<CosmosMsg>, chess_match: &Match) {
-        msgs.push(CosmosMsg::Bank(BankMsg::Send {
+    fn refund_players(msgs: &mut Vec<SubMsg>, chess_match: &Match) {
+        msgs.push(SubMsg::new(BankMsg::Send {
             to_address: chess_match.challenger.to_string(),
             amount: vec![chess_match.bet.clone()],
         }));
-        msgs.push(CosmosMsg::Bank(BankMsg::Send {
+        msgs.push(SubMsg::new(BankMsg::Send {
             to_address: chess_match.opponent.to_string(),
             amount: vec![chess_match.bet.clone()],
         }));
     }
 
-    fn transfer_pot_to_winner(msgs: &mut Vec<CosmosMsg>, chess_match: &Match, winner: &Addr) {
-        msgs.push(CosmosMsg::Bank(BankMsg::Send {
+    fn transfer_pot_to_winner(msgs: &mut Vec<SubMsg>, chess_match: &Match, winner: &Addr) {
+        msgs.push(SubMsg::new(BankMsg::Send {
             to_address: winner.to_string(),
             amount: vec![Coin::new(
                 chess_match.bet.amount.u128() * 2,
@@ -356,7 +3866,120 @@ pub(crate) mod exec {
     }
 
     #[inline(always)]
-    fn validate_fen_move(move_fen: &str) -> Result<(), ContractError> {
+    fn ensure_ongoing(chess_match: &Match) -> Result<(), ContractError> {
+        match chess_match.state {
+            MatchState::AwaitingOpponent => Err(ContractError::StillAwaitingOpponent {}),
+            MatchState::Won | MatchState::Drawn => Err(ContractError::MatchAlreadyFinished {}),
+            MatchState::OnGoing(_) => Ok(()),
+        }
+    }
+
+    #[inline(always)]
+    fn ensure_admin(deps: &DepsMut, addr: &Addr) -> Result<(), ContractError> {
+        ensure_eq!(
+            ADMIN.load(deps.storage)?,
+            addr,
+            ContractError::Unauthorized {}
+        );
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn validate_entry_fee(funds: &[Coin], entry_fee: &Coin) -> Result<(), ContractError> {
+        if entry_fee.amount.is_zero() {
+            ensure!(
+                funds.is_empty(),
+                ContractError::InvalidBet {
+                    reason: InvalidBetReason::TooManyCoins,
+                }
+            );
+            return Ok(());
+        }
+
+        let fee = validate_bet(funds, entry_fee)?;
+        validate_opponent_bet(&entry_fee.amount, &fee.amount)
+    }
+
+    fn validate_team(
+        api: &dyn cosmwasm_std::Api,
+        team: Vec<Addr>,
+    ) -> Result<Vec<Addr>, ContractError> {
+        team.iter()
+            .map(|player| validate_address(api, player.as_str()))
+            .collect()
+    }
+
+    #[inline(always)]
+    fn validate_team_match(
+        team_a: &[Addr],
+        team_b: &[Addr],
+        boards: u32,
+    ) -> Result<(), ContractError> {
+        if !(1..=MAX_BOARDS).contains(&boards) {
+            return Err(ContractError::InvalidTeamMatch {
+                reason: InvalidTeamMatchReason::BoardCount,
+            });
+        }
+        if team_a.len() < boards as usize || team_b.len() < boards as usize {
+            return Err(ContractError::InvalidTeamMatch {
+                reason: InvalidTeamMatchReason::TeamTooSmall,
+            });
+        }
+        let players: Vec<&Addr> = team_a.iter().chain(team_b).collect();
+        if (1..players.len()).any(|i| players[..i].contains(&players[i])) {
+            return Err(ContractError::InvalidTeamMatch {
+                reason: InvalidTeamMatchReason::DuplicatePlayer,
+            });
+        }
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn validate_tournament(
+        kind: &TournamentKind,
+        max_players: u32,
+        prize_split: &[u32],
+    ) -> Result<(), ContractError> {
+        if max_players < 2 {
+            return Err(ContractError::InvalidTournament {
+                reason: InvalidTournamentReason::TooFewPlayers,
+            });
+        }
+        match kind {
+            TournamentKind::Swiss { rounds } if *rounds == 0 || *rounds >= max_players => {
+                return Err(ContractError::InvalidTournament {
+                    reason: InvalidTournamentReason::InvalidRounds,
+                });
+            }
+            TournamentKind::Knockout { .. } if bracket_rounds(max_players as usize).is_none() => {
+                return Err(ContractError::InvalidTournament {
+                    reason: InvalidTournamentReason::BracketSize,
+                });
+            }
+            TournamentKind::Knockout {
+                seeding: Seeding::Random { seed_commitment },
+            } if !matches!(hex::decode(seed_commitment), Ok(hash) if hash.len() == 32) => {
+                return Err(ContractError::InvalidTournament {
+                    reason: InvalidTournamentReason::SeedCommitment,
+                });
+            }
+            _ => {}
+        }
+        if prize_split.iter().sum::<u32>() != PRIZE_SPLIT_TOTAL {
+            return Err(ContractError::InvalidTournament {
+                reason: InvalidTournamentReason::PrizeSplitTotal,
+            });
+        }
+        if prize_split.len() > max_players as usize {
+            return Err(ContractError::InvalidTournament {
+                reason: InvalidTournamentReason::TooManyPrizes,
+            });
+        }
+        Ok(())
+    }
+
+    #[inline(always)]
+    pub(crate) fn validate_fen_move(move_fen: &str) -> Result<(), ContractError> {
         ensure_eq!(
             move_fen.len(),
             MOVE_FEN_LENGTH,
@@ -436,13 +4059,355 @@ pub(crate) mod exec {
     }
 }
 
-pub fn query(_deps: Deps, _env: Env, _msg: QueryMsg) -> StdResult<Binary> {
-    unimplemented!()
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    use QueryMsg::*;
+
+    match msg {
+        Match { match_id } => to_json_binary(&query::chess_match(deps, match_id)?),
+        Tournament { tournament_id } => to_json_binary(&query::tournament(deps, tournament_id)?),
+        Standings { tournament_id } => to_json_binary(&query::standings(deps, tournament_id)?),
+        Bracket { tournament_id } => to_json_binary(&query::bracket(deps, tournament_id)?),
+        SideBetMarket { match_id } => to_json_binary(&query::side_bet_market(deps, match_id)?),
+        Hooks {} => to_json_binary(&query::hooks(deps)?),
+        Arbiter { match_id } => to_json_binary(&query::arbiter(deps, match_id)?),
+        PlayerStats { player } => to_json_binary(&query::player_stats(deps, player)?),
+        HeadToHead { a, b } => to_json_binary(&query::head_to_head(deps, a, b)?),
+        Profile { address } => to_json_binary(&query::profile(deps, address)?),
+        ResolveNickname { nickname } => to_json_binary(&query::resolve_nickname(deps, nickname)?),
+        CurrentSeason {} => to_json_binary(&query::current_season(deps)?),
+        Season { season_id } => to_json_binary(&query::season(deps, season_id)?),
+        SeasonLeaderboard {
+            season_id,
+            start_after,
+            limit,
+        } => to_json_binary(&query::season_leaderboard(
+            deps,
+            season_id,
+            start_after,
+            limit,
+        )?),
+        Puzzle { puzzle_id } => to_json_binary(&query::puzzle(deps, puzzle_id)?),
+        ChallengePolicy { player } => to_json_binary(&query::challenge_policy(deps, player)?),
+        Votes { match_id } => to_json_binary(&query::votes(deps, match_id)?),
+        TeamMatch { team_match_id } => to_json_binary(&query::team_match(deps, team_match_id)?),
+        IbcOpponent { match_id } => to_json_binary(&query::ibc_opponent(deps, match_id)?),
+        IbcEscrow {
+            channel_id,
+            match_id,
+            player,
+        } => to_json_binary(&query::ibc_escrow(deps, channel_id, match_id, player)?),
+        IbcPeers {} => to_json_binary(&query::ibc_peers(deps)?),
+        ValidateLine { start_fen, moves } => {
+            to_json_binary(&query::validate_line(env, start_fen, moves)?)
+        }
+    }
+}
+
+pub(crate) mod query {
+    use super::*;
+
+    pub fn chess_match(deps: Deps, match_id: String) -> StdResult<MatchResponse> {
+        let mut bytes = [0u8; 32];
+        hex::decode_to_slice(match_id, &mut bytes)
+            .map_err(|_| StdError::generic_err("Invalid match ID"))?;
+        let chess_match = MATCHES.load(deps.storage, bytes)?;
+        let moves = MATCH_MOVES
+            .prefix(bytes.as_slice())
+            .range(deps.storage, None, None, Order::Ascending)
+            .map(|entry| entry.map(|(_, played)| played))
+            .collect::<StdResult<_>>()?;
+        Ok(MatchResponse {
+            board: chess_match.board(),
+            chess_match,
+            moves,
+        })
+    }
+
+    pub fn player_stats(deps: Deps, player: String) -> StdResult<PlayerStatsResponse> {
+        let player = deps.api.addr_validate(&player)?;
+        let stats = PLAYER_STATS
+            .may_load(deps.storage, &player)?
+            .unwrap_or_default();
+        let rating = exec::load_rating(deps.storage, &player)?;
+        Ok(PlayerStatsResponse {
+            average_plies: stats.average_plies(),
+            stats,
+            rating,
+            player,
+        })
+    }
+
+    pub fn head_to_head(deps: Deps, a: String, b: String) -> StdResult<HeadToHeadResponse> {
+        let (a, b) = (deps.api.addr_validate(&a)?, deps.api.addr_validate(&b)?);
+        let key = pair_key(&a, &b);
+        let record = HEAD_TO_HEAD
+            .may_load(deps.storage, key)?
+            .unwrap_or_default();
+        let (a_wins, b_wins) = match key.0 == a {
+            true => (record.first_wins, record.second_wins),
+            false => (record.second_wins, record.first_wins),
+        };
+        Ok(HeadToHeadResponse {
+            a_wins,
+            b_wins,
+            draws: record.draws,
+            a,
+            b,
+        })
+    }
+
+    pub fn profile(deps: Deps, address: String) -> StdResult<Option<Profile>> {
+        let address = deps.api.addr_validate(&address)?;
+        PROFILES.may_load(deps.storage, &address)
+    }
+
+    pub fn resolve_nickname(deps: Deps, nickname: String) -> StdResult<Option<Addr>> {
+        NICKNAMES.may_load(deps.storage, &Profile::nickname_key(&nickname))
+    }
+
+    pub fn current_season(deps: Deps) -> StdResult<Option<SeasonId>> {
+        CURRENT_SEASON.may_load(deps.storage)
+    }
+
+    pub fn season(deps: Deps, season_id: SeasonId) -> StdResult<Season> {
+        SEASONS.load(deps.storage, season_id)
+    }
+
+    pub fn season_leaderboard(
+        deps: Deps,
+        season_id: SeasonId,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<SeasonLeaderboardResponse> {
+        let limit = limit
+            .unwrap_or(DEFAULT_LEADERBOARD_LIMIT)
+            .min(MAX_LEADERBOARD_LIMIT) as usize;
+        let start = match start_after {
+            Some(player) => {
+                let player = deps.api.addr_validate(&player)?;
+                let standing = SEASON_STANDINGS.load(deps.storage, (season_id, &player))?;
+                Some((rank_key(&standing), player))
+            }
+            None => None,
+        };
+        let standings = SEASON_RANKING
+            .sub_prefix(season_id)
+            .keys(
+                deps.storage,
+                start
+                    .as_ref()
+                    .map(|(rank, player)| Bound::exclusive((*rank, player))),
+                None,
+                Order::Ascending,
+            )
+            .take(limit)
+            .map(|key| {
+                let (_, player) = key?;
+                SEASON_STANDINGS.load(deps.storage, (season_id, &player))
+            })
+            .collect::<StdResult<_>>()?;
+        Ok(SeasonLeaderboardResponse { standings })
+    }
+
+    pub fn puzzle(deps: Deps, puzzle_id: PuzzleId) -> StdResult<Puzzle> {
+        PUZZLES.load(deps.storage, puzzle_id)
+    }
+
+    pub fn team_match(deps: Deps, team_match_id: TeamMatchId) -> StdResult<TeamMatch> {
+        TEAM_MATCHES.load(deps.storage, team_match_id)
+    }
+
+    pub fn votes(deps: Deps, match_id: String) -> StdResult<VotesResponse> {
+        let mut bytes = [0u8; 32];
+        hex::decode_to_slice(match_id, &mut bytes)
+            .map_err(|_| StdError::generic_err("Invalid match ID"))?;
+        let chess_match = MATCHES.load(deps.storage, bytes)?;
+        let group = VOTE_GROUPS.load(deps.storage, bytes)?;
+        Ok(VotesResponse {
+            voting_ends: group.voting_ends(&chess_match),
+            tally: group.tally(),
+            voters_color: group.color,
+        })
+    }
+
+    pub fn ibc_opponent(deps: Deps, match_id: String) -> StdResult<Option<IbcOpponent>> {
+        let mut bytes = [0u8; 32];
+        hex::decode_to_slice(match_id, &mut bytes)
+            .map_err(|_| StdError::generic_err("Invalid match ID"))?;
+        IBC_OPPONENTS.may_load(deps.storage, bytes)
+    }
+
+    pub fn ibc_escrow(
+        deps: Deps,
+        channel_id: String,
+        match_id: String,
+        player: String,
+    ) -> StdResult<Option<IbcEscrow>> {
+        let player = deps.api.addr_validate(&player)?;
+        IBC_ESCROWS.may_load(
+            deps.storage,
+            (&channel_id, &match_id.to_ascii_lowercase(), &player),
+        )
+    }
+
+    pub fn ibc_peers(deps: Deps) -> StdResult<IbcPeersResponse> {
+        let peers = IBC_PEERS
+            .keys(deps.storage, None, None, Order::Ascending)
+            .map(|peer| {
+                peer.map(|(connection_id, port_id)| IbcPeer {
+                    connection_id,
+                    port_id,
+                })
+            })
+            .collect::<StdResult<_>>()?;
+        Ok(IbcPeersResponse { peers })
+    }
+
+    pub fn challenge_policy(deps: Deps, player: String) -> StdResult<ChallengePolicy> {
+        let player = deps.api.addr_validate(&player)?;
+        Ok(CHALLENGE_POLICIES
+            .may_load(deps.storage, &player)?
+            .unwrap_or_default())
+    }
+
+    pub fn tournament(deps: Deps, tournament_id: TournamentId) -> StdResult<Tournament> {
+        TOURNAMENTS.load(deps.storage, tournament_id)
+    }
+
+    pub fn standings(deps: Deps, tournament_id: TournamentId) -> StdResult<StandingsResponse> {
+        let tournament = TOURNAMENTS.load(deps.storage, tournament_id)?;
+        let standings = exec::load_standings(deps.storage, tournament_id)?;
+        Ok(StandingsResponse {
+            standings: rank_standings(&tournament.kind, &tournament.players, &standings),
+        })
+    }
+
+    pub fn side_bet_market(deps: Deps, match_id: String) -> StdResult<Option<Market>> {
+        let mut bytes = [0u8; 32];
+        hex::decode_to_slice(match_id, &mut bytes)
+            .map_err(|_| StdError::generic_err("Invalid match ID"))?;
+        MARKETS.may_load(deps.storage, bytes)
+    }
+
+    pub fn arbiter(deps: Deps, match_id: Option<String>) -> StdResult<Addr> {
+        let match_id = match match_id {
+            Some(match_id) => {
+                let mut bytes = [0u8; 32];
+                hex::decode_to_slice(match_id, &mut bytes)
+                    .map_err(|_| StdError::generic_err("Invalid match ID"))?;
+                Some(bytes)
+            }
+            None => None,
+        };
+        exec::load_arbiter(deps.storage, match_id)
+    }
+
+    pub fn hooks(deps: Deps) -> StdResult<HooksResponse> {
+        let hooks = HOOKS
+            .keys(deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<_>>()?;
+        Ok(HooksResponse { hooks })
+    }
+
+    pub fn bracket(deps: Deps, tournament_id: TournamentId) -> StdResult<BracketResponse> {
+        let mut rounds: Vec<Vec<BracketTie>> = vec![];
+        for tie in exec::load_bracket(deps.storage, tournament_id)? {
+            if rounds.len() < tie.round as usize {
+                rounds.push(vec![]);
+            }
+            if let Some(round) = rounds.last_mut() {
+                round.push(tie);
+            }
+        }
+        Ok(BracketResponse { rounds })
+    }
+
+    /// Replays a line under the same rules as `exec::make_move`, stopping at
+    /// the first move it would reject.
+    pub fn validate_line(
+        env: Env,
+        start_fen: Option<String>,
+        moves: Vec<String>,
+    ) -> StdResult<ValidateLineResponse> {
+        let board = match start_fen {
+            Some(fen) => {
+                Match::decode_board(&fen).map_err(|_| StdError::generic_err("Invalid start FEN"))?
+            }
+            None => Board::default(),
+        };
+        let contract = env.contract.address;
+        let mut line =
+            Match::new(contract.clone(), contract, 0, Coin::default()).with_position(&board);
+
+        let mut san = Vec::with_capacity(moves.len());
+        for (index, move_fen) in moves.iter().enumerate() {
+            let position = line.position();
+            let played = exec::validate_fen_move(move_fen)
+                .and_then(|_| match line.state {
+                    MatchState::Won | MatchState::Drawn => {
+                        Err(ContractError::MatchAlreadyFinished {})
+                    }
+                    _ => exec::decode_move(move_fen),
+                })
+                .and_then(|mov| {
+                    line.play_move(&mov, env.block.height)
+                        .map_err(|_| ContractError::IllegalMove {})?;
+                    Ok(mov)
+                });
+            match played {
+                Ok(mov) => san.push(to_san(&position, mov)),
+                Err(err) => {
+                    return Ok(ValidateLineResponse::Invalid {
+                        index: index as u32,
+                        reason: err.to_string(),
+                    })
+                }
+            }
+        }
+
+        Ok(ValidateLineResponse::Valid {
+            fen: line.board(),
+            state: line.state,
+            san,
+        })
+    }
+}
+
+pub fn reply(_deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+    match (msg.id, msg.result) {
+        // The move stands even if the bot failed to handle its notification
+        (BOT_CALLBACK_REPLY_ID, SubMsgResult::Err(err)) => Ok(Response::new()
+            .add_attribute("action", "bot_callback_failed")
+            .add_attribute("error", err)),
+        // The match is over even if a hook failed to handle its notification
+        (HOOK_REPLY_ID, SubMsgResult::Err(err)) => Ok(Response::new()
+            .add_attribute("action", "hook_failed")
+            .add_attribute("error", err)),
+        // The packet's handler was reverted, its error replaces the
+        // acknowledgement
+        (IBC_RECEIVE_REPLY_ID, SubMsgResult::Err(err)) => Ok(Response::new()
+            .set_data(StdAck::error(&err).to_binary())
+            .add_attribute("action", "ibc_packet_failed")
+            .add_attribute("error", err)),
+        (id, _) => Err(StdError::generic_err(format!("unknown reply id {id}")).into()),
+    }
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
-    let _original_version =
+pub fn migrate(deps: DepsMut, _env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    let original_version =
         ensure_from_older_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
-    Ok(Response::default())
+    migrations::schedule(
+        deps.storage,
+        (original_version.major, original_version.minor),
+    )?;
+    let (migrated, done) =
+        migrations::migrate_matches(deps.storage, msg.limit.unwrap_or(DEFAULT_MIGRATION_LIMIT))?;
+
+    Ok(Response::new()
+        .add_attribute("action", "migrate")
+        .add_attribute("from_version", original_version.to_string())
+        .add_attribute("to_version", CONTRACT_VERSION)
+        .add_attribute("migrated_matches", migrated.to_string())
+        .add_attribute("done", done.to_string()))
 }
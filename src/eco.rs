@@ -0,0 +1,253 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Opening of a game, by ECO (Encyclopaedia of Chess Openings) code.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct Opening {
+    pub eco: String,
+    pub name: String,
+}
+
+// Deepest line in `ECO_TABLE`, games are only classified up to this ply
+pub const MAX_OPENING_PLIES: usize = 10;
+
+// Main lines of common openings, as (code, name, moves). Moves are in the
+// notation `Match::play_move` records them in, castling king-to-rook.
+pub(crate) const ECO_TABLE: &[(&str, &str, &str)] = &[
+    ("A00", "Polish Opening", "b2b4"),
+    ("A00", "Grob Opening", "g2g4"),
+    ("A01", "Nimzo-Larsen Attack", "b2b3"),
+    ("A02", "Bird's Opening", "f2f4"),
+    ("A04", "Reti Opening", "g1f3"),
+    ("A06", "Reti Opening", "g1f3 d7d5"),
+    ("A09", "Reti Opening", "g1f3 d7d5 c2c4"),
+    ("A10", "English Opening", "c2c4"),
+    ("A20", "English Opening", "c2c4 e7e5"),
+    ("A30", "English Opening: Symmetrical Variation", "c2c4 c7c5"),
+    ("A40", "Queen's Pawn Game", "d2d4"),
+    ("A43", "Old Benoni Defence", "d2d4 c7c5"),
+    ("A45", "Indian Game", "d2d4 g8f6"),
+    ("A46", "Indian Game", "d2d4 g8f6 g1f3"),
+    ("A50", "Indian Game", "d2d4 g8f6 c2c4"),
+    ("A56", "Benoni Defence", "d2d4 g8f6 c2c4 c7c5"),
+    ("A57", "Benko Gambit", "d2d4 g8f6 c2c4 c7c5 d4d5 b7b5"),
+    ("A80", "Dutch Defence", "d2d4 f7f5"),
+    ("B00", "King's Pawn Opening", "e2e4"),
+    ("B01", "Scandinavian Defence", "e2e4 d7d5"),
+    ("B02", "Alekhine's Defence", "e2e4 g8f6"),
+    ("B06", "Modern Defence", "e2e4 g7g6"),
+    ("B07", "Pirc Defence", "e2e4 d7d6 d2d4 g8f6"),
+    ("B10", "Caro-Kann Defence", "e2e4 c7c6"),
+    (
+        "B12",
+        "Caro-Kann Defence: Advance Variation",
+        "e2e4 c7c6 d2d4 d7d5 e4e5",
+    ),
+    (
+        "B13",
+        "Caro-Kann Defence: Exchange Variation",
+        "e2e4 c7c6 d2d4 d7d5 e4d5",
+    ),
+    ("B20", "Sicilian Defence", "e2e4 c7c5"),
+    (
+        "B21",
+        "Sicilian Defence: Smith-Morra Gambit",
+        "e2e4 c7c5 d2d4 c5d4 c2c3",
+    ),
+    (
+        "B22",
+        "Sicilian Defence: Alapin Variation",
+        "e2e4 c7c5 c2c3",
+    ),
+    ("B23", "Sicilian Defence: Closed", "e2e4 c7c5 b1c3"),
+    ("B27", "Sicilian Defence", "e2e4 c7c5 g1f3"),
+    ("B30", "Sicilian Defence", "e2e4 c7c5 g1f3 b8c6"),
+    ("B40", "Sicilian Defence", "e2e4 c7c5 g1f3 e7e6"),
+    ("B50", "Sicilian Defence", "e2e4 c7c5 g1f3 d7d6"),
+    (
+        "B54",
+        "Sicilian Defence",
+        "e2e4 c7c5 g1f3 d7d6 d2d4 c5d4 f3d4",
+    ),
+    (
+        "B56",
+        "Sicilian Defence",
+        "e2e4 c7c5 g1f3 d7d6 d2d4 c5d4 f3d4 g8f6 b1c3",
+    ),
+    (
+        "B70",
+        "Sicilian Defence: Dragon Variation",
+        "e2e4 c7c5 g1f3 d7d6 d2d4 c5d4 f3d4 g8f6 b1c3 g7g6",
+    ),
+    (
+        "B80",
+        "Sicilian Defence: Scheveningen Variation",
+        "e2e4 c7c5 g1f3 d7d6 d2d4 c5d4 f3d4 g8f6 b1c3 e7e6",
+    ),
+    (
+        "B90",
+        "Sicilian Defence: Najdorf Variation",
+        "e2e4 c7c5 g1f3 d7d6 d2d4 c5d4 f3d4 g8f6 b1c3 a7a6",
+    ),
+    ("C00", "French Defence", "e2e4 e7e6"),
+    (
+        "C01",
+        "French Defence: Exchange Variation",
+        "e2e4 e7e6 d2d4 d7d5 e4d5",
+    ),
+    (
+        "C02",
+        "French Defence: Advance Variation",
+        "e2e4 e7e6 d2d4 d7d5 e4e5",
+    ),
+    (
+        "C03",
+        "French Defence: Tarrasch Variation",
+        "e2e4 e7e6 d2d4 d7d5 b1d2",
+    ),
+    ("C10", "French Defence", "e2e4 e7e6 d2d4 d7d5 b1c3"),
+    (
+        "C11",
+        "French Defence: Classical Variation",
+        "e2e4 e7e6 d2d4 d7d5 b1c3 g8f6",
+    ),
+    (
+        "C15",
+        "French Defence: Winawer Variation",
+        "e2e4 e7e6 d2d4 d7d5 b1c3 f8b4",
+    ),
+    ("C20", "King's Pawn Game", "e2e4 e7e5"),
+    ("C23", "Bishop's Opening", "e2e4 e7e5 f1c4"),
+    ("C25", "Vienna Game", "e2e4 e7e5 b1c3"),
+    ("C30", "King's Gambit", "e2e4 e7e5 f2f4"),
+    ("C33", "King's Gambit Accepted", "e2e4 e7e5 f2f4 e5f4"),
+    ("C40", "King's Knight Opening", "e2e4 e7e5 g1f3"),
+    ("C41", "Philidor Defence", "e2e4 e7e5 g1f3 d7d6"),
+    ("C42", "Petrov's Defence", "e2e4 e7e5 g1f3 g8f6"),
+    ("C44", "King's Pawn Game", "e2e4 e7e5 g1f3 b8c6"),
+    ("C44", "Scotch Game", "e2e4 e7e5 g1f3 b8c6 d2d4"),
+    ("C45", "Scotch Game", "e2e4 e7e5 g1f3 b8c6 d2d4 e5d4 f3d4"),
+    ("C46", "Three Knights Game", "e2e4 e7e5 g1f3 b8c6 b1c3"),
+    ("C47", "Four Knights Game", "e2e4 e7e5 g1f3 b8c6 b1c3 g8f6"),
+    ("C50", "Italian Game", "e2e4 e7e5 g1f3 b8c6 f1c4"),
+    (
+        "C50",
+        "Italian Game: Giuoco Piano",
+        "e2e4 e7e5 g1f3 b8c6 f1c4 f8c5",
+    ),
+    (
+        "C51",
+        "Italian Game: Evans Gambit",
+        "e2e4 e7e5 g1f3 b8c6 f1c4 f8c5 b2b4",
+    ),
+    (
+        "C53",
+        "Italian Game: Giuoco Piano",
+        "e2e4 e7e5 g1f3 b8c6 f1c4 f8c5 c2c3",
+    ),
+    (
+        "C55",
+        "Italian Game: Two Knights Defence",
+        "e2e4 e7e5 g1f3 b8c6 f1c4 g8f6",
+    ),
+    (
+        "C57",
+        "Italian Game: Two Knights Defence, Knight Attack",
+        "e2e4 e7e5 g1f3 b8c6 f1c4 g8f6 f3g5",
+    ),
+    ("C60", "Ruy Lopez", "e2e4 e7e5 g1f3 b8c6 f1b5"),
+    (
+        "C65",
+        "Ruy Lopez: Berlin Defence",
+        "e2e4 e7e5 g1f3 b8c6 f1b5 g8f6",
+    ),
+    (
+        "C68",
+        "Ruy Lopez: Exchange Variation",
+        "e2e4 e7e5 g1f3 b8c6 f1b5 a7a6 b5c6",
+    ),
+    (
+        "C70",
+        "Ruy Lopez: Morphy Defence",
+        "e2e4 e7e5 g1f3 b8c6 f1b5 a7a6 b5a4",
+    ),
+    (
+        "C78",
+        "Ruy Lopez: Morphy Defence",
+        "e2e4 e7e5 g1f3 b8c6 f1b5 a7a6 b5a4 g8f6 e1h1",
+    ),
+    (
+        "C84",
+        "Ruy Lopez: Closed",
+        "e2e4 e7e5 g1f3 b8c6 f1b5 a7a6 b5a4 g8f6 e1h1 f8e7",
+    ),
+    ("D00", "Queen's Pawn Game", "d2d4 d7d5"),
+    ("D02", "Queen's Pawn Game", "d2d4 d7d5 g1f3"),
+    ("D02", "London System", "d2d4 d7d5 g1f3 g8f6 c1f4"),
+    ("D06", "Queen's Gambit", "d2d4 d7d5 c2c4"),
+    (
+        "D07",
+        "Queen's Gambit Declined: Chigorin Defence",
+        "d2d4 d7d5 c2c4 b8c6",
+    ),
+    ("D10", "Slav Defence", "d2d4 d7d5 c2c4 c7c6"),
+    ("D20", "Queen's Gambit Accepted", "d2d4 d7d5 c2c4 d5c4"),
+    ("D30", "Queen's Gambit Declined", "d2d4 d7d5 c2c4 e7e6"),
+    (
+        "D35",
+        "Queen's Gambit Declined",
+        "d2d4 d7d5 c2c4 e7e6 b1c3 g8f6",
+    ),
+    (
+        "D43",
+        "Semi-Slav Defence",
+        "d2d4 d7d5 c2c4 c7c6 g1f3 g8f6 b1c3 e7e6",
+    ),
+    (
+        "D70",
+        "Neo-Grünfeld Defence",
+        "d2d4 g8f6 c2c4 g7g6 f2f3 d7d5",
+    ),
+    ("D80", "Grünfeld Defence", "d2d4 g8f6 c2c4 g7g6 b1c3 d7d5"),
+    ("E00", "Indian Game", "d2d4 g8f6 c2c4 e7e6"),
+    ("E00", "Catalan Opening", "d2d4 g8f6 c2c4 e7e6 g2g3"),
+    ("E10", "Indian Game", "d2d4 g8f6 c2c4 e7e6 g1f3"),
+    (
+        "E12",
+        "Queen's Indian Defence",
+        "d2d4 g8f6 c2c4 e7e6 g1f3 b7b6",
+    ),
+    (
+        "E20",
+        "Nimzo-Indian Defence",
+        "d2d4 g8f6 c2c4 e7e6 b1c3 f8b4",
+    ),
+    ("E60", "King's Indian Defence", "d2d4 g8f6 c2c4 g7g6"),
+    ("E61", "King's Indian Defence", "d2d4 g8f6 c2c4 g7g6 b1c3"),
+    (
+        "E70",
+        "King's Indian Defence",
+        "d2d4 g8f6 c2c4 g7g6 b1c3 f8g7 e2e4",
+    ),
+    (
+        "E90",
+        "King's Indian Defence",
+        "d2d4 g8f6 c2c4 g7g6 b1c3 f8g7 e2e4 d7d6 g1f3",
+    ),
+];
+
+/// The deepest opening whose main line starts `moves`, if any.
+pub fn classify<'a>(moves: impl IntoIterator<Item = &'a str>) -> Option<Opening> {
+    let moves: Vec<&str> = moves.into_iter().take(MAX_OPENING_PLIES).collect();
+    ECO_TABLE
+        .iter()
+        .filter(|(_, _, line)| {
+            let line: Vec<&str> = line.split(' ').collect();
+            moves.starts_with(&line)
+        })
+        .max_by_key(|(_, _, line)| line.split(' ').count())
+        .map(|(eco, name, _)| Opening {
+            eco: eco.to_string(),
+            name: name.to_string(),
+        })
+}
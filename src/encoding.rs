@@ -0,0 +1,131 @@
+use cozy_chess::{
+    BitBoard, Board, BoardBuilder, BoardBuilderError, CastleRights, Color, File, Piece, Rank,
+    Square,
+};
+
+// Occupancy bitboard, flags, castling rights, halfmove clock and fullmove number
+const HEADER_LENGTH: usize = 14;
+
+const BLACK_TO_MOVE: u8 = 0b1;
+const EN_PASSANT: u8 = 0b10;
+const EN_PASSANT_FILE_SHIFT: u8 = 2;
+
+const BLACK_PIECE: u8 = 0b1000;
+
+/// Packs a board into at most 30 bytes:
+///
+/// - bytes 0..8: occupancy bitboard (little endian)
+/// - byte 8: side to move (bit 0), en passant (bit 1) and its file (bits 2..5)
+/// - bytes 9..11: castling rook files, one nibble each for white short, white
+///   long, black short and black long castling (file + 1, 0 if none)
+/// - byte 11: halfmove clock
+/// - bytes 12..14: fullmove number (little endian)
+/// - then one nibble per occupied square, in square order: colour (bit 3) and
+///   piece (bits 0..3)
+pub fn pack_board(board: &Board) -> Vec<u8> {
+    let occupied = board.occupied();
+    let mut bytes = Vec::with_capacity(HEADER_LENGTH + (occupied.len() as usize).div_ceil(2));
+    bytes.extend_from_slice(&occupied.0.to_le_bytes());
+
+    let mut flags = 0u8;
+    if board.side_to_move() == Color::Black {
+        flags |= BLACK_TO_MOVE;
+    }
+    if let Some(file) = board.en_passant() {
+        flags |= EN_PASSANT | (file as u8) << EN_PASSANT_FILE_SHIFT;
+    }
+    bytes.push(flags);
+
+    let white = board.castle_rights(Color::White);
+    let black = board.castle_rights(Color::Black);
+    bytes.push(pack_file(white.short) << 4 | pack_file(white.long));
+    bytes.push(pack_file(black.short) << 4 | pack_file(black.long));
+    bytes.push(board.halfmove_clock());
+    bytes.extend_from_slice(&board.fullmove_number().to_le_bytes());
+
+    let pieces: Vec<u8> = occupied
+        .iter()
+        .map(|square| {
+            let piece = board.piece_on(square).map_or(0, |piece| piece as u8);
+            match board.color_on(square) {
+                Some(Color::Black) => piece | BLACK_PIECE,
+                _ => piece,
+            }
+        })
+        .collect();
+    for pair in pieces.chunks(2) {
+        bytes.push(pair[0] << 4 | pair.get(1).copied().unwrap_or_default());
+    }
+    bytes
+}
+
+/// Inverse of `pack_board`.
+pub fn unpack_board(bytes: &[u8]) -> Result<Board, BoardBuilderError> {
+    let header = bytes
+        .get(..HEADER_LENGTH)
+        .ok_or(BoardBuilderError::InvalidBoard)?;
+    let occupied = BitBoard(u64::from_le_bytes(
+        header[..8]
+            .try_into()
+            .map_err(|_| BoardBuilderError::InvalidBoard)?,
+    ));
+    let pieces = &bytes[HEADER_LENGTH..];
+    if pieces.len() != (occupied.len() as usize).div_ceil(2) {
+        return Err(BoardBuilderError::InvalidBoard);
+    }
+
+    let mut builder = BoardBuilder::empty();
+    for (i, square) in occupied.iter().enumerate() {
+        let nibble = match i % 2 {
+            0 => pieces[i / 2] >> 4,
+            _ => pieces[i / 2] & 0b1111,
+        };
+        let piece = Piece::try_index((nibble & !BLACK_PIECE) as usize)
+            .ok_or(BoardBuilderError::InvalidBoard)?;
+        let color = match nibble & BLACK_PIECE {
+            0 => Color::White,
+            _ => Color::Black,
+        };
+        *builder.square_mut(square) = Some((piece, color));
+    }
+
+    let flags = header[8];
+    builder.side_to_move = match flags & BLACK_TO_MOVE {
+        0 => Color::White,
+        _ => Color::Black,
+    };
+    if flags & EN_PASSANT != 0 {
+        let file = File::index((flags >> EN_PASSANT_FILE_SHIFT & 0b111) as usize);
+        let rank = match builder.side_to_move {
+            Color::White => Rank::Sixth,
+            Color::Black => Rank::Third,
+        };
+        builder.en_passant = Some(Square::new(file, rank));
+    }
+
+    *builder.castle_rights_mut(Color::White) = unpack_castle_rights(header[9])?;
+    *builder.castle_rights_mut(Color::Black) = unpack_castle_rights(header[10])?;
+    builder.halfmove_clock = header[11];
+    builder.fullmove_number = u16::from_le_bytes([header[12], header[13]]);
+    builder.build()
+}
+
+fn pack_file(file: Option<File>) -> u8 {
+    file.map_or(0, |file| file as u8 + 1)
+}
+
+fn unpack_file(nibble: u8) -> Result<Option<File>, BoardBuilderError> {
+    match nibble {
+        0 => Ok(None),
+        _ => File::try_index(nibble as usize - 1)
+            .map(Some)
+            .ok_or(BoardBuilderError::InvalidCastlingRights),
+    }
+}
+
+fn unpack_castle_rights(byte: u8) -> Result<CastleRights, BoardBuilderError> {
+    Ok(CastleRights {
+        short: unpack_file(byte >> 4)?,
+        long: unpack_file(byte & 0b1111)?,
+    })
+}
@@ -44,6 +44,180 @@ pub enum ContractError {
 
     #[error("Illegal move")]
     IllegalMove {},
+
+    #[error("Unknown tournament")]
+    UnknownTournament {},
+
+    #[error("Revealed seed doesn't match the commitment")]
+    InvalidSeed {},
+
+    #[error("Not the tournament organizer")]
+    NotTournamentOrganizer {},
+
+    #[error("Invalid tournament")]
+    InvalidTournament { reason: InvalidTournamentReason },
+
+    #[error("Tournament registration closed")]
+    RegistrationClosed {},
+
+    #[error("Tournament full")]
+    TournamentFull {},
+
+    #[error("Already registered")]
+    AlreadyRegistered {},
+
+    #[error("Not enough players")]
+    NotEnoughPlayers {},
+
+    #[error("Tournament not ongoing")]
+    TournamentNotOnGoing {},
+
+    #[error("Round still in progress")]
+    RoundInProgress {},
+
+    #[error("Players cannot place side bets on their own match")]
+    PlayersCannotSideBet {},
+
+    #[error("Betting closed")]
+    BettingClosed {},
+
+    #[error("Side-bet market full")]
+    MarketFull {},
+
+    #[error("Invalid house fee")]
+    InvalidHouseFee {},
+
+    #[error("Invalid delegate")]
+    InvalidDelegate {},
+
+    #[error("Invalid expiration")]
+    InvalidExpiration {},
+
+    #[error("Unknown move key")]
+    UnknownMoveKey {},
+
+    #[error("Only contracts can register bot callbacks")]
+    NotAContract {},
+
+    #[error("Hook already registered")]
+    HookAlreadyRegistered {},
+
+    #[error("Unknown hook")]
+    UnknownHook {},
+
+    #[error("Rematch is no longer available")]
+    RematchUnavailable {},
+
+    #[error("Rematch already offered")]
+    RematchAlreadyOffered {},
+
+    #[error("No rematch offer")]
+    NoRematchOffer {},
+
+    #[error("Takebacks are not allowed in rated matches")]
+    TakebacksNotAllowed {},
+
+    #[error("Invalid number of plies to take back")]
+    InvalidTakeback {},
+
+    #[error("No takeback request")]
+    NoTakebackRequest {},
+
+    #[error("Invalid conditional moves")]
+    InvalidConditionalMoves {},
+
+    #[error("Matches are still being migrated")]
+    MigrationInProgress {},
+
+    #[error("Invalid adjudication")]
+    InvalidAdjudication {},
+
+    #[error("Match arbiter is not the expected one")]
+    ArbiterMismatch {},
+
+    #[error("Unknown puzzle")]
+    UnknownPuzzle {},
+
+    #[error("Invalid puzzle")]
+    InvalidPuzzle { reason: InvalidPuzzleReason },
+
+    #[error("Puzzle already solved")]
+    PuzzleAlreadySolved {},
+
+    #[error("Not the puzzle poster")]
+    NotPuzzlePoster {},
+
+    #[error("No solution committed in an earlier block")]
+    NoSolutionCommitment {},
+
+    #[error("Solution does not match its commitment")]
+    CommitmentMismatch {},
+
+    #[error("Solutions committed to the puzzle are still pending")]
+    PendingSolutions {},
+
+    #[error("Puzzle is being withdrawn")]
+    PuzzleWithdrawing {},
+
+    #[error("Incorrect solution")]
+    IncorrectSolution {},
+
+    #[error("Solution too expensive to verify")]
+    SearchLimitExceeded {},
+
+    #[error("A season is already in progress")]
+    SeasonInProgress {},
+
+    #[error("No season in progress")]
+    NoSeasonInProgress {},
+
+    #[error("Prize split must add up to 100")]
+    InvalidPrizeSplit {},
+
+    #[error("Invalid profile")]
+    InvalidProfile { reason: InvalidProfileReason },
+
+    #[error("Nickname already taken")]
+    NicknameTaken {},
+
+    #[error("Challenge rejected")]
+    ChallengeRejected { reason: ChallengeRejectedReason },
+
+    #[error("Invalid vote group")]
+    InvalidVoteGroup {},
+
+    #[error("Not a vote match")]
+    NotAVoteMatch {},
+
+    #[error("Not a voter in this match")]
+    NotAVoter {},
+
+    #[error("Voting closed")]
+    VotingClosed {},
+
+    #[error("Voting still open")]
+    VotingStillOpen {},
+
+    #[error("Unknown team match")]
+    UnknownTeamMatch {},
+
+    #[error("Invalid team match")]
+    InvalidTeamMatch { reason: InvalidTeamMatchReason },
+
+    #[error("Invalid IBC channel")]
+    InvalidIbcChannel { reason: InvalidIbcChannelReason },
+
+    #[error("Unknown IBC channel")]
+    UnknownChannel {},
+
+    #[error("Not a cross-chain match")]
+    NotAnIbcMatch {},
+
+    #[error("Unknown escrow")]
+    UnknownEscrow {},
+
+    #[error("Unknown IBC peer")]
+    UnknownIbcPeer {},
 }
 
 #[derive(Error, Debug, PartialEq)]
@@ -59,3 +233,77 @@ pub enum InvalidBetReason {
     #[error("Invalid amount")]
     InvalidAmount,
 }
+
+#[derive(Error, Debug, PartialEq)]
+pub enum InvalidTournamentReason {
+    #[error("Too few players")]
+    TooFewPlayers,
+    #[error("Prize split must add up to 100")]
+    PrizeSplitTotal,
+    #[error("More prizes than players")]
+    TooManyPrizes,
+    #[error("Invalid number of rounds")]
+    InvalidRounds,
+    #[error("Knockout brackets need a power of two players")]
+    BracketSize,
+    #[error("Seed commitment must be a hex SHA-256 hash")]
+    SeedCommitment,
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum InvalidPuzzleReason {
+    #[error("Invalid board")]
+    InvalidBoard,
+    #[error("Game already over")]
+    GameOver,
+    #[error("Mate depth out of range")]
+    MateInOutOfRange,
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum InvalidProfileReason {
+    #[error("Nickname must be 3 to 20 characters long")]
+    NicknameLength,
+    #[error("Nickname must start with a letter and contain only letters, digits, '_' and '-'")]
+    NicknameCharset,
+    #[error("Country must be an ISO 3166-1 alpha-2 code")]
+    Country,
+    #[error("Invalid avatar URI")]
+    AvatarUri,
+    #[error("Bio too long or containing control characters")]
+    Bio,
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum InvalidTeamMatchReason {
+    #[error("Invalid number of boards")]
+    BoardCount,
+    #[error("Not enough players for every board")]
+    TeamTooSmall,
+    #[error("Players can only play one board")]
+    DuplicatePlayer,
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ChallengeRejectedReason {
+    #[error("Challenger is blocked")]
+    Blocked,
+    #[error("Challenger is not on the allow list")]
+    NotAllowed,
+    #[error("Challenger's rating is too low")]
+    RatingTooLow,
+    #[error("Bet too low")]
+    BetTooLow,
+    #[error("Bet too high")]
+    BetTooHigh,
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum InvalidIbcChannelReason {
+    #[error("Channel must be unordered")]
+    Order,
+    #[error("Unsupported channel version")]
+    Version,
+    #[error("Counterparty is not an allowed peer")]
+    UnknownPeer,
+}
@@ -1,19 +1,27 @@
-use cosmwasm_std::{Addr, Coin};
+use cosmwasm_std::{Addr, Binary, Coin, StdError, StdResult};
 use cozy_chess::{Board, Color, FenParseError, GameStatus, IllegalMoveError, Move, MoveParseError};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::str::FromStr;
 
+use crate::eco::{classify, Opening};
+use crate::encoding::{pack_board, unpack_board};
+use crate::migrations::v0_3;
+use crate::tournament::TournamentId;
+
 pub const MOVE_FEN_LENGTH: usize = 4;
 
-// Not yet implemented
-// #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
-// pub enum MatchStyle {
-//     Bullet, // 1 minute
-//     Blitz,  // 5 minutes
-//     Rapid,  // 15 minutes
-//     Daily,  // 1 day
-// }
+// Bounds on the conditional moves a player can queue in a match
+pub const MAX_CONDITIONAL_LINES: usize = 16;
+pub const MAX_CONDITIONAL_PLIES: usize = 20;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub enum MatchStyle {
+    Bullet, // 1 minute
+    Blitz,  // 5 minutes
+    Rapid,  // 15 minutes
+    Daily,  // 1 day
+}
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
 pub enum NextMove {
@@ -29,18 +37,44 @@ pub enum MatchState {
     Drawn,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub enum MatchResult {
+    WhiteWins,
+    BlackWins,
+    Draw,
+}
+
+/// Result imposed by an arbiter; a voided match is refunded without result.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub enum Adjudication {
+    WhiteWins,
+    BlackWins,
+    Draw,
+    Void,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct PlayedMove {
+    pub mov: String,
+    pub block_height: u64,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
 pub struct Match {
     pub challenger: Addr,
     pub opponent: Addr,
-    board: String, // Don't expose this
+    board: Binary, // Packed, see `pack_board`. Don't expose this
     pub state: MatchState,
     pub nonce: u64,
-    // Not yet implemented
-    // pub style: MatchStyle,
+    pub style: MatchStyle,
+    // Rated matches update players' ratings and can't be taken back
+    pub rated: bool,
     pub last_move: u64,
     pub start: u64,
     pub bet: Coin,
+    pub tournament: Option<TournamentId>,
+    // Deepest ECO opening the moves played so far follow
+    pub opening: Option<Opening>,
 }
 
 impl Match {
@@ -48,13 +82,16 @@ impl Match {
         Self {
             challenger,
             opponent,
-            board: Board::default().to_string(),
+            board: pack_board(&Board::default()).into(),
             state: MatchState::AwaitingOpponent,
             nonce,
-            // style,
+            style: MatchStyle::Daily,
+            rated: true,
             last_move: 0u64,
             start: 0u64,
             bet,
+            tournament: None,
+            opening: None,
         }
     }
 
@@ -70,18 +107,39 @@ impl Match {
         Self {
             challenger,
             opponent,
-            board: Board::default().to_string(),
+            board: pack_board(&Board::default()).into(),
             state,
             nonce,
-            // style,
+            style: MatchStyle::Daily,
+            rated: true,
             last_move,
             start,
             bet,
+            tournament: None,
+            opening: None,
         }
     }
 
+    /// Starts from `board` instead of the initial position.
+    pub fn with_position(mut self, board: &Board) -> Self {
+        self.set_board(board);
+        self
+    }
+
+    /// Board in FEN notation.
     pub fn board(&self) -> String {
-        self.board.to_owned()
+        Match::encode_board(&self.position())
+    }
+
+    pub fn position(&self) -> Board {
+        unpack_board(&self.board)
+            .expect("Board encoding should always be correct as it is controlled by Match.")
+    }
+
+    /// Number of half-moves played so far.
+    pub fn plies(&self) -> u32 {
+        let board = self.position();
+        (board.fullmove_number() as u32 - 1) * 2 + (board.side_to_move() == Color::Black) as u32
     }
 
     pub fn start(&mut self, block_height: u64) {
@@ -89,12 +147,47 @@ impl Match {
         self.start = block_height;
     }
 
-    pub fn play_move(&mut self, mov: &Move, block_height: u64) -> Result<&Self, IllegalMoveError> {
-        let mut board = Match::decode_board(&self.board)
-            .expect("Board encoding should always be correct as it is controlled by Match.");
-
+    /// Plays `mov`, returning it as recorded in the move history. The history
+    /// is stored apart from the match, see `MATCH_MOVES`.
+    pub fn play_move(
+        &mut self,
+        mov: &Move,
+        block_height: u64,
+    ) -> Result<PlayedMove, IllegalMoveError> {
+        let mut board = self.position();
         board.try_play(*mov)?;
 
+        self.set_board(&board);
+        self.last_move = block_height;
+        Ok(PlayedMove {
+            mov: mov.to_string(),
+            block_height,
+        })
+    }
+
+    /// Takes back the moves played after `kept`, the start of the move
+    /// history, replaying it from the starting position. `last_move` goes
+    /// back to the height of the last kept move, or to the start of the match.
+    pub fn take_back(&mut self, kept: &[PlayedMove]) -> Result<&Self, IllegalMoveError> {
+        let mut board = Board::default();
+        for played in kept {
+            let mov = Match::decode_move(&played.mov)
+                .expect("Move history should always be correct as it is controlled by Match.");
+            board.try_play(mov)?;
+        }
+
+        self.set_board(&board);
+        self.classify_opening(kept);
+        self.last_move = kept.last().map_or(self.start, |played| played.block_height);
+        Ok(self)
+    }
+
+    /// Classifies the opening from the start of the move history.
+    pub fn classify_opening(&mut self, history: &[PlayedMove]) {
+        self.opening = classify(history.iter().map(|played| played.mov.as_str()));
+    }
+
+    fn set_board(&mut self, board: &Board) {
         self.state = match board.status() {
             GameStatus::Ongoing => match board.side_to_move() {
                 Color::White => MatchState::OnGoing(NextMove::Whites),
@@ -103,9 +196,7 @@ impl Match {
             GameStatus::Won => MatchState::Won,
             GameStatus::Drawn => MatchState::Drawn,
         };
-        self.board = Match::encode_board(&board);
-        self.last_move = block_height;
-        Ok(self)
+        self.board = pack_board(board).into();
     }
 
     pub fn decode_board(board: &str) -> Result<Board, FenParseError> {
@@ -122,7 +213,32 @@ impl Match {
 
     #[cfg(test)]
     pub(crate) fn set_board_state(mut self, board: String) -> Self {
-        self.board = board;
+        self.board = pack_board(&Match::decode_board(&board).unwrap()).into();
         self
     }
 }
+
+impl TryFrom<v0_3::Match> for Match {
+    type Error = StdError;
+
+    // v0.3 matches were all rated, and their move history is unknown
+    fn try_from(legacy: v0_3::Match) -> StdResult<Self> {
+        let board = Match::decode_board(&legacy.board).map_err(|_| {
+            StdError::parse_err("cw_chess::migrations::v0_3::Match", "invalid board")
+        })?;
+        Ok(Self {
+            challenger: legacy.challenger,
+            opponent: legacy.opponent,
+            board: pack_board(&board).into(),
+            state: legacy.state,
+            nonce: legacy.nonce,
+            style: MatchStyle::Daily,
+            rated: true,
+            last_move: legacy.last_move,
+            start: legacy.start,
+            bet: legacy.bet,
+            tournament: None,
+            opening: None,
+        })
+    }
+}
@@ -1,9 +1,23 @@
 use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::Addr;
-// use cosmwasm_std::{to_json_binary, Addr, CosmosMsg, StdResult, WasmMsg};
-// use crate::msg::ExecuteMsg;
+use cosmwasm_std::{to_json_binary, Addr, Coin, CosmosMsg, QuerierWrapper, StdResult, WasmMsg};
+
+use crate::challenge::ChallengePolicy;
+use crate::game::{Adjudication, MatchResult, MatchStyle};
+use crate::ibc::{IbcEscrow, IbcOpponent};
+use crate::market::Market;
+use crate::msg::{
+    BracketResponse, ExecuteMsg, HeadToHeadResponse, HooksResponse, IbcPeersResponse,
+    MatchResponse, PlayerStatsResponse, QueryMsg, SeasonLeaderboardResponse, StandingsResponse,
+    ValidateLineResponse, VotesResponse,
+};
+use crate::profile::Profile;
+use crate::puzzle::{Puzzle, PuzzleId};
+use crate::season::{Season, SeasonId};
+use crate::team::{TeamMatch, TeamMatchId, TeamTiebreak};
+use crate::tournament::{Color, Tournament, TournamentId, TournamentKind};
 
 /// CwChessContract is a wrapper around Addr that provides a lot of helpers
 /// for working with this.
@@ -15,13 +29,713 @@ impl CwChessContract {
         self.0.clone()
     }
 
-    // pub fn call<T: Into<ExecuteMsg>>(&self, msg: T) -> StdResult<CosmosMsg> {
-    //     let msg = to_json_binary(&msg.into())?;
-    //     Ok(WasmMsg::Execute {
-    //         contract_addr: self.addr().into(),
-    //         msg,
-    //         funds: vec![],
-    //     }
-    //     .into())
-    // }
+    pub fn call<T: Into<ExecuteMsg>>(&self, msg: T, funds: Vec<Coin>) -> StdResult<CosmosMsg> {
+        let msg = to_json_binary(&msg.into())?;
+        Ok(WasmMsg::Execute {
+            contract_addr: self.addr().into(),
+            msg,
+            funds,
+        }
+        .into())
+    }
+
+    pub fn query<T: DeserializeOwned>(
+        &self,
+        querier: &QuerierWrapper,
+        msg: QueryMsg,
+    ) -> StdResult<T> {
+        querier.query_wasm_smart(self.addr(), &msg)
+    }
+
+    /// Challenges `opponent`, an address or nickname, staking `bet`.
+    pub fn create_match(
+        &self,
+        opponent: impl Into<String>,
+        rated: bool,
+        arbiter: Option<Addr>,
+        bet: Coin,
+    ) -> StdResult<CosmosMsg> {
+        let opponent = opponent.into();
+        self.call(
+            ExecuteMsg::CreateMatch {
+                opponent,
+                rated: Some(rated),
+                arbiter,
+            },
+            vec![bet],
+        )
+    }
+
+    pub fn abort_match(&self, match_id: impl Into<String>) -> StdResult<CosmosMsg> {
+        let match_id = match_id.into();
+        self.call(ExecuteMsg::AbortMatch { match_id }, vec![])
+    }
+
+    /// Accepts a challenge, staking the same `bet` as the challenger and
+    /// agreeing to its arbiter.
+    pub fn join_match(
+        &self,
+        match_id: impl Into<String>,
+        expected_arbiter: Option<Addr>,
+        bet: Coin,
+    ) -> StdResult<CosmosMsg> {
+        let match_id = match_id.into();
+        self.call(
+            ExecuteMsg::JoinMatch {
+                match_id,
+                expected_arbiter,
+            },
+            vec![bet],
+        )
+    }
+
+    pub fn make_move(
+        &self,
+        match_id: impl Into<String>,
+        move_fen: impl Into<String>,
+    ) -> StdResult<CosmosMsg> {
+        let (match_id, move_fen) = (match_id.into(), move_fen.into());
+        self.call(ExecuteMsg::MakeMove { match_id, move_fen }, vec![])
+    }
+
+    pub fn resign(&self, match_id: impl Into<String>) -> StdResult<CosmosMsg> {
+        let match_id = match_id.into();
+        self.call(ExecuteMsg::Resign { match_id }, vec![])
+    }
+
+    pub fn offer_draw(&self, match_id: impl Into<String>) -> StdResult<CosmosMsg> {
+        let match_id = match_id.into();
+        self.call(ExecuteMsg::OfferDraw { match_id }, vec![])
+    }
+
+    pub fn request_takeback(
+        &self,
+        match_id: impl Into<String>,
+        plies: u32,
+    ) -> StdResult<CosmosMsg> {
+        let match_id = match_id.into();
+        self.call(ExecuteMsg::RequestTakeback { match_id, plies }, vec![])
+    }
+
+    pub fn accept_takeback(&self, match_id: impl Into<String>) -> StdResult<CosmosMsg> {
+        let match_id = match_id.into();
+        self.call(ExecuteMsg::AcceptTakeback { match_id }, vec![])
+    }
+
+    pub fn set_conditional_moves(
+        &self,
+        match_id: impl Into<String>,
+        lines: Vec<Vec<String>>,
+    ) -> StdResult<CosmosMsg> {
+        let match_id = match_id.into();
+        self.call(ExecuteMsg::SetConditionalMoves { match_id, lines }, vec![])
+    }
+
+    pub fn grant_move_key(
+        &self,
+        delegate: Addr,
+        expires: u64,
+        match_id: Option<String>,
+    ) -> StdResult<CosmosMsg> {
+        self.call(
+            ExecuteMsg::GrantMoveKey {
+                delegate,
+                expires,
+                match_id,
+            },
+            vec![],
+        )
+    }
+
+    pub fn revoke_move_key(&self, delegate: Addr) -> StdResult<CosmosMsg> {
+        self.call(ExecuteMsg::RevokeMoveKey { delegate }, vec![])
+    }
+
+    pub fn register_bot_callback(&self) -> StdResult<CosmosMsg> {
+        self.call(ExecuteMsg::RegisterBotCallback {}, vec![])
+    }
+
+    pub fn unregister_bot_callback(&self) -> StdResult<CosmosMsg> {
+        self.call(ExecuteMsg::UnregisterBotCallback {}, vec![])
+    }
+
+    /// Offers a rematch of a finished match, escrowing the same `bet`.
+    pub fn offer_rematch(&self, match_id: impl Into<String>, bet: Coin) -> StdResult<CosmosMsg> {
+        let match_id = match_id.into();
+        self.call(ExecuteMsg::OfferRematch { match_id }, vec![bet])
+    }
+
+    pub fn accept_rematch(&self, match_id: impl Into<String>, bet: Coin) -> StdResult<CosmosMsg> {
+        let match_id = match_id.into();
+        self.call(ExecuteMsg::AcceptRematch { match_id }, vec![bet])
+    }
+
+    pub fn cancel_rematch(&self, match_id: impl Into<String>) -> StdResult<CosmosMsg> {
+        let match_id = match_id.into();
+        self.call(ExecuteMsg::CancelRematch { match_id }, vec![])
+    }
+
+    pub fn update_arbiter(&self, arbiter: Addr) -> StdResult<CosmosMsg> {
+        self.call(ExecuteMsg::UpdateArbiter { arbiter }, vec![])
+    }
+
+    pub fn adjudicate(
+        &self,
+        match_id: impl Into<String>,
+        result: Adjudication,
+        reason: impl Into<String>,
+    ) -> StdResult<CosmosMsg> {
+        let (match_id, reason) = (match_id.into(), reason.into());
+        self.call(
+            ExecuteMsg::Adjudicate {
+                match_id,
+                result,
+                reason,
+            },
+            vec![],
+        )
+    }
+
+    pub fn migrate_matches(&self, limit: Option<u32>) -> StdResult<CosmosMsg> {
+        self.call(ExecuteMsg::MigrateMatches { limit }, vec![])
+    }
+
+    pub fn add_hook(&self, addr: Addr) -> StdResult<CosmosMsg> {
+        self.call(ExecuteMsg::AddHook { addr }, vec![])
+    }
+
+    pub fn remove_hook(&self, addr: Addr) -> StdResult<CosmosMsg> {
+        self.call(ExecuteMsg::RemoveHook { addr }, vec![])
+    }
+
+    pub fn create_tournament(
+        &self,
+        kind: TournamentKind,
+        entry_fee: Coin,
+        max_players: u32,
+        time_control: MatchStyle,
+        prize_split: Vec<u32>,
+    ) -> StdResult<CosmosMsg> {
+        self.call(
+            ExecuteMsg::CreateTournament {
+                kind,
+                entry_fee,
+                max_players,
+                time_control,
+                prize_split,
+            },
+            vec![],
+        )
+    }
+
+    /// Registers for a tournament, paying its `entry_fee`.
+    pub fn register_tournament(
+        &self,
+        tournament_id: TournamentId,
+        entry_fee: Coin,
+    ) -> StdResult<CosmosMsg> {
+        self.call(
+            ExecuteMsg::RegisterTournament { tournament_id },
+            vec![entry_fee],
+        )
+    }
+
+    pub fn start_tournament(
+        &self,
+        tournament_id: TournamentId,
+        seed: Option<String>,
+    ) -> StdResult<CosmosMsg> {
+        self.call(
+            ExecuteMsg::StartTournament {
+                tournament_id,
+                seed,
+            },
+            vec![],
+        )
+    }
+
+    pub fn pair_next_round(&self, tournament_id: TournamentId) -> StdResult<CosmosMsg> {
+        self.call(ExecuteMsg::PairNextRound { tournament_id }, vec![])
+    }
+
+    pub fn cancel_tournament(&self, tournament_id: TournamentId) -> StdResult<CosmosMsg> {
+        self.call(ExecuteMsg::CancelTournament { tournament_id }, vec![])
+    }
+
+    pub fn place_side_bet(
+        &self,
+        match_id: impl Into<String>,
+        outcome: MatchResult,
+        amount: Coin,
+    ) -> StdResult<CosmosMsg> {
+        let match_id = match_id.into();
+        self.call(ExecuteMsg::PlaceSideBet { match_id, outcome }, vec![amount])
+    }
+
+    pub fn update_side_bet_config(
+        &self,
+        close_after_plies: u32,
+        house_fee_percent: u32,
+    ) -> StdResult<CosmosMsg> {
+        self.call(
+            ExecuteMsg::UpdateSideBetConfig {
+                close_after_plies,
+                house_fee_percent,
+            },
+            vec![],
+        )
+    }
+
+    pub fn set_profile(&self, profile: Profile) -> StdResult<CosmosMsg> {
+        let Profile {
+            nickname,
+            country,
+            avatar_uri,
+            bio,
+        } = profile;
+        self.call(
+            ExecuteMsg::SetProfile {
+                nickname,
+                country,
+                avatar_uri,
+                bio,
+            },
+            vec![],
+        )
+    }
+
+    /// Starts a season, escrowing its `prize_pool`.
+    pub fn start_season(&self, prize_pool: Coin, prize_split: Vec<u32>) -> StdResult<CosmosMsg> {
+        let funds = match prize_pool.amount.is_zero() {
+            true => vec![],
+            false => vec![prize_pool.clone()],
+        };
+        self.call(
+            ExecuteMsg::StartSeason {
+                prize_pool,
+                prize_split,
+            },
+            funds,
+        )
+    }
+
+    pub fn end_season(&self, limit: Option<u32>) -> StdResult<CosmosMsg> {
+        self.call(ExecuteMsg::EndSeason { limit }, vec![])
+    }
+
+    /// Posts a puzzle, escrowing its `reward`.
+    pub fn post_puzzle(
+        &self,
+        fen: impl Into<String>,
+        mate_in: u32,
+        reward: Coin,
+    ) -> StdResult<CosmosMsg> {
+        let fen = fen.into();
+        self.call(
+            ExecuteMsg::PostPuzzle {
+                fen,
+                mate_in,
+                reward: reward.clone(),
+            },
+            vec![reward],
+        )
+    }
+
+    pub fn commit_solution(
+        &self,
+        puzzle_id: PuzzleId,
+        commitment: impl Into<String>,
+    ) -> StdResult<CosmosMsg> {
+        let commitment = commitment.into();
+        self.call(
+            ExecuteMsg::CommitSolution {
+                puzzle_id,
+                commitment,
+            },
+            vec![],
+        )
+    }
+
+    pub fn solve_puzzle(
+        &self,
+        puzzle_id: PuzzleId,
+        line: Vec<String>,
+        salt: impl Into<String>,
+    ) -> StdResult<CosmosMsg> {
+        let salt = salt.into();
+        self.call(
+            ExecuteMsg::SolvePuzzle {
+                puzzle_id,
+                line,
+                salt,
+            },
+            vec![],
+        )
+    }
+
+    pub fn withdraw_puzzle(&self, puzzle_id: PuzzleId) -> StdResult<CosmosMsg> {
+        self.call(ExecuteMsg::WithdrawPuzzle { puzzle_id }, vec![])
+    }
+
+    pub fn set_challenge_policy(&self, policy: ChallengePolicy) -> StdResult<CosmosMsg> {
+        let ChallengePolicy {
+            blocked,
+            allow_only,
+            min_rating,
+            min_bet,
+            max_bet,
+        } = policy;
+        self.call(
+            ExecuteMsg::SetChallengePolicy {
+                blocked,
+                allow_only,
+                min_rating,
+                min_bet,
+                max_bet,
+            },
+            vec![],
+        )
+    }
+
+    pub fn create_vote_match(
+        &self,
+        voters: Vec<Addr>,
+        voters_color: Color,
+        voting_period: u64,
+    ) -> StdResult<CosmosMsg> {
+        self.call(
+            ExecuteMsg::CreateVoteMatch {
+                voters,
+                voters_color,
+                voting_period,
+            },
+            vec![],
+        )
+    }
+
+    pub fn vote_move(
+        &self,
+        match_id: impl Into<String>,
+        move_fen: impl Into<String>,
+    ) -> StdResult<CosmosMsg> {
+        let (match_id, move_fen) = (match_id.into(), move_fen.into());
+        self.call(ExecuteMsg::VoteMove { match_id, move_fen }, vec![])
+    }
+
+    pub fn finalize_vote(&self, match_id: impl Into<String>) -> StdResult<CosmosMsg> {
+        let match_id = match_id.into();
+        self.call(ExecuteMsg::FinalizeVote { match_id }, vec![])
+    }
+
+    /// Creates a team match, escrowing team A's `stake`.
+    pub fn create_team_match(
+        &self,
+        team_a: Vec<Addr>,
+        team_b: Vec<Addr>,
+        boards: u32,
+        stake: Coin,
+        tiebreak: TeamTiebreak,
+    ) -> StdResult<CosmosMsg> {
+        let funds = match stake.amount.is_zero() {
+            true => vec![],
+            false => vec![stake.clone()],
+        };
+        self.call(
+            ExecuteMsg::CreateTeamMatch {
+                team_a,
+                team_b,
+                boards,
+                stake,
+                tiebreak,
+            },
+            funds,
+        )
+    }
+
+    /// Accepts a team match for team B, escrowing the same `stake`.
+    pub fn accept_team_match(
+        &self,
+        team_match_id: TeamMatchId,
+        stake: Coin,
+    ) -> StdResult<CosmosMsg> {
+        let funds = match stake.amount.is_zero() {
+            true => vec![],
+            false => vec![stake],
+        };
+        self.call(ExecuteMsg::AcceptTeamMatch { team_match_id }, funds)
+    }
+
+    pub fn confirm_team_board(&self, team_match_id: TeamMatchId) -> StdResult<CosmosMsg> {
+        self.call(ExecuteMsg::ConfirmTeamBoard { team_match_id }, vec![])
+    }
+
+    pub fn abort_team_match(&self, team_match_id: TeamMatchId) -> StdResult<CosmosMsg> {
+        self.call(ExecuteMsg::AbortTeamMatch { team_match_id }, vec![])
+    }
+
+    /// Challenges `opponent` on the chain across `channel_id`, escrowing `bet` here.
+    pub fn create_ibc_match(
+        &self,
+        channel_id: impl Into<String>,
+        opponent: impl Into<String>,
+        opponent_bet: Coin,
+        payout_address: impl Into<String>,
+        bet: Coin,
+    ) -> StdResult<CosmosMsg> {
+        self.call(
+            ExecuteMsg::CreateIbcMatch {
+                channel_id: channel_id.into(),
+                opponent: opponent.into(),
+                opponent_bet,
+                payout_address: payout_address.into(),
+            },
+            vec![bet],
+        )
+    }
+
+    /// Joins a match hosted across `channel_id`, escrowing `stake` here.
+    pub fn join_ibc_match(
+        &self,
+        channel_id: impl Into<String>,
+        match_id: impl Into<String>,
+        payout_address: impl Into<String>,
+        stake: Coin,
+    ) -> StdResult<CosmosMsg> {
+        self.call(
+            ExecuteMsg::JoinIbcMatch {
+                channel_id: channel_id.into(),
+                match_id: match_id.into(),
+                payout_address: payout_address.into(),
+            },
+            vec![stake],
+        )
+    }
+
+    pub fn relay_move(
+        &self,
+        channel_id: impl Into<String>,
+        match_id: impl Into<String>,
+        move_fen: impl Into<String>,
+    ) -> StdResult<CosmosMsg> {
+        self.call(
+            ExecuteMsg::RelayMove {
+                channel_id: channel_id.into(),
+                match_id: match_id.into(),
+                move_fen: move_fen.into(),
+            },
+            vec![],
+        )
+    }
+
+    pub fn add_ibc_peer(
+        &self,
+        connection_id: impl Into<String>,
+        port_id: impl Into<String>,
+    ) -> StdResult<CosmosMsg> {
+        self.call(
+            ExecuteMsg::AddIbcPeer {
+                connection_id: connection_id.into(),
+                port_id: port_id.into(),
+            },
+            vec![],
+        )
+    }
+
+    pub fn remove_ibc_peer(
+        &self,
+        connection_id: impl Into<String>,
+        port_id: impl Into<String>,
+    ) -> StdResult<CosmosMsg> {
+        self.call(
+            ExecuteMsg::RemoveIbcPeer {
+                connection_id: connection_id.into(),
+                port_id: port_id.into(),
+            },
+            vec![],
+        )
+    }
+
+    pub fn chess_match(
+        &self,
+        querier: &QuerierWrapper,
+        match_id: impl Into<String>,
+    ) -> StdResult<MatchResponse> {
+        let match_id = match_id.into();
+        self.query(querier, QueryMsg::Match { match_id })
+    }
+
+    pub fn tournament(
+        &self,
+        querier: &QuerierWrapper,
+        tournament_id: TournamentId,
+    ) -> StdResult<Tournament> {
+        self.query(querier, QueryMsg::Tournament { tournament_id })
+    }
+
+    pub fn standings(
+        &self,
+        querier: &QuerierWrapper,
+        tournament_id: TournamentId,
+    ) -> StdResult<StandingsResponse> {
+        self.query(querier, QueryMsg::Standings { tournament_id })
+    }
+
+    pub fn bracket(
+        &self,
+        querier: &QuerierWrapper,
+        tournament_id: TournamentId,
+    ) -> StdResult<BracketResponse> {
+        self.query(querier, QueryMsg::Bracket { tournament_id })
+    }
+
+    pub fn side_bet_market(
+        &self,
+        querier: &QuerierWrapper,
+        match_id: impl Into<String>,
+    ) -> StdResult<Option<Market>> {
+        let match_id = match_id.into();
+        self.query(querier, QueryMsg::SideBetMarket { match_id })
+    }
+
+    pub fn hooks(&self, querier: &QuerierWrapper) -> StdResult<HooksResponse> {
+        self.query(querier, QueryMsg::Hooks {})
+    }
+
+    pub fn arbiter(&self, querier: &QuerierWrapper, match_id: Option<String>) -> StdResult<Addr> {
+        self.query(querier, QueryMsg::Arbiter { match_id })
+    }
+
+    pub fn player_stats(
+        &self,
+        querier: &QuerierWrapper,
+        player: impl Into<String>,
+    ) -> StdResult<PlayerStatsResponse> {
+        let player = player.into();
+        self.query(querier, QueryMsg::PlayerStats { player })
+    }
+
+    pub fn head_to_head(
+        &self,
+        querier: &QuerierWrapper,
+        a: impl Into<String>,
+        b: impl Into<String>,
+    ) -> StdResult<HeadToHeadResponse> {
+        let (a, b) = (a.into(), b.into());
+        self.query(querier, QueryMsg::HeadToHead { a, b })
+    }
+
+    pub fn profile(
+        &self,
+        querier: &QuerierWrapper,
+        address: impl Into<String>,
+    ) -> StdResult<Option<Profile>> {
+        let address = address.into();
+        self.query(querier, QueryMsg::Profile { address })
+    }
+
+    pub fn resolve_nickname(
+        &self,
+        querier: &QuerierWrapper,
+        nickname: impl Into<String>,
+    ) -> StdResult<Option<Addr>> {
+        let nickname = nickname.into();
+        self.query(querier, QueryMsg::ResolveNickname { nickname })
+    }
+
+    pub fn current_season(&self, querier: &QuerierWrapper) -> StdResult<Option<SeasonId>> {
+        self.query(querier, QueryMsg::CurrentSeason {})
+    }
+
+    pub fn season(&self, querier: &QuerierWrapper, season_id: SeasonId) -> StdResult<Season> {
+        self.query(querier, QueryMsg::Season { season_id })
+    }
+
+    pub fn season_leaderboard(
+        &self,
+        querier: &QuerierWrapper,
+        season_id: SeasonId,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<SeasonLeaderboardResponse> {
+        self.query(
+            querier,
+            QueryMsg::SeasonLeaderboard {
+                season_id,
+                start_after,
+                limit,
+            },
+        )
+    }
+
+    pub fn puzzle(&self, querier: &QuerierWrapper, puzzle_id: PuzzleId) -> StdResult<Puzzle> {
+        self.query(querier, QueryMsg::Puzzle { puzzle_id })
+    }
+
+    pub fn team_match(
+        &self,
+        querier: &QuerierWrapper,
+        team_match_id: TeamMatchId,
+    ) -> StdResult<TeamMatch> {
+        self.query(querier, QueryMsg::TeamMatch { team_match_id })
+    }
+
+    pub fn votes(
+        &self,
+        querier: &QuerierWrapper,
+        match_id: impl Into<String>,
+    ) -> StdResult<VotesResponse> {
+        let match_id = match_id.into();
+        self.query(querier, QueryMsg::Votes { match_id })
+    }
+
+    pub fn challenge_policy(
+        &self,
+        querier: &QuerierWrapper,
+        player: impl Into<String>,
+    ) -> StdResult<ChallengePolicy> {
+        let player = player.into();
+        self.query(querier, QueryMsg::ChallengePolicy { player })
+    }
+
+    pub fn ibc_opponent(
+        &self,
+        querier: &QuerierWrapper,
+        match_id: impl Into<String>,
+    ) -> StdResult<Option<IbcOpponent>> {
+        let match_id = match_id.into();
+        self.query(querier, QueryMsg::IbcOpponent { match_id })
+    }
+
+    pub fn ibc_escrow(
+        &self,
+        querier: &QuerierWrapper,
+        channel_id: impl Into<String>,
+        match_id: impl Into<String>,
+        player: impl Into<String>,
+    ) -> StdResult<Option<IbcEscrow>> {
+        let channel_id = channel_id.into();
+        let match_id = match_id.into();
+        let player = player.into();
+        self.query(
+            querier,
+            QueryMsg::IbcEscrow {
+                channel_id,
+                match_id,
+                player,
+            },
+        )
+    }
+
+    pub fn ibc_peers(&self, querier: &QuerierWrapper) -> StdResult<IbcPeersResponse> {
+        self.query(querier, QueryMsg::IbcPeers {})
+    }
+
+    pub fn validate_line(
+        &self,
+        querier: &QuerierWrapper,
+        start_fen: Option<String>,
+        moves: Vec<String>,
+    ) -> StdResult<ValidateLineResponse> {
+        self.query(querier, QueryMsg::ValidateLine { start_fen, moves })
+    }
 }
@@ -0,0 +1,356 @@
+use cosmwasm_std::{
+    ensure_eq, from_json, to_json_binary, Addr, Binary, Coin, DepsMut, Env, Event,
+    IbcBasicResponse, IbcChannel, IbcChannelCloseMsg, IbcChannelConnectMsg, IbcChannelOpenMsg,
+    IbcChannelOpenResponse, IbcMsg, IbcOrder, IbcPacket, IbcPacketAckMsg, IbcPacketReceiveMsg,
+    IbcPacketTimeoutMsg, IbcReceiveResponse, IbcTimeout, MessageInfo, Never, Response, StdAck,
+    Storage, SubMsg, WasmMsg,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::contract::{exec, IBC_RECEIVE_REPLY_ID};
+use crate::error::{ContractError, InvalidIbcChannelReason};
+use crate::migrations;
+use crate::msg::{ChessPacket, ExecuteMsg};
+use crate::state::{IBC_CHANNELS, IBC_PEERS};
+
+pub const IBC_VERSION: &str = "cw-chess-1";
+pub const IBC_ORDER: IbcOrder = IbcOrder::Unordered;
+
+// Seconds a packet has to be relayed before it times out
+pub const PACKET_LIFETIME: u64 = 60 * 60 * 24;
+
+/// Player of a match hosted here who plays from the chain across `channel_id`,
+/// under the contract's address. Their stake is escrowed on their chain.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct IbcOpponent {
+    pub channel_id: String,
+    // Address on the opponent's chain
+    pub player: String,
+    // Stake the opponent escrows on their chain
+    pub stake: Coin,
+    // Challenger's address on the opponent's chain, paid the opponent's stake
+    pub challenger_payout: String,
+    // Opponent's address on this chain, paid the challenger's stake, once joined
+    pub payout_address: Option<Addr>,
+}
+
+/// Stake of a player here in a match hosted across `channel_id`, released
+/// when the host settles the match.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct IbcEscrow {
+    pub player: Addr,
+    pub stake: Coin,
+    // Whether the host accepted the player into the match
+    pub joined: bool,
+}
+
+/// Peer contract channels may be opened to: its port on the other end of
+/// our `connection_id`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct IbcPeer {
+    pub connection_id: String,
+    pub port_id: String,
+}
+
+pub fn ibc_timeout(env: &Env) -> IbcTimeout {
+    IbcTimeout::with_timestamp(env.block.time.plus_seconds(PACKET_LIFETIME))
+}
+
+const BECH32_CHARSET: &[u8] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+
+/// Whether `address` is a well-formed lowercase bech32 address, with a valid
+/// checksum. Addresses on another chain can't be checked any further here.
+pub fn is_bech32_address(address: &str) -> bool {
+    let Some((hrp, data)) = address.rsplit_once('1') else {
+        return false;
+    };
+    if hrp.is_empty()
+        || data.len() < 6
+        || !hrp
+            .bytes()
+            .all(|c| (33..=126).contains(&c) && !c.is_ascii_uppercase())
+    {
+        return false;
+    }
+    let Some(data) = data
+        .bytes()
+        .map(|c| BECH32_CHARSET.iter().position(|&d| d == c).map(|d| d as u8))
+        .collect::<Option<Vec<u8>>>()
+    else {
+        return false;
+    };
+    let hrp_expanded = hrp
+        .bytes()
+        .map(|c| c >> 5)
+        .chain([0])
+        .chain(hrp.bytes().map(|c| c & 31));
+    bech32_polymod(hrp_expanded.chain(data)) == 1
+}
+
+fn bech32_polymod(values: impl Iterator<Item = u8>) -> u32 {
+    const GENERATOR: [u32; 5] = [0x3b6a57b2, 0x26508e6d, 0x1ea119fa, 0x3d4233dd, 0x2a1462b3];
+    let mut checksum: u32 = 1;
+    for value in values {
+        let top = checksum >> 25;
+        checksum = (checksum & 0x1ffffff) << 5 ^ value as u32;
+        for (i, generator) in GENERATOR.iter().enumerate() {
+            if (top >> i) & 1 == 1 {
+                checksum ^= generator;
+            }
+        }
+    }
+    checksum
+}
+
+pub fn channel_open(
+    deps: DepsMut,
+    _env: Env,
+    msg: IbcChannelOpenMsg,
+) -> Result<IbcChannelOpenResponse, ContractError> {
+    validate_channel(deps.storage, msg.channel(), msg.counterparty_version())?;
+    Ok(())
+}
+
+pub fn channel_connect(
+    deps: DepsMut,
+    _env: Env,
+    msg: IbcChannelConnectMsg,
+) -> Result<IbcBasicResponse, ContractError> {
+    let channel = msg.channel();
+    validate_channel(deps.storage, channel, msg.counterparty_version())?;
+    IBC_CHANNELS.save(deps.storage, &channel.endpoint.channel_id, channel)?;
+
+    Ok(IbcBasicResponse::new()
+        .add_attribute("action", "ibc_channel_connect")
+        .add_attribute("channel_id", &channel.endpoint.channel_id))
+}
+
+/// Closes a channel to new matches. Packets of matches already relayed over
+/// it can no longer be delivered.
+pub fn channel_close(
+    deps: DepsMut,
+    _env: Env,
+    msg: IbcChannelCloseMsg,
+) -> Result<IbcBasicResponse, ContractError> {
+    let channel = msg.channel();
+    IBC_CHANNELS.remove(deps.storage, &channel.endpoint.channel_id);
+
+    Ok(IbcBasicResponse::new()
+        .add_attribute("action", "ibc_channel_close")
+        .add_attribute("channel_id", &channel.endpoint.channel_id))
+}
+
+/// Handles a packet from a peer contract in a `ReceiveIbcPacket` submessage
+/// to the contract itself, so that a failure reverts the handler's changes.
+/// Failures are acknowledged with the error by `reply`, so the peer can undo
+/// its side.
+pub fn packet_receive(
+    deps: DepsMut,
+    env: Env,
+    msg: IbcPacketReceiveMsg,
+) -> Result<IbcReceiveResponse, Never> {
+    // Checked here rather than by the handler, whose errors the chain
+    // redacts, so that the peer can tell to send the packet again
+    if migrations::in_progress(deps.storage) {
+        let err = ContractError::MigrationInProgress {};
+        return Ok(IbcReceiveResponse::new()
+            .set_ack(StdAck::error(err.to_string()))
+            .add_attribute("action", "ibc_packet_receive")
+            .add_attribute("error", err.to_string()));
+    }
+    let packet = msg.packet;
+    let handler = to_json_binary(&ExecuteMsg::ReceiveIbcPacket {
+        channel_id: packet.dest.channel_id,
+        data: packet.data,
+    });
+    match handler {
+        Ok(handler) => Ok(IbcReceiveResponse::new()
+            .set_ack(StdAck::success(b"\x01"))
+            .add_attribute("action", "ibc_packet_receive")
+            .add_submessage(SubMsg::reply_on_error(
+                WasmMsg::Execute {
+                    contract_addr: env.contract.address.to_string(),
+                    msg: handler,
+                    funds: vec![],
+                },
+                IBC_RECEIVE_REPLY_ID,
+            ))),
+        Err(err) => Ok(IbcReceiveResponse::new()
+            .set_ack(StdAck::error(err.to_string()))
+            .add_attribute("action", "ibc_packet_receive")
+            .add_attribute("error", err.to_string())),
+    }
+}
+
+/// Handles the data of a packet received on `channel_id`, dispatched by
+/// `packet_receive`.
+pub fn receive(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    channel_id: String,
+    data: Binary,
+) -> Result<Response, ContractError> {
+    ensure_eq!(
+        info.sender,
+        env.contract.address,
+        ContractError::Unauthorized {}
+    );
+    match from_json(data)? {
+        ChessPacket::JoinMatch {
+            match_id,
+            player,
+            stake,
+            payout_address,
+        } => exec::receive_ibc_join(
+            deps,
+            env,
+            channel_id,
+            match_id,
+            player,
+            stake,
+            payout_address,
+        ),
+        ChessPacket::MakeMove {
+            match_id,
+            player,
+            move_fen,
+        } => exec::receive_ibc_move(deps, env, channel_id, match_id, player, move_fen),
+        ChessPacket::SettleMatch {
+            match_id,
+            player,
+            pay_to,
+        } => exec::receive_ibc_settlement(deps, channel_id, match_id, player, pay_to),
+    }
+}
+
+pub fn packet_ack(
+    deps: DepsMut,
+    env: Env,
+    msg: IbcPacketAckMsg,
+) -> Result<IbcBasicResponse, ContractError> {
+    let ack: StdAck = from_json(&msg.acknowledgement.data)?;
+    let error = match ack {
+        StdAck::Success(_) => None,
+        StdAck::Error(error) => Some(error),
+    };
+    packet_outcome(deps, &env, &msg.original_packet, error)
+}
+
+pub fn packet_timeout(
+    deps: DepsMut,
+    env: Env,
+    msg: IbcPacketTimeoutMsg,
+) -> Result<IbcBasicResponse, ContractError> {
+    let packet = &msg.packet;
+    match from_json(&packet.data)? {
+        // The peer still holds the opponent's stake, so settlement is retried
+        ChessPacket::SettleMatch { match_id, .. } => {
+            let res = resend_settlement(&env, packet, match_id);
+            Ok(IbcBasicResponse::new()
+                .add_attribute("action", "ibc_packet_timeout")
+                .add_events(res.events)
+                .add_submessages(res.messages))
+        }
+        _ => packet_outcome(deps, &env, packet, Some("timeout".to_string())),
+    }
+}
+
+fn resend_settlement(env: &Env, packet: &IbcPacket, match_id: String) -> Response {
+    Response::new()
+        .add_message(IbcMsg::SendPacket {
+            channel_id: packet.src.channel_id.clone(),
+            data: packet.data.clone(),
+            timeout: ibc_timeout(env),
+        })
+        .add_event(
+            Event::new("ibc_settlement_resent")
+                .add_attribute("channel_id", &packet.src.channel_id)
+                .add_attribute("match_id", match_id),
+        )
+}
+
+/// Follows up on a packet this contract sent, given the error it failed with.
+fn packet_outcome(
+    deps: DepsMut,
+    env: &Env,
+    packet: &IbcPacket,
+    error: Option<String>,
+) -> Result<IbcBasicResponse, ContractError> {
+    let channel_id = packet.src.channel_id.clone();
+    let res = match (from_json(&packet.data)?, error) {
+        (
+            ChessPacket::JoinMatch {
+                match_id, player, ..
+            },
+            None,
+        ) => exec::ibc_join_accepted(deps, channel_id, match_id, player)?,
+        (
+            ChessPacket::JoinMatch {
+                match_id, player, ..
+            },
+            Some(error),
+        ) => exec::ibc_join_failed(deps, channel_id, match_id, player, error)?,
+        (_, None) => Response::new(),
+        (
+            ChessPacket::MakeMove {
+                match_id, move_fen, ..
+            },
+            Some(error),
+        ) => Response::new().add_event(
+            Event::new("ibc_move_failed")
+                .add_attribute("channel_id", channel_id)
+                .add_attribute("match_id", match_id)
+                .add_attribute("move", move_fen)
+                .add_attribute("error", error),
+        ),
+        // The peer only turns settlements down while it migrates
+        (ChessPacket::SettleMatch { match_id, .. }, Some(error))
+            if error == ContractError::MigrationInProgress {}.to_string() =>
+        {
+            resend_settlement(env, packet, match_id)
+        }
+        (ChessPacket::SettleMatch { match_id, .. }, Some(error)) => Response::new().add_event(
+            Event::new("ibc_settlement_failed")
+                .add_attribute("channel_id", channel_id)
+                .add_attribute("match_id", match_id)
+                .add_attribute("error", error),
+        ),
+    };
+
+    Ok(IbcBasicResponse::new()
+        .add_attribute("action", "ibc_packet_outcome")
+        .add_attributes(res.attributes)
+        .add_events(res.events)
+        .add_submessages(res.messages))
+}
+
+fn validate_channel(
+    storage: &dyn Storage,
+    channel: &IbcChannel,
+    counterparty_version: Option<&str>,
+) -> Result<(), ContractError> {
+    let peer = (
+        channel.connection_id.as_str(),
+        channel.counterparty_endpoint.port_id.as_str(),
+    );
+    if !IBC_PEERS.has(storage, peer) {
+        return Err(ContractError::InvalidIbcChannel {
+            reason: InvalidIbcChannelReason::UnknownPeer,
+        });
+    }
+    if channel.order != IBC_ORDER {
+        return Err(ContractError::InvalidIbcChannel {
+            reason: InvalidIbcChannelReason::Order,
+        });
+    }
+    if channel.version != IBC_VERSION
+        || counterparty_version.is_some_and(|version| version != IBC_VERSION)
+    {
+        return Err(ContractError::InvalidIbcChannel {
+            reason: InvalidIbcChannelReason::Version,
+        });
+    }
+    Ok(())
+}
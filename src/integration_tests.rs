@@ -4,16 +4,8 @@ mod tests {
     use crate::msg::InstantiateMsg;
     use cosmwasm_std::{Addr, Coin, Empty};
     // use cosmwasm_std::{Addr, Coin, Empty, Uint128};
-    use cw_multi_test::{App, AppBuilder, Contract, ContractWrapper, Executor};
-
-    pub fn chess_contract() -> Box<dyn Contract<Empty>> {
-        let contract = ContractWrapper::new(
-            crate::contract::execute,
-            crate::contract::instantiate,
-            crate::contract::query,
-        );
-        Box::new(contract)
-    }
+    use crate::testing::chess_contract;
+    use cw_multi_test::{App, AppBuilder, ContractWrapper, Executor};
 
     // const USER: &str = "USER";
     const ADMIN: &str = "ADMIN";
@@ -66,4 +58,486 @@ mod tests {
             let (_app, _contract) = proper_instantiate();
         }
     }
+
+    mod migrate {
+        use super::*;
+        use crate::contract::exec::match_id;
+        use crate::game::{Match, MatchState, MatchStyle, NextMove};
+        use crate::migrations::v0_3;
+        use crate::msg::{ExecuteMsg, MatchResponse, MigrateMsg, QueryMsg};
+        use crate::state::{self, MATCH_IDS, MATCH_MOVES, MIN_BET, NEXT_NONCE, PLAYER_MATCHES};
+        use crate::ContractError;
+        use cosmwasm_std::{
+            Binary, Deps, DepsMut, Env, MessageInfo, Response, StdError, StdResult,
+        };
+
+        const USER: &str = "USER";
+        const PLAYERS: [&str; 4] = ["alice", "bob", "carol", "dave"];
+        // Opponent in the match stored in the current layout
+        const LATE_PLAYER: &str = "erin";
+
+        /// Stand-in for the v0.3 contract, storing `PLAYERS.len()` matches
+        /// in the v0.3 layout, and one in the current layout as if it was
+        /// stored after the upgrade.
+        fn v0_3_instantiate(
+            deps: DepsMut,
+            _env: Env,
+            info: MessageInfo,
+            msg: InstantiateMsg,
+        ) -> StdResult<Response> {
+            cw2::set_contract_version(deps.storage, "crates.io:cw-chess", "0.3.0")?;
+            state::ADMIN.save(deps.storage, &info.sender)?;
+            MIN_BET.save(
+                deps.storage,
+                &(msg.min_bet.amount, msg.min_bet.denom.clone()),
+            )?;
+
+            for (nonce, opponent) in PLAYERS.iter().enumerate() {
+                let (challenger, opponent) = (Addr::unchecked(USER), Addr::unchecked(*opponent));
+                let id = match_id(&challenger, &opponent, nonce as u64);
+                v0_3::MATCHES.save(
+                    deps.storage,
+                    id,
+                    &v0_3::Match {
+                        challenger: challenger.clone(),
+                        opponent: opponent.clone(),
+                        board: "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1"
+                            .to_string(),
+                        state: MatchState::OnGoing(NextMove::Blacks),
+                        nonce: nonce as u64,
+                        last_move: 12,
+                        start: 10,
+                        bet: msg.min_bet.clone(),
+                    },
+                )?;
+                PLAYER_MATCHES.save(deps.storage, (&challenger, id), &())?;
+                PLAYER_MATCHES.save(deps.storage, (&opponent, id), &())?;
+                MATCH_IDS.save(deps.storage, nonce as u64, &id)?;
+            }
+
+            let nonce = PLAYERS.len() as u64;
+            let (challenger, opponent) = (Addr::unchecked(USER), Addr::unchecked(LATE_PLAYER));
+            let id = match_id(&challenger, &opponent, nonce);
+            let mut late = Match::new(challenger.clone(), opponent.clone(), nonce, msg.min_bet);
+            late.rated = false;
+            late.style = MatchStyle::Bullet;
+            late.start(10);
+            let played = late
+                .play_move(&"e2e4".parse().unwrap(), 12)
+                .map_err(|_| StdError::generic_err("illegal move"))?;
+            state::MATCHES.save(deps.storage, id, &late)?;
+            MATCH_MOVES.save(deps.storage, (id.as_slice(), 0), &played)?;
+            PLAYER_MATCHES.save(deps.storage, (&challenger, id), &())?;
+            PLAYER_MATCHES.save(deps.storage, (&opponent, id), &())?;
+            MATCH_IDS.save(deps.storage, nonce, &id)?;
+            NEXT_NONCE.save(deps.storage, &(nonce + 1))?;
+            Ok(Response::default())
+        }
+
+        fn v0_3_execute(
+            _deps: DepsMut,
+            _env: Env,
+            _info: MessageInfo,
+            _msg: Empty,
+        ) -> StdResult<Response> {
+            Err(StdError::generic_err("not supported"))
+        }
+
+        fn v0_3_query(_deps: Deps, _env: Env, _msg: Empty) -> StdResult<Binary> {
+            Err(StdError::generic_err("not supported"))
+        }
+
+        fn query_match(
+            app: &App,
+            contract: &CwChessContract,
+            nonce: u64,
+        ) -> StdResult<MatchResponse> {
+            let opponent = PLAYERS.get(nonce as usize).unwrap_or(&LATE_PLAYER);
+            let id = match_id(&Addr::unchecked(USER), &Addr::unchecked(*opponent), nonce);
+            app.wrap().query_wasm_smart(
+                contract.addr(),
+                &QueryMsg::Match {
+                    match_id: hex::encode(id),
+                },
+            )
+        }
+
+        #[test]
+        fn migrates_v0_3_matches_in_pages() {
+            let mut app = mock_app();
+            let v0_3_id = app.store_code(Box::new(ContractWrapper::new(
+                v0_3_execute,
+                v0_3_instantiate,
+                v0_3_query,
+            )));
+            let cw_chess_id = app.store_code(chess_contract());
+
+            let msg = InstantiateMsg {
+                min_bet: Coin::new(10, NATIVE_DENOM),
+            };
+            let addr = app
+                .instantiate_contract(
+                    v0_3_id,
+                    Addr::unchecked(ADMIN),
+                    &msg,
+                    &[],
+                    "cw-chess",
+                    Some(ADMIN.to_string()),
+                )
+                .unwrap();
+            let contract = CwChessContract(addr);
+
+            app.migrate_contract(
+                Addr::unchecked(ADMIN),
+                contract.addr(),
+                &MigrateMsg { limit: Some(3) },
+                cw_chess_id,
+            )
+            .unwrap();
+            let version = cw2::query_contract_info(&app.wrap(), contract.addr()).unwrap();
+            assert_eq!(env!("CARGO_PKG_VERSION"), version.version);
+
+            // Matches are rewritten in key order, some are left for the next page
+            let migrated = (0..PLAYERS.len() as u64)
+                .filter(|nonce| query_match(&app, &contract, *nonce).is_ok())
+                .count();
+            assert!(migrated < PLAYERS.len());
+
+            // Matches can't be played until the migration is over
+            let move_msg = ExecuteMsg::MakeMove {
+                match_id: hex::encode(match_id(
+                    &Addr::unchecked(USER),
+                    &Addr::unchecked(PLAYERS[0]),
+                    0,
+                )),
+                move_fen: "e7e5".to_string(),
+            };
+            let err = app
+                .execute_contract(Addr::unchecked(PLAYERS[0]), contract.addr(), &move_msg, &[])
+                .unwrap_err();
+            assert_eq!(
+                ContractError::MigrationInProgress {},
+                err.downcast().unwrap()
+            );
+
+            app.execute_contract(
+                Addr::unchecked(USER),
+                contract.addr(),
+                &ExecuteMsg::MigrateMatches { limit: None },
+                &[],
+            )
+            .unwrap();
+            for nonce in 0..PLAYERS.len() as u64 {
+                let MatchResponse {
+                    board, chess_match, ..
+                } = query_match(&app, &contract, nonce).unwrap();
+                assert_eq!(
+                    "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1",
+                    board
+                );
+                assert_eq!(MatchState::OnGoing(NextMove::Blacks), chess_match.state);
+                assert_eq!(12, chess_match.last_move);
+                assert!(chess_match.rated);
+                assert!(chess_match.tournament.is_none());
+            }
+
+            // Matches already in the current layout are left as they are
+            let MatchResponse {
+                chess_match, moves, ..
+            } = query_match(&app, &contract, PLAYERS.len() as u64).unwrap();
+            assert!(!chess_match.rated);
+            assert_eq!(MatchStyle::Bullet, chess_match.style);
+            assert_eq!(1, moves.len());
+
+            // Migrated matches can be played on
+            app.execute_contract(Addr::unchecked(PLAYERS[0]), contract.addr(), &move_msg, &[])
+                .unwrap();
+            let chess_match = query_match(&app, &contract, 0).unwrap().chess_match;
+            assert_eq!(MatchState::OnGoing(NextMove::Whites), chess_match.state);
+        }
+    }
+
+    mod client {
+        use super::*;
+        use crate::contract::exec::match_id;
+        use crate::game::{MatchResult, MatchState, NextMove};
+        use cosmwasm_std::{CosmosMsg, WasmMsg};
+
+        const ALICE: &str = "alice";
+        const BOB: &str = "bob";
+
+        #[test]
+        fn plays_a_match_through_typed_helpers() {
+            let (mut app, contract) = proper_instantiate();
+            let bet = Coin::new(10, NATIVE_DENOM);
+            app.init_modules(|router, _, storage| {
+                for player in [ALICE, BOB] {
+                    router
+                        .bank
+                        .init_balance(storage, &Addr::unchecked(player), vec![bet.clone()])
+                        .unwrap();
+                }
+            });
+
+            let msg = contract
+                .create_match(Addr::unchecked(BOB), true, None, bet.clone())
+                .unwrap();
+            app.execute(Addr::unchecked(ALICE), msg).unwrap();
+            let id = hex::encode(match_id(&Addr::unchecked(ALICE), &Addr::unchecked(BOB), 0));
+            let msg = contract.join_match(&id, None, bet.clone()).unwrap();
+            app.execute(Addr::unchecked(BOB), msg).unwrap();
+
+            let msg = contract.make_move(&id, "e2e4").unwrap();
+            app.execute(Addr::unchecked(ALICE), msg).unwrap();
+            let response = contract.chess_match(&app.wrap(), &id).unwrap();
+            assert_eq!(
+                MatchState::OnGoing(NextMove::Blacks),
+                response.chess_match.state
+            );
+            assert_eq!(
+                "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1",
+                response.board
+            );
+            assert_eq!(None, contract.side_bet_market(&app.wrap(), &id).unwrap());
+            assert_eq!(
+                Addr::unchecked(ADMIN),
+                contract.arbiter(&app.wrap(), None).unwrap()
+            );
+
+            let msg = contract.resign(&id).unwrap();
+            app.execute(Addr::unchecked(BOB), msg).unwrap();
+            let balance = app.wrap().query_balance(ALICE, NATIVE_DENOM).unwrap();
+            assert_eq!(Coin::new(20, NATIVE_DENOM), balance);
+            assert!(contract.chess_match(&app.wrap(), &id).is_err());
+
+            // Bets attach their funds
+            let msg = contract
+                .place_side_bet(&id, MatchResult::Draw, Coin::new(5, NATIVE_DENOM))
+                .unwrap();
+            let CosmosMsg::Wasm(WasmMsg::Execute { funds, .. }) = msg else {
+                panic!("expected a wasm execute message");
+            };
+            assert_eq!(vec![Coin::new(5, NATIVE_DENOM)], funds);
+        }
+    }
+
+    mod harness {
+        use crate::testing::{pgn_moves, ChessAppBuilder, DENOM};
+        use cosmwasm_std::{coins, Coin};
+
+        const PLAYERS: [&str; 2] = ["alice", "bob"];
+
+        fn suite() -> crate::testing::ChessApp {
+            PLAYERS
+                .iter()
+                .fold(ChessAppBuilder::new(), |builder, player| {
+                    builder.with_player(player, coins(100, DENOM))
+                })
+                .build()
+        }
+
+        #[test]
+        fn escrows_stakes_and_pays_the_winner() {
+            let mut suite = suite();
+            let contract = suite.contract.addr();
+            let id = suite
+                .start_match("alice", "bob", Coin::new(25, DENOM))
+                .unwrap();
+            suite.assert_balance("alice", 75);
+            suite.assert_balance("bob", 75);
+            suite.assert_balance(contract.as_str(), 50);
+
+            suite
+                .play_pgn(
+                    &id,
+                    "[Event \"Casual\"]\n\n1. f3 e5 {weakening} 2. g4?? Qh4# 0-1",
+                )
+                .unwrap();
+            suite.assert_balance("alice", 75);
+            suite.assert_balance("bob", 125);
+            suite.assert_balance(contract.as_str(), 0);
+            assert!(suite.chess_match(&id).is_err());
+        }
+
+        #[test]
+        fn refunds_stakes_on_stalemate() {
+            let mut suite = suite();
+            let id = suite
+                .start_match("bob", "alice", Coin::new(10, DENOM))
+                .unwrap();
+            suite
+                .play_uci(
+                    &id,
+                    &[
+                        "c2c4", "h7h5", "h2h4", "a7a5", "d1a4", "a8a6", "a4a5", "a6h6", "a5c7",
+                        "f7f6", "c7d7", "e8f7", "d7b7", "d8d3", "b7b8", "d3h7", "b8c8", "f7g6",
+                        "c8e6",
+                    ],
+                )
+                .unwrap();
+            suite.assert_balance("alice", 100);
+            suite.assert_balance("bob", 100);
+        }
+
+        #[test]
+        fn rejects_illegal_pgn_moves() {
+            let mut suite = suite();
+            let id = suite
+                .start_match("alice", "bob", Coin::new(10, DENOM))
+                .unwrap();
+            assert!(suite.play_pgn(&id, "1. e4 e5 2. Ke3").is_err());
+            assert_eq!(
+                vec!["e4", "e5", "Nf3", "Nc6"],
+                pgn_moves("1.e4 e5 (1... c5) 2. Nf3 $1 Nc6 *")
+            );
+        }
+    }
+
+    mod gas {
+        use super::*;
+        use crate::contract::exec::match_id;
+        use crate::eco::MAX_OPENING_PLIES;
+        use crate::encoding::pack_board;
+        use crate::game::Match;
+        use crate::state::MATCHES;
+        use cosmwasm_std::{
+            from_json, testing::MockStorage, to_json_string, Binary, Order, Record, Storage,
+        };
+        use std::{cell::Cell, rc::Rc};
+
+        // Default KV store gas config of the Cosmos SDK, which wasmd charges
+        // for contract storage
+        const READ_COST_FLAT: u64 = 1000;
+        const READ_COST_PER_BYTE: u64 = 3;
+        const WRITE_COST_FLAT: u64 = 2000;
+        const WRITE_COST_PER_BYTE: u64 = 30;
+        const DELETE_COST: u64 = 1000;
+        const ITER_NEXT_COST_FLAT: u64 = 30;
+
+        const ALICE: &str = "alice";
+        const BOB: &str = "bob";
+
+        /// Storage charging gas the way the Cosmos SDK KV store does.
+        struct MeteredStorage {
+            inner: MockStorage,
+            gas: Rc<Cell<u64>>,
+        }
+
+        impl MeteredStorage {
+            fn charge(&self, gas: u64) {
+                self.gas.set(self.gas.get() + gas);
+            }
+        }
+
+        impl Storage for MeteredStorage {
+            fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+                let value = self.inner.get(key);
+                let bytes = key.len() + value.as_ref().map_or(0, Vec::len);
+                self.charge(READ_COST_FLAT + READ_COST_PER_BYTE * bytes as u64);
+                value
+            }
+
+            fn range<'a>(
+                &'a self,
+                start: Option<&[u8]>,
+                end: Option<&[u8]>,
+                order: Order,
+            ) -> Box<dyn Iterator<Item = Record> + 'a> {
+                Box::new(self.inner.range(start, end, order).inspect(|(key, value)| {
+                    let bytes = key.len() + value.len();
+                    self.charge(ITER_NEXT_COST_FLAT + READ_COST_PER_BYTE * bytes as u64);
+                }))
+            }
+
+            fn set(&mut self, key: &[u8], value: &[u8]) {
+                let bytes = key.len() + value.len();
+                self.charge(WRITE_COST_FLAT + WRITE_COST_PER_BYTE * bytes as u64);
+                self.inner.set(key, value);
+            }
+
+            fn remove(&mut self, key: &[u8]) {
+                self.charge(DELETE_COST);
+                self.inner.remove(key);
+            }
+        }
+
+        /// Gas a move costs in storage, and what packing the board saves of it.
+        #[test]
+        fn move_gas_stays_flat_as_the_history_grows() {
+            let gas = Rc::new(Cell::new(0));
+            let storage = MeteredStorage {
+                inner: MockStorage::new(),
+                gas: gas.clone(),
+            };
+            let bet = Coin::new(10, NATIVE_DENOM);
+            let mut app = AppBuilder::new()
+                .with_storage(storage)
+                .build(|router, _, storage| {
+                    for player in [ALICE, BOB] {
+                        router
+                            .bank
+                            .init_balance(storage, &Addr::unchecked(player), vec![bet.clone()])
+                            .unwrap();
+                    }
+                });
+            let code_id = app.store_code(chess_contract());
+            let msg = InstantiateMsg {
+                min_bet: bet.clone(),
+            };
+            let addr = app
+                .instantiate_contract(code_id, Addr::unchecked(ADMIN), &msg, &[], "cw-chess", None)
+                .unwrap();
+            let contract = CwChessContract(addr);
+
+            let msg = contract
+                .create_match(Addr::unchecked(BOB), true, None, bet.clone())
+                .unwrap();
+            app.execute(Addr::unchecked(ALICE), msg).unwrap();
+            let id = hex::encode(match_id(&Addr::unchecked(ALICE), &Addr::unchecked(BOB), 0));
+            let msg = contract.join_match(&id, None, bet).unwrap();
+            app.execute(Addr::unchecked(BOB), msg).unwrap();
+
+            let shuffle = ["g1f3", "g8f6", "f3g1", "f6g8"];
+            let mut costs = vec![];
+            for (ply, move_fen) in shuffle.iter().cycle().take(40).enumerate() {
+                let player = if ply % 2 == 0 { ALICE } else { BOB };
+                let msg = contract.make_move(&id, *move_fen).unwrap();
+                gas.set(0);
+                app.execute(Addr::unchecked(player), msg).unwrap();
+                costs.push(gas.get());
+            }
+            // Past the opening, a move writes the board and its own ply only,
+            // so it costs a side the same whatever the length of the history
+            assert!(costs[MAX_OPENING_PLIES..]
+                .windows(3)
+                .all(|plies| plies[0] == plies[2]));
+
+            // What the packed board saves on writing the stored match, measured
+            // by writing it with the board as FEN instead
+            let key = MATCHES.key(match_id(&Addr::unchecked(ALICE), &Addr::unchecked(BOB), 0));
+            let stored = app
+                .wrap()
+                .query_wasm_raw(contract.addr(), key.to_vec())
+                .unwrap()
+                .unwrap();
+            let chess_match: Match = from_json(&stored).unwrap();
+            let packed =
+                to_json_string(&Binary::from(pack_board(&chess_match.position()))).unwrap();
+            let fen = to_json_string(&chess_match.board()).unwrap();
+            let with_fen = String::from_utf8(stored.clone())
+                .unwrap()
+                .replace(&packed, &fen);
+            let write_gas = |value: &[u8]| {
+                let gas = Rc::new(Cell::new(0));
+                let mut storage = MeteredStorage {
+                    inner: MockStorage::new(),
+                    gas: gas.clone(),
+                };
+                storage.set(&key, value);
+                gas.get()
+            };
+            let saved = write_gas(with_fen.as_bytes()) - write_gas(&stored);
+            assert!(saved > 0);
+        }
+    }
 }
@@ -1,15 +1,42 @@
 use crate::msg::{ExecuteMsg, InstantiateMsg};
-use cosmwasm_std::{entry_point, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
+use cosmwasm_std::{
+    entry_point, Binary, Deps, DepsMut, Env, IbcBasicResponse, IbcChannelCloseMsg,
+    IbcChannelConnectMsg, IbcChannelOpenMsg, IbcChannelOpenResponse, IbcPacketAckMsg,
+    IbcPacketReceiveMsg, IbcPacketTimeoutMsg, IbcReceiveResponse, MessageInfo, Never, Reply,
+    Response, StdResult,
+};
 
+pub mod challenge;
 pub mod contract;
+pub mod eco;
+pub mod encoding;
 mod error;
-mod game;
+pub mod game;
 pub mod helpers;
+pub mod ibc;
 pub mod integration_tests;
+pub mod market;
+pub mod migrations;
 pub mod msg;
+pub mod notation;
+pub mod profile;
+pub mod puzzle;
+pub mod rating;
+pub mod season;
 pub mod state;
+pub mod stats;
+pub mod team;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 #[cfg(test)]
+#[allow(
+    unknown_lints,
+    clippy::bool_assert_comparison,
+    clippy::cloned_ref_to_slice_refs
+)]
 mod tests;
+pub mod tournament;
+pub mod vote;
 
 pub use crate::error::ContractError;
 
@@ -37,3 +64,62 @@ pub fn execute(
 pub fn query(deps: Deps, env: Env, msg: msg::QueryMsg) -> StdResult<Binary> {
     contract::query(deps, env, msg)
 }
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
+    contract::reply(deps, env, msg)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn ibc_channel_open(
+    deps: DepsMut,
+    env: Env,
+    msg: IbcChannelOpenMsg,
+) -> Result<IbcChannelOpenResponse, ContractError> {
+    ibc::channel_open(deps, env, msg)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn ibc_channel_connect(
+    deps: DepsMut,
+    env: Env,
+    msg: IbcChannelConnectMsg,
+) -> Result<IbcBasicResponse, ContractError> {
+    ibc::channel_connect(deps, env, msg)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn ibc_channel_close(
+    deps: DepsMut,
+    env: Env,
+    msg: IbcChannelCloseMsg,
+) -> Result<IbcBasicResponse, ContractError> {
+    ibc::channel_close(deps, env, msg)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn ibc_packet_receive(
+    deps: DepsMut,
+    env: Env,
+    msg: IbcPacketReceiveMsg,
+) -> Result<IbcReceiveResponse, Never> {
+    ibc::packet_receive(deps, env, msg)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn ibc_packet_ack(
+    deps: DepsMut,
+    env: Env,
+    msg: IbcPacketAckMsg,
+) -> Result<IbcBasicResponse, ContractError> {
+    ibc::packet_ack(deps, env, msg)
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn ibc_packet_timeout(
+    deps: DepsMut,
+    env: Env,
+    msg: IbcPacketTimeoutMsg,
+) -> Result<IbcBasicResponse, ContractError> {
+    ibc::packet_timeout(deps, env, msg)
+}
@@ -0,0 +1,123 @@
+use cosmwasm_std::{Addr, Uint128};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::game::MatchResult;
+
+// Settlement pays every bettor in the same transaction, so markets are capped
+pub const MAX_SIDE_BETTORS: u32 = 50;
+
+// House fee is expressed in percent of the total pool
+pub const HOUSE_FEE_TOTAL: u32 = 100;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct SideBetConfig {
+    // Betting closes once this many plies have been played
+    pub close_after_plies: u32,
+    pub house_fee_percent: u32,
+}
+
+impl Default for SideBetConfig {
+    fn default() -> Self {
+        Self {
+            close_after_plies: 10,
+            house_fee_percent: 2,
+        }
+    }
+}
+
+/// Parimutuel pools of a match, one per outcome.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct Market {
+    pub denom: String,
+    pub white_wins: Uint128,
+    pub black_wins: Uint128,
+    pub draw: Uint128,
+    pub bettors: u32,
+    // Set when a takeback rewinds the match past the point betting closed at
+    #[serde(default)]
+    pub closed: bool,
+}
+
+impl Market {
+    pub fn new(denom: &str) -> Market {
+        Self {
+            denom: denom.to_string(),
+            white_wins: Uint128::zero(),
+            black_wins: Uint128::zero(),
+            draw: Uint128::zero(),
+            bettors: 0,
+            closed: false,
+        }
+    }
+
+    pub fn pool(&self, outcome: &MatchResult) -> Uint128 {
+        match outcome {
+            MatchResult::WhiteWins => self.white_wins,
+            MatchResult::BlackWins => self.black_wins,
+            MatchResult::Draw => self.draw,
+        }
+    }
+
+    pub fn total(&self) -> Uint128 {
+        self.white_wins + self.black_wins + self.draw
+    }
+
+    pub fn add(&mut self, outcome: &MatchResult, amount: Uint128) {
+        match outcome {
+            MatchResult::WhiteWins => self.white_wins += amount,
+            MatchResult::BlackWins => self.black_wins += amount,
+            MatchResult::Draw => self.draw += amount,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct SideBet {
+    pub outcome: MatchResult,
+    pub amount: Uint128,
+}
+
+/// Amounts owed to each bettor once the market closes, plus the house fee.
+///
+/// Winners share the pool net of the house fee, pro rata to their stake on
+/// the winning outcome; rounding dust goes to the house. A voided market
+/// (`result` is `None`), or one where nobody backed the result, is refunded
+/// in full without fee.
+pub fn settle(
+    market: &Market,
+    bets: &[(Addr, Vec<SideBet>)],
+    result: Option<&MatchResult>,
+    house_fee_percent: u32,
+) -> (Vec<(Addr, Uint128)>, Uint128) {
+    let winning_pool = result.map(|r| market.pool(r)).unwrap_or_default();
+    if winning_pool.is_zero() {
+        let refunds = bets
+            .iter()
+            .map(|(bettor, bets)| (bettor.clone(), bets.iter().map(|bet| bet.amount).sum()))
+            .collect();
+        return (refunds, Uint128::zero());
+    }
+
+    let total = market.total();
+    let distributable = total - total.multiply_ratio(house_fee_percent, HOUSE_FEE_TOTAL);
+    let payouts: Vec<(Addr, Uint128)> = bets
+        .iter()
+        .filter_map(|(bettor, bets)| {
+            let stake: Uint128 = bets
+                .iter()
+                .filter(|bet| Some(&bet.outcome) == result)
+                .map(|bet| bet.amount)
+                .sum();
+            (!stake.is_zero()).then(|| {
+                (
+                    bettor.clone(),
+                    distributable.multiply_ratio(stake, winning_pool),
+                )
+            })
+        })
+        .collect();
+
+    let paid: Uint128 = payouts.iter().map(|(_, amount)| amount).sum();
+    (payouts, total - paid)
+}
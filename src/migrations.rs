@@ -0,0 +1,94 @@
+use cosmwasm_std::{Order, StdResult, Storage};
+use cw_storage_plus::{Bound, Item, Map};
+use serde::{Deserialize, Serialize};
+
+use crate::game::Match;
+use crate::state::{MatchId, MATCHES};
+
+// Matches rewritten per migration call when no limit is given
+pub const DEFAULT_MIGRATION_LIMIT: u32 = 100;
+
+// Set while stored matches are being rewritten: key of the last rewritten
+// match, none if the migration hasn't started yet
+pub const MATCH_MIGRATION: Item<Option<MatchId>> = Item::new("match_migration");
+
+/// Storage layout of v0.3.
+pub mod v0_3 {
+    use cosmwasm_std::{Addr, Coin};
+    use cw_storage_plus::Map;
+    use schemars::JsonSchema;
+    use serde::{Deserialize, Serialize};
+
+    use crate::game::MatchState;
+    use crate::state::MatchId;
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+    pub struct Match {
+        pub challenger: Addr,
+        pub opponent: Addr,
+        pub board: String,
+        pub state: MatchState,
+        pub nonce: u64,
+        pub last_move: u64,
+        pub start: u64,
+        pub bet: Coin,
+    }
+
+    pub const MATCHES: Map<MatchId, Match> = Map::new("matches");
+}
+
+/// A match as stored while a migration is under way: matches past the cursor
+/// are in the v0.3 layout, unless they were stored after the upgrade.
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum StoredMatch {
+    Current(Box<Match>),
+    V0_3(v0_3::Match),
+}
+
+const STORED_MATCHES: Map<MatchId, StoredMatch> = Map::new("matches");
+
+/// Whether some stored matches are still in an older layout.
+pub fn in_progress(storage: &dyn Storage) -> bool {
+    MATCH_MIGRATION.exists(storage)
+}
+
+/// Schedules the rewrite of matches stored by `from` (major, minor), if their
+/// layout has changed since.
+pub fn schedule(storage: &mut dyn Storage, from: (u64, u64)) -> StdResult<()> {
+    if from < (0, 4) {
+        MATCH_MIGRATION.save(storage, &None)?;
+    }
+    Ok(())
+}
+
+/// Rewrites up to `limit` stored v0.3 matches into the current layout,
+/// leaving those already in it untouched. Returns how many matches were
+/// visited, and whether the migration is complete.
+pub fn migrate_matches(storage: &mut dyn Storage, limit: u32) -> StdResult<(u32, bool)> {
+    let Some(cursor) = MATCH_MIGRATION.may_load(storage)? else {
+        return Ok((0, true));
+    };
+
+    let page = STORED_MATCHES
+        .range(
+            storage,
+            cursor.map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit as usize)
+        .collect::<StdResult<Vec<_>>>()?;
+    for (match_id, stored) in &page {
+        if let StoredMatch::V0_3(legacy) = stored {
+            MATCHES.save(storage, *match_id, &Match::try_from(legacy.clone())?)?;
+        }
+    }
+
+    let done = page.len() < limit as usize;
+    match page.last() {
+        Some((match_id, _)) if !done => MATCH_MIGRATION.save(storage, &Some(*match_id))?,
+        _ => MATCH_MIGRATION.remove(storage),
+    }
+    Ok((page.len() as u32, done))
+}
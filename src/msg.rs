@@ -1,5 +1,18 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{Addr, Coin};
+use cosmwasm_std::{Addr, Binary, Coin, Uint128};
+
+use crate::challenge::ChallengePolicy;
+use crate::game::{Adjudication, Match, MatchResult, MatchState, MatchStyle, PlayedMove};
+use crate::ibc::{IbcEscrow, IbcOpponent, IbcPeer};
+use crate::market::Market;
+use crate::profile::Profile;
+use crate::puzzle::{Puzzle, PuzzleId};
+use crate::season::{Season, SeasonId, SeasonStanding};
+use crate::stats::PlayerStats;
+use crate::team::{TeamMatch, TeamMatchId, TeamTiebreak};
+use crate::tournament::{
+    BracketTie, Color, RankedStanding, Tournament, TournamentId, TournamentKind,
+};
 
 #[cw_serde]
 pub struct InstantiateMsg {
@@ -8,15 +21,442 @@ pub struct InstantiateMsg {
 
 #[cw_serde]
 pub enum ExecuteMsg {
-    CreateMatch { opponent: Addr },
-    AbortMatch { match_id: String },
-    JoinMatch { match_id: String },
-    MakeMove { match_id: String, move_fen: String },
+    CreateMatch {
+        // Address or nickname
+        opponent: String,
+        // Defaults to a rated match
+        rated: Option<bool>,
+        // Arbiter of the match instead of the contract's, confirmed by the opponent
+        arbiter: Option<Addr>,
+    },
+    AbortMatch {
+        match_id: String,
+    },
+    JoinMatch {
+        match_id: String,
+        // Must be the arbiter the challenger picked, if any
+        expected_arbiter: Option<Addr>,
+    },
+    MakeMove {
+        match_id: String,
+        move_fen: String,
+    },
+    Resign {
+        match_id: String,
+    },
+    OfferDraw {
+        match_id: String,
+    },
+    // Asks the opponent to undo the last `plies` half-moves (unrated matches only)
+    RequestTakeback {
+        match_id: String,
+        plies: u32,
+    },
+    AcceptTakeback {
+        match_id: String,
+    },
+    // Lines alternate the expected opponent move and the reply to play
+    SetConditionalMoves {
+        match_id: String,
+        lines: Vec<Vec<String>>,
+    },
+    GrantMoveKey {
+        delegate: Addr,
+        // Block height
+        expires: u64,
+        match_id: Option<String>,
+    },
+    RevokeMoveKey {
+        delegate: Addr,
+    },
+    // Sender (a contract) opts in to `BotExecuteMsg` turn notifications
+    RegisterBotCallback {},
+    UnregisterBotCallback {},
+    // Offerer deposits the same stake as in the finished match
+    OfferRematch {
+        match_id: String,
+    },
+    AcceptRematch {
+        match_id: String,
+    },
+    CancelRematch {
+        match_id: String,
+    },
+    UpdateArbiter {
+        arbiter: Addr,
+    },
+    Adjudicate {
+        match_id: String,
+        result: Adjudication,
+        reason: String,
+    },
+    // Continues a paged storage migration, anyone can call it
+    MigrateMatches {
+        limit: Option<u32>,
+    },
+    AddHook {
+        addr: Addr,
+    },
+    RemoveHook {
+        addr: Addr,
+    },
+    CreateTournament {
+        kind: TournamentKind,
+        entry_fee: Coin,
+        max_players: u32,
+        time_control: MatchStyle,
+        prize_split: Vec<u32>,
+    },
+    RegisterTournament {
+        tournament_id: TournamentId,
+    },
+    // `seed` reveals the secret of a randomly seeded knockout
+    StartTournament {
+        tournament_id: TournamentId,
+        seed: Option<String>,
+    },
+    PairNextRound {
+        tournament_id: TournamentId,
+    },
+    // Organizer or admin calls off a tournament still in registration,
+    // refunding every entrant
+    CancelTournament {
+        tournament_id: TournamentId,
+    },
+    PlaceSideBet {
+        match_id: String,
+        outcome: MatchResult,
+    },
+    UpdateSideBetConfig {
+        close_after_plies: u32,
+        house_fee_percent: u32,
+    },
+    // Replaces the sender's profile, releasing their previous nickname
+    SetProfile {
+        nickname: String,
+        country: Option<String>,
+        avatar_uri: Option<String>,
+        bio: Option<String>,
+    },
+    // Admin only, escrows `prize_pool` for the top of the season's leaderboard
+    StartSeason {
+        prize_pool: Coin,
+        prize_split: Vec<u32>,
+    },
+    // Admin only, ends the season and pays its prizes once `limit` sized
+    // pages of standings have all been ranked
+    EndSeason {
+        limit: Option<u32>,
+    },
+    // Escrows `reward` for the first solver to mate from `fen` within `mate_in` moves
+    PostPuzzle {
+        fen: String,
+        mate_in: u32,
+        reward: Coin,
+    },
+    // Commits to a solution, see `puzzle::solution_commitment`
+    CommitSolution {
+        puzzle_id: PuzzleId,
+        commitment: String,
+    },
+    // Reveals a solution committed in an earlier block
+    SolvePuzzle {
+        puzzle_id: PuzzleId,
+        line: Vec<String>,
+        salt: String,
+    },
+    // Refunds the reward of an unsolved puzzle to its poster. While solutions
+    // are pending, closes the puzzle to new commitments until they lapse.
+    WithdrawPuzzle {
+        puzzle_id: PuzzleId,
+    },
+    // Replaces the challenges the sender accepts, see `ChallengePolicy`
+    SetChallengePolicy {
+        blocked: Vec<Addr>,
+        allow_only: Option<Vec<Addr>>,
+        min_rating: Option<u32>,
+        min_bet: Option<Uint128>,
+        max_bet: Option<Uint128>,
+    },
+    // Plays the sender against `voters`, who vote on each of their moves
+    // for `voting_period` blocks
+    CreateVoteMatch {
+        voters: Vec<Addr>,
+        voters_color: Color,
+        voting_period: u64,
+    },
+    VoteMove {
+        match_id: String,
+        move_fen: String,
+    },
+    // Plays the most voted move once voting closed, ties go to the first
+    // move in UCI order. The voters lose if none of them voted.
+    FinalizeVote {
+        match_id: String,
+    },
+    // Sender captains team A and escrows its `stake`, teams are listed from
+    // board 1 down
+    CreateTeamMatch {
+        team_a: Vec<Addr>,
+        team_b: Vec<Addr>,
+        boards: u32,
+        stake: Coin,
+        tiebreak: TeamTiebreak,
+    },
+    // A member of team B matches the stake, setting up a match on every board
+    AcceptTeamMatch {
+        team_match_id: TeamMatchId,
+    },
+    // A board player agrees to play, the boards start once all of them did
+    ConfirmTeamBoard {
+        team_match_id: TeamMatchId,
+    },
+    // Captain of team A withdraws a team match team B hasn't accepted, or
+    // either captain one whose boards haven't started
+    AbortTeamMatch {
+        team_match_id: TeamMatchId,
+    },
+    // Challenges `opponent`, an address on the chain across `channel_id`, who
+    // escrows `opponent_bet` there. The challenger collects it at
+    // `payout_address` on that chain if they win.
+    CreateIbcMatch {
+        channel_id: String,
+        opponent: String,
+        opponent_bet: Coin,
+        payout_address: String,
+    },
+    // Escrows the sent stake and joins a match hosted across `channel_id`.
+    // The sender collects the challenger's stake at `payout_address` on the
+    // host chain if they win.
+    JoinIbcMatch {
+        channel_id: String,
+        match_id: String,
+        payout_address: String,
+    },
+    // Relays the sender's move to a match hosted across `channel_id`
+    RelayMove {
+        channel_id: String,
+        match_id: String,
+        move_fen: String,
+    },
+    // Admin only, lets channels be opened to the peer contract on `port_id`
+    // at the other end of `connection_id`
+    AddIbcPeer {
+        connection_id: String,
+        port_id: String,
+    },
+    // Admin only, channels already open to the peer stay open
+    RemoveIbcPeer {
+        connection_id: String,
+        port_id: String,
+    },
+    // Handles a `ChessPacket` received on `channel_id`, only sent by the
+    // contract to itself
+    ReceiveIbcPacket {
+        channel_id: String,
+        data: Binary,
+    },
 }
 
 #[cw_serde]
 #[derive(QueryResponses)]
-pub enum QueryMsg {}
+pub enum QueryMsg {
+    #[returns(MatchResponse)]
+    Match { match_id: String },
+    #[returns(Tournament)]
+    Tournament { tournament_id: TournamentId },
+    #[returns(StandingsResponse)]
+    Standings { tournament_id: TournamentId },
+    #[returns(BracketResponse)]
+    Bracket { tournament_id: TournamentId },
+    #[returns(Option<Market>)]
+    SideBetMarket { match_id: String },
+    #[returns(HooksResponse)]
+    Hooks {},
+    // Arbiter of a match, or of the contract if no match is given
+    #[returns(Addr)]
+    Arbiter { match_id: Option<String> },
+    #[returns(PlayerStatsResponse)]
+    PlayerStats { player: String },
+    #[returns(HeadToHeadResponse)]
+    HeadToHead { a: String, b: String },
+    #[returns(Option<Profile>)]
+    Profile { address: String },
+    // Owner of a nickname, in any case
+    #[returns(Option<Addr>)]
+    ResolveNickname { nickname: String },
+    #[returns(Option<SeasonId>)]
+    CurrentSeason {},
+    #[returns(Season)]
+    Season { season_id: SeasonId },
+    // Standings of a season from first place down, a page at a time
+    #[returns(SeasonLeaderboardResponse)]
+    SeasonLeaderboard {
+        season_id: SeasonId,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    #[returns(Puzzle)]
+    Puzzle { puzzle_id: PuzzleId },
+    #[returns(TeamMatch)]
+    TeamMatch { team_match_id: TeamMatchId },
+    // Live vote counts of a vote match
+    #[returns(VotesResponse)]
+    Votes { match_id: String },
+    #[returns(ChallengePolicy)]
+    ChallengePolicy { player: String },
+    // Opponent from another chain of a match hosted here
+    #[returns(Option<IbcOpponent>)]
+    IbcOpponent { match_id: String },
+    // Stake of `player` escrowed here for a match hosted across `channel_id`
+    #[returns(Option<IbcEscrow>)]
+    IbcEscrow {
+        channel_id: String,
+        match_id: String,
+        player: String,
+    },
+    #[returns(IbcPeersResponse)]
+    IbcPeers {},
+    // Replays `moves` from `start_fen`, or from the initial position, without
+    // touching any match
+    #[returns(ValidateLineResponse)]
+    ValidateLine {
+        start_fen: Option<String>,
+        moves: Vec<String>,
+    },
+}
+
+#[cw_serde]
+pub struct MatchResponse {
+    // Board in FEN notation
+    pub board: String,
+    pub chess_match: Match,
+    // Moves played so far, in order
+    pub moves: Vec<PlayedMove>,
+}
+
+#[cw_serde]
+pub struct StandingsResponse {
+    pub standings: Vec<RankedStanding>,
+}
+
+#[cw_serde]
+pub struct VotesResponse {
+    pub voters_color: Color,
+    // Block height voting on the current move closes at
+    pub voting_ends: u64,
+    // Most voted move first
+    pub tally: Vec<(String, u32)>,
+}
+
+#[cw_serde]
+pub struct PlayerStatsResponse {
+    pub player: Addr,
+    pub stats: PlayerStats,
+    pub average_plies: u64,
+    // All-time rating
+    pub rating: u32,
+}
+
+#[cw_serde]
+pub struct HeadToHeadResponse {
+    pub a: Addr,
+    pub b: Addr,
+    pub a_wins: u32,
+    pub b_wins: u32,
+    pub draws: u32,
+}
+
+#[cw_serde]
+pub struct SeasonLeaderboardResponse {
+    // From first place down
+    pub standings: Vec<SeasonStanding>,
+}
+
+#[cw_serde]
+pub struct BracketResponse {
+    // Ties of each round, from the first round to the final
+    pub rounds: Vec<Vec<BracketTie>>,
+}
+
+#[cw_serde]
+pub enum ValidateLineResponse {
+    Valid {
+        // Final board in FEN notation
+        fen: String,
+        state: MatchState,
+        // Moves in standard algebraic notation
+        san: Vec<String>,
+    },
+    // First move the contract would reject, and why
+    Invalid {
+        index: u32,
+        reason: String,
+    },
+}
+
+/// Messages sent to bot contracts that registered a callback.
+#[cw_serde]
+pub enum BotExecuteMsg {
+    YourTurn {
+        match_id: String,
+        // Board in FEN notation
+        fen: String,
+        // Opponent's last move, none at the start of a match
+        last_move: Option<String>,
+    },
+}
+
+#[cw_serde]
+pub struct HooksResponse {
+    pub hooks: Vec<Addr>,
+}
 
 #[cw_serde]
-pub struct MigrateMsg {}
+pub struct IbcPeersResponse {
+    pub peers: Vec<IbcPeer>,
+}
+
+/// Messages sent to every registered hook contract.
+#[cw_serde]
+pub enum HookExecuteMsg {
+    MatchFinishedHook {
+        match_id: String,
+        white: Addr,
+        black: Addr,
+        result: MatchResult,
+        // Total stake of both players, zero for tournament games
+        pot: Coin,
+    },
+}
+
+/// Packets exchanged by cw-chess contracts over IBC. The host chain of a match
+/// sends `SettleMatch`, the chain of its opponent the others.
+#[cw_serde]
+pub enum ChessPacket {
+    JoinMatch {
+        match_id: String,
+        // Address on the sending chain
+        player: String,
+        stake: Coin,
+        // Player's address on the host chain
+        payout_address: String,
+    },
+    MakeMove {
+        match_id: String,
+        player: String,
+        move_fen: String,
+    },
+    // Releases the stake `player` escrowed to `pay_to`
+    SettleMatch {
+        match_id: String,
+        player: String,
+        pay_to: String,
+    },
+}
+
+#[cw_serde]
+pub struct MigrateMsg {
+    // Matches rewritten by this call, the rest with `ExecuteMsg::MigrateMatches`
+    pub limit: Option<u32>,
+}
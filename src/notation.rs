@@ -0,0 +1,154 @@
+use cozy_chess::{Board, File, GameStatus, Move, Piece, Rank, Square};
+
+// Check, mate and annotation suffixes carry no information about the move
+const SAN_SUFFIXES: &[char] = &['+', '#', '!', '?'];
+
+/// Resolves a move in standard algebraic notation (e.g. `Nbd7`, `exd5`,
+/// `O-O`, `e8=Q+`) to the legal move it denotes on `board`.
+///
+/// Returns `None` if the notation is malformed, or matches no legal move or
+/// more than one.
+pub fn parse_san(board: &Board, san: &str) -> Option<Move> {
+    let san = san.trim_end_matches(SAN_SUFFIXES);
+    match san {
+        "O-O" | "0-0" => return find_castle(board, true),
+        "O-O-O" | "0-0-0" => return find_castle(board, false),
+        _ => {}
+    }
+
+    // Promotions are written `e8=Q`, or sometimes `e8Q`
+    let san = san.replace('=', "");
+    let (san, promotion) = match san.chars().last().and_then(parse_piece) {
+        Some(piece) => (&san[..san.len() - 1], Some(piece)),
+        None => (&san[..], None),
+    };
+
+    let (piece, san) = match san.chars().next().and_then(parse_piece) {
+        Some(piece) => (piece, &san[1..]),
+        None => (Piece::Pawn, san),
+    };
+    let san = san.replace('x', "");
+    if san.len() < 2 || !san.is_ascii() {
+        return None;
+    }
+    let (disambiguation, to) = san.split_at(san.len() - 2);
+    let to: Square = to.parse().ok()?;
+
+    let mut from_file = None;
+    let mut from_rank = None;
+    for c in disambiguation.chars() {
+        match (File::try_from(c), Rank::try_from(c)) {
+            (Ok(file), _) => from_file = Some(file),
+            (_, Ok(rank)) => from_rank = Some(rank),
+            _ => return None,
+        }
+    }
+
+    let mut candidates = vec![];
+    board.generate_moves(|moves| {
+        if moves.piece == piece
+            && from_file.map_or(true, |file| moves.from.file() == file)
+            && from_rank.map_or(true, |rank| moves.from.rank() == rank)
+        {
+            candidates.extend(
+                moves
+                    .into_iter()
+                    .filter(|mv| mv.to == to && mv.promotion == promotion),
+            );
+        }
+        false
+    });
+    match candidates[..] {
+        [mv] => Some(mv),
+        _ => None,
+    }
+}
+
+/// Writes a legal `mv` in standard algebraic notation, with a `+` or `#`
+/// suffix for check and mate.
+pub fn to_san(board: &Board, mv: Move) -> String {
+    let piece = board.piece_on(mv.from).unwrap_or(Piece::Pawn);
+    let capture = board.colors(!board.side_to_move()).has(mv.to);
+    let mut san = String::new();
+
+    if piece == Piece::King && board.colors(board.side_to_move()).has(mv.to) {
+        san.push_str(match mv.to.file() > mv.from.file() {
+            true => "O-O",
+            false => "O-O-O",
+        });
+    } else if piece == Piece::Pawn {
+        // Pawns change file only when capturing, en passant included
+        if mv.from.file() != mv.to.file() {
+            san.push(mv.from.file().into());
+            san.push('x');
+        }
+        san.push_str(&mv.to.to_string());
+        if let Some(promotion) = mv.promotion {
+            san.push('=');
+            san.push(piece_char(promotion));
+        }
+    } else {
+        san.push(piece_char(piece));
+        // Other pieces of the same kind that can also reach the target square
+        let mut rivals = vec![];
+        board.generate_moves(|moves| {
+            if moves.piece == piece && moves.from != mv.from && moves.to.has(mv.to) {
+                rivals.push(moves.from);
+            }
+            false
+        });
+        if !rivals.is_empty() {
+            let same_file = rivals.iter().any(|sq| sq.file() == mv.from.file());
+            let same_rank = rivals.iter().any(|sq| sq.rank() == mv.from.rank());
+            if !same_file || same_rank {
+                san.push(mv.from.file().into());
+            }
+            if same_file {
+                san.push(mv.from.rank().into());
+            }
+        }
+        if capture {
+            san.push('x');
+        }
+        san.push_str(&mv.to.to_string());
+    }
+
+    let mut after = board.clone();
+    after.play_unchecked(mv);
+    if after.status() == GameStatus::Won {
+        san.push('#');
+    } else if !after.checkers().is_empty() {
+        san.push('+');
+    }
+    san
+}
+
+// Castling moves are encoded king-to-rook, short castling towards the h-file
+fn find_castle(board: &Board, short: bool) -> Option<Move> {
+    let own_rooks = board.colored_pieces(board.side_to_move(), Piece::Rook);
+    let mut castle = None;
+    board.generate_moves(|moves| {
+        if moves.piece == Piece::King {
+            castle = moves
+                .into_iter()
+                .find(|mv| own_rooks.has(mv.to) && (mv.to.file() > mv.from.file()) == short);
+        }
+        castle.is_some()
+    });
+    castle
+}
+
+fn piece_char(piece: Piece) -> char {
+    char::from(piece).to_ascii_uppercase()
+}
+
+fn parse_piece(c: char) -> Option<Piece> {
+    match c {
+        'N' => Some(Piece::Knight),
+        'B' => Some(Piece::Bishop),
+        'R' => Some(Piece::Rook),
+        'Q' => Some(Piece::Queen),
+        'K' => Some(Piece::King),
+        _ => None,
+    }
+}
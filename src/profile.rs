@@ -0,0 +1,60 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::error::InvalidProfileReason;
+
+pub const MIN_NICKNAME_LENGTH: usize = 3;
+// Well below the length of any bech32 address, so nicknames never read as one
+pub const MAX_NICKNAME_LENGTH: usize = 20;
+pub const MAX_AVATAR_URI_LENGTH: usize = 256;
+pub const MAX_BIO_LENGTH: usize = 280;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct Profile {
+    // Unique, case-insensitively
+    pub nickname: String,
+    // ISO 3166-1 alpha-2 code, e.g. "BE"
+    pub country: Option<String>,
+    pub avatar_uri: Option<String>,
+    pub bio: Option<String>,
+}
+
+impl Profile {
+    pub fn validate(&self) -> Result<(), InvalidProfileReason> {
+        let nickname = &self.nickname;
+        if !(MIN_NICKNAME_LENGTH..=MAX_NICKNAME_LENGTH).contains(&nickname.len()) {
+            return Err(InvalidProfileReason::NicknameLength);
+        }
+        if !nickname.starts_with(|c: char| c.is_ascii_alphabetic())
+            || !nickname
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        {
+            return Err(InvalidProfileReason::NicknameCharset);
+        }
+        if let Some(country) = &self.country {
+            if country.len() != 2 || !country.chars().all(|c| c.is_ascii_uppercase()) {
+                return Err(InvalidProfileReason::Country);
+            }
+        }
+        if let Some(uri) = &self.avatar_uri {
+            if uri.len() > MAX_AVATAR_URI_LENGTH
+                || !uri.chars().all(|c| c.is_ascii_graphic())
+                || !uri.contains("://")
+            {
+                return Err(InvalidProfileReason::AvatarUri);
+            }
+        }
+        if let Some(bio) = &self.bio {
+            if bio.chars().count() > MAX_BIO_LENGTH || bio.chars().any(char::is_control) {
+                return Err(InvalidProfileReason::Bio);
+            }
+        }
+        Ok(())
+    }
+
+    /// Key nicknames are unique by.
+    pub fn nickname_key(nickname: &str) -> String {
+        nickname.to_ascii_lowercase()
+    }
+}
@@ -0,0 +1,141 @@
+use cosmwasm_std::{Addr, Coin};
+use cozy_chess::{Board, GameStatus, Move};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+pub type PuzzleId = u64;
+
+// Deepest mate a puzzle can ask for, in moves of the side to play
+pub const MAX_MATE_IN: u32 = 3;
+
+// Upper bound on the positions visited when verifying a solution, to keep
+// `SolvePuzzle` gas-bounded
+pub const MAX_SEARCH_NODES: u32 = 20_000;
+
+// Blocks a solver has to reveal a commitment in, during which the poster
+// can't withdraw the puzzle from under them. Once the poster asked to
+// withdraw it, no new commitment is accepted.
+pub const REVEAL_WINDOW: u64 = 100;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct Puzzle {
+    pub poster: Addr,
+    // Board in FEN notation, the side to move has to mate
+    pub fen: String,
+    pub mate_in: u32,
+    pub reward: Coin,
+    pub solver: Option<Addr>,
+    // Block height the poster asked to withdraw the puzzle at
+    #[serde(default)]
+    pub withdrawal_requested: Option<u64>,
+}
+
+/// A solver's commitment to a solution, revealed at a later block.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct SolutionCommitment {
+    // Hex encoded, see `solution_commitment`
+    pub hash: String,
+    pub block_height: u64,
+}
+
+/// Hash a solver commits to before revealing `line`: sha256 of the solver
+/// address, the big endian puzzle id, the space separated moves and `salt`.
+pub fn solution_commitment(
+    solver: &Addr,
+    puzzle_id: PuzzleId,
+    line: &[String],
+    salt: &str,
+) -> String {
+    let hash = Sha256::digest(
+        [
+            solver.as_bytes(),
+            &puzzle_id.to_be_bytes(),
+            line.join(" ").as_bytes(),
+            salt.as_bytes(),
+        ]
+        .concat(),
+    );
+    hex::encode(hash)
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct SearchLimitExceeded;
+
+/// Whether `key`, played on `board`, forces mate within `mate_in` moves
+/// against every defence, visiting at most `max_nodes` positions.
+pub fn key_move_forces_mate(
+    board: &Board,
+    key: Move,
+    mate_in: u32,
+    max_nodes: u32,
+) -> Result<bool, SearchLimitExceeded> {
+    let mut budget = max_nodes;
+    let mut after = board.clone();
+    if after.try_play(key).is_err() {
+        return Ok(false);
+    }
+    match after.status() {
+        GameStatus::Won => Ok(true),
+        GameStatus::Drawn => Ok(false),
+        GameStatus::Ongoing => every_defence_loses(&after, mate_in.saturating_sub(1), &mut budget),
+    }
+}
+
+// Whether the side to move can mate within `moves` of its moves
+fn forces_mate(board: &Board, moves: u32, budget: &mut u32) -> Result<bool, SearchLimitExceeded> {
+    if moves == 0 {
+        return Ok(false);
+    }
+    for mv in legal_moves(board) {
+        let after = visit(board, mv, budget)?;
+        let mates = match after.status() {
+            GameStatus::Won => true,
+            GameStatus::Drawn => false,
+            GameStatus::Ongoing => every_defence_loses(&after, moves - 1, budget)?,
+        };
+        if mates {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+// Whether every move of the side to move lets the opponent mate within
+// `moves` of their moves
+fn every_defence_loses(
+    board: &Board,
+    moves: u32,
+    budget: &mut u32,
+) -> Result<bool, SearchLimitExceeded> {
+    if moves == 0 {
+        return Ok(false);
+    }
+    for mv in legal_moves(board) {
+        let after = visit(board, mv, budget)?;
+        let loses = match after.status() {
+            GameStatus::Ongoing => forces_mate(&after, moves, budget)?,
+            _ => false,
+        };
+        if !loses {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+fn visit(board: &Board, mv: Move, budget: &mut u32) -> Result<Board, SearchLimitExceeded> {
+    *budget = budget.checked_sub(1).ok_or(SearchLimitExceeded)?;
+    let mut after = board.clone();
+    after.play_unchecked(mv);
+    Ok(after)
+}
+
+fn legal_moves(board: &Board) -> Vec<Move> {
+    let mut moves = vec![];
+    board.generate_moves(|piece_moves| {
+        moves.extend(piece_moves);
+        false
+    });
+    moves
+}
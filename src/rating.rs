@@ -0,0 +1,46 @@
+use crate::game::MatchResult;
+
+pub const DEFAULT_RATING: u32 = 1200;
+pub const K_FACTOR: u32 = 32;
+
+// Rating differences above this are treated as this value (FIDE 400-point rule)
+const MAX_RATING_DIFF: u32 = 400;
+const TABLE_STEP: u32 = 25;
+
+// Expected score in permille of the higher rated player, by rating difference
+// in steps of `TABLE_STEP`. Floats are not available in Wasm, hence the table.
+const EXPECTED_SCORE: [u32; 17] = [
+    500, 536, 571, 606, 640, 673, 703, 733, 760, 785, 808, 830, 849, 867, 882, 896, 909,
+];
+
+/// Expected score of `rating` against `opponent`, in permille.
+pub fn expected_score(rating: u32, opponent: u32) -> u32 {
+    let diff = rating.abs_diff(opponent).min(MAX_RATING_DIFF);
+    let idx = (diff / TABLE_STEP) as usize;
+    let lower = EXPECTED_SCORE[idx];
+    let upper = EXPECTED_SCORE[(idx + 1).min(EXPECTED_SCORE.len() - 1)];
+    let favourite = lower + (upper - lower) * (diff % TABLE_STEP) / TABLE_STEP;
+
+    if rating >= opponent {
+        favourite
+    } else {
+        1000 - favourite
+    }
+}
+
+/// New (white, black) ratings after a game.
+pub fn update_ratings(white: u32, black: u32, result: &MatchResult) -> (u32, u32) {
+    let white_score: u32 = match result {
+        MatchResult::WhiteWins => 1000,
+        MatchResult::BlackWins => 0,
+        MatchResult::Draw => 500,
+    };
+    let white_delta = K_FACTOR as i64 * (white_score as i64 - expected_score(white, black) as i64);
+    // Round to the nearest point, symmetrically for both players
+    let delta = (white_delta + white_delta.signum() * 500) / 1000;
+
+    (
+        (white as i64 + delta).max(0) as u32,
+        (black as i64 - delta).max(0) as u32,
+    )
+}
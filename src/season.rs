@@ -0,0 +1,124 @@
+use cosmwasm_std::{Addr, Coin, Uint128};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::rating::DEFAULT_RATING;
+
+pub type SeasonId = u64;
+
+// Standings scanned per `EndSeason` call when no limit is given
+pub const DEFAULT_END_SEASON_LIMIT: u32 = 100;
+// Standings per `SeasonLeaderboard` page
+pub const DEFAULT_LEADERBOARD_LIMIT: u32 = 30;
+pub const MAX_LEADERBOARD_LIMIT: u32 = 100;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct Season {
+    pub start: u64,
+    // Block height the season ended at, none while it is running
+    pub end: Option<u64>,
+    pub prize_pool: Coin,
+    // Percent of the prize pool for each rank, from first place down
+    pub prize_split: Vec<u32>,
+    // Prizes paid when the season ended
+    pub prizes: Vec<(Addr, Uint128)>,
+}
+
+impl Season {
+    pub fn new(start: u64, prize_pool: Coin, prize_split: Vec<u32>) -> Season {
+        Self {
+            start,
+            end: None,
+            prize_pool,
+            prize_split,
+            prizes: vec![],
+        }
+    }
+}
+
+/// A player's record over a season. Ratings start afresh every season.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct SeasonStanding {
+    pub player: Addr,
+    // 2 points per win, 1 per draw
+    pub points: u32,
+    pub rating: u32,
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+}
+
+impl SeasonStanding {
+    pub fn new(player: Addr) -> SeasonStanding {
+        Self {
+            player,
+            points: 0,
+            rating: DEFAULT_RATING,
+            wins: 0,
+            draws: 0,
+            losses: 0,
+        }
+    }
+
+    pub fn record(&mut self, score: u32, rating: u32) {
+        match score {
+            2 => self.wins += 1,
+            1 => self.draws += 1,
+            _ => self.losses += 1,
+        }
+        self.points += score;
+        self.rating = rating;
+    }
+}
+
+/// Progress of ending a season, whose standings are scanned in pages.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct SeasonClose {
+    pub season_id: SeasonId,
+    // Last player scanned, none before the first page
+    pub cursor: Option<Addr>,
+    // Best standings scanned so far, the only ones that can still win a prize
+    pub leaders: Vec<SeasonStanding>,
+}
+
+/// The standings placed in the first `places` ranks, keeping everybody tied
+/// for the last of them.
+pub fn keep_leaders(standings: Vec<SeasonStanding>, places: usize) -> Vec<SeasonStanding> {
+    let mut leaders = vec![];
+    for group in rank_season(standings) {
+        if leaders.len() >= places {
+            break;
+        }
+        leaders.extend(group);
+    }
+    leaders
+}
+
+/// Orders standings like `rank_season` when ascending: points then rating,
+/// highest first, packed in one key for `SEASON_RANKING`.
+pub fn rank_key(standing: &SeasonStanding) -> u64 {
+    u64::MAX - ((standing.points as u64) << 32 | standing.rating as u64)
+}
+
+/// Standings from first place down, by points then season rating. Players
+/// level on both share a rank, in address order.
+pub fn rank_season(mut standings: Vec<SeasonStanding>) -> Vec<Vec<SeasonStanding>> {
+    standings.sort_by(|a, b| {
+        (b.points, b.rating)
+            .cmp(&(a.points, a.rating))
+            .then_with(|| a.player.cmp(&b.player))
+    });
+
+    let mut ranking: Vec<Vec<SeasonStanding>> = vec![];
+    for standing in standings {
+        match ranking.last_mut() {
+            Some(group)
+                if (group[0].points, group[0].rating) == (standing.points, standing.rating) =>
+            {
+                group.push(standing)
+            }
+            _ => ranking.push(vec![standing]),
+        }
+    }
+    ranking
+}
@@ -1,12 +1,26 @@
-use cosmwasm_std::{Addr, StdResult, Storage, Uint128};
+use cosmwasm_std::{Addr, Coin, IbcChannel, StdResult, Storage, Uint128};
 use cw_storage_plus::{Item, Map};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 
-use crate::game::Match;
+use crate::challenge::ChallengePolicy;
+use crate::game::{Match, MatchStyle, PlayedMove};
+use crate::ibc::{IbcEscrow, IbcOpponent};
+use crate::market::{Market, SideBet, SideBetConfig};
+use crate::profile::Profile;
+use crate::puzzle::{Puzzle, PuzzleId, SolutionCommitment};
+use crate::season::{Season, SeasonClose, SeasonId, SeasonStanding};
+use crate::stats::{HeadToHead, PlayerStats};
+use crate::team::{TeamMatch, TeamMatchId};
+use crate::tournament::{BracketTie, Standing, Tournament, TournamentId};
+use crate::vote::VoteGroup;
 
 pub type MatchId = [u8; 32];
 
 // Contract admin address
 pub const ADMIN: Item<Addr> = Item::new("contract_admin");
+// Adjudicates matches without their own arbiter, defaults to the admin
+pub const ARBITER: Item<Addr> = Item::new("arbiter");
 
 // Minigmum bet amount to start a game
 pub const MIN_BET: Item<(Uint128, String)> = Item::new("min_bet");
@@ -16,9 +30,145 @@ pub const NEXT_NONCE: Item<u64> = Item::new("next_nonce");
 pub const MATCHES: Map<MatchId, Match> = Map::new("matches");
 pub const MATCH_IDS: Map<u64, MatchId> = Map::new("match_ids");
 pub const PLAYER_MATCHES: Map<(&Addr, MatchId), ()> = Map::new("player_matches");
+// Arbiters chosen by the challenger of a match, overriding the contract arbiter
+pub const MATCH_ARBITERS: Map<MatchId, Addr> = Map::new("match_arbiters");
+// Player who has a standing draw offer in a match
+pub const DRAW_OFFERS: Map<MatchId, Addr> = Map::new("draw_offers");
+// Conditional moves queued by a player in a match, as lines alternating the
+// expected opponent move and the reply
+pub const CONDITIONAL_MOVES: Map<(&[u8], &Addr), Vec<Vec<String>>> = Map::new("conditional_moves");
+// Move history of a match by ply, kept apart from the match so that a move
+// writes only its own entry instead of rewriting all the earlier ones
+pub const MATCH_MOVES: Map<(&[u8], u32), PlayedMove> = Map::new("match_moves");
+// Pending takeback request of a match: requesting player and number of plies
+pub const TAKEBACK_REQUESTS: Map<MatchId, (Addr, u32)> = Map::new("takeback_requests");
+
+/// Lets a delegate play moves, resign and offer draws for its grantor, in one
+/// match or in all of them, until the `expires` block height.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct MoveKey {
+    pub expires: u64,
+    pub match_id: Option<String>,
+}
+
+// Blocks after the end of a match during which a rematch can be offered and accepted
+pub const REMATCH_WINDOW: u64 = 600;
+
+/// What is left of a finished match between two players, for rematches.
+/// Tournament, vote, cross-chain and team matches aren't kept.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct FinishedMatch {
+    pub white: Addr,
+    pub black: Addr,
+    pub bet: Coin,
+    pub style: MatchStyle,
+    pub rated: bool,
+    // Block height at which the match finished
+    pub finished: u64,
+    // Player whose stake is escrowed for a rematch
+    pub rematch_offer: Option<Addr>,
+}
+
+pub const FINISHED_MATCHES: Map<MatchId, FinishedMatch> = Map::new("finished_matches");
+// Finished matches by the block height they finished at, to prune lapsed ones
+pub const FINISHED_BY_HEIGHT: Map<(u64, MatchId), ()> = Map::new("finished_by_height");
+// Lapsed finished matches pruned each time a match finishes
+pub const FINISHED_PRUNE_LIMIT: usize = 10;
+
+// Contracts notified of every finished match
+pub const HOOKS: Map<&Addr, ()> = Map::new("hooks");
+
+// Bot contracts notified when it is their turn to move
+pub const BOT_CALLBACKS: Map<&Addr, ()> = Map::new("bot_callbacks");
+
+// Move keys, by grantor and delegate
+pub const MOVE_KEYS: Map<(&Addr, &Addr), MoveKey> = Map::new("move_keys");
+
+pub const NEXT_TOURNAMENT_ID: Item<TournamentId> = Item::new("next_tournament_id");
+pub const TOURNAMENTS: Map<TournamentId, Tournament> = Map::new("tournaments");
+pub const STANDINGS: Map<(TournamentId, &Addr), Standing> = Map::new("standings");
+// Knockout ties, by tournament, round and slot
+pub const BRACKET: Map<(TournamentId, u32, u32), BracketTie> = Map::new("bracket");
+pub const BRACKET_MATCHES: Map<MatchId, (u32, u32)> = Map::new("bracket_matches");
+
+pub const PLAYER_STATS: Map<&Addr, PlayerStats> = Map::new("player_stats");
+// All-time rating of players, updated by every rated match. Season ratings
+// start afresh each season and only rank its leaderboard
+pub const RATINGS: Map<&Addr, u32> = Map::new("ratings");
+// Keyed by `stats::pair_key`
+pub const HEAD_TO_HEAD: Map<(&Addr, &Addr), HeadToHead> = Map::new("head_to_head");
+
+pub const PROFILES: Map<&Addr, Profile> = Map::new("profiles");
+// Owner of each nickname, by lowercased nickname
+pub const NICKNAMES: Map<&str, Addr> = Map::new("nicknames");
+
+// Players without a policy accept every challenge
+pub const CHALLENGE_POLICIES: Map<&Addr, ChallengePolicy> = Map::new("challenge_policies");
+
+// Voters of vote matches, and their votes on the current move
+pub const VOTE_GROUPS: Map<MatchId, VoteGroup> = Map::new("vote_groups");
+
+pub const NEXT_TEAM_MATCH_ID: Item<TeamMatchId> = Item::new("next_team_match_id");
+pub const TEAM_MATCHES: Map<TeamMatchId, TeamMatch> = Map::new("team_matches");
+// Team match and board of each board match
+pub const TEAM_BOARDS: Map<MatchId, (TeamMatchId, u32)> = Map::new("team_boards");
+
+// Peer contracts channels may be opened to, by connection ID and their port ID
+pub const IBC_PEERS: Map<(&str, &str), ()> = Map::new("ibc_peers");
+// Connected channels to peer contracts, by our end's channel ID
+pub const IBC_CHANNELS: Map<&str, IbcChannel> = Map::new("ibc_channels");
+// Opponents of matches hosted here who play from another chain
+pub const IBC_OPPONENTS: Map<MatchId, IbcOpponent> = Map::new("ibc_opponents");
+// Stakes escrowed here for matches hosted on another chain, by channel, match
+// ID and player
+pub const IBC_ESCROWS: Map<(&str, &str, &Addr), IbcEscrow> = Map::new("ibc_escrows");
+
+pub const NEXT_SEASON_ID: Item<SeasonId> = Item::new("next_season_id");
+// Season in progress, if any
+pub const CURRENT_SEASON: Item<SeasonId> = Item::new("current_season");
+pub const SEASONS: Map<SeasonId, Season> = Map::new("seasons");
+pub const SEASON_STANDINGS: Map<(SeasonId, &Addr), SeasonStanding> = Map::new("season_standings");
+// Season standings by `rank_key`, to page through a leaderboard in rank order
+pub const SEASON_RANKING: Map<(SeasonId, u64, &Addr), ()> = Map::new("season_ranking");
+// Set while the season that just ended is paying its prizes
+pub const SEASON_CLOSE: Item<SeasonClose> = Item::new("season_close");
+
+pub const NEXT_PUZZLE_ID: Item<PuzzleId> = Item::new("next_puzzle_id");
+pub const PUZZLES: Map<PuzzleId, Puzzle> = Map::new("puzzles");
+pub const SOLUTION_COMMITMENTS: Map<(PuzzleId, &Addr), SolutionCommitment> =
+    Map::new("solution_commitments");
+
+pub const SIDE_BET_CONFIG: Item<SideBetConfig> = Item::new("side_bet_config");
+pub const MARKETS: Map<MatchId, Market> = Map::new("markets");
+// Keyed by match ID bytes, as fixed-size arrays cannot be used as a key prefix
+pub const SIDE_BETS: Map<(&[u8], &Addr), Vec<SideBet>> = Map::new("side_bets");
 
 pub fn increment_nonce(store: &mut dyn Storage) -> StdResult<u64> {
     let nonce: u64 = NEXT_NONCE.may_load(store)?.unwrap_or_default() + 1;
     NEXT_NONCE.save(store, &nonce)?;
     Ok(nonce)
 }
+
+pub fn next_tournament_id(store: &mut dyn Storage) -> StdResult<TournamentId> {
+    let id: TournamentId = NEXT_TOURNAMENT_ID.may_load(store)?.unwrap_or_default();
+    NEXT_TOURNAMENT_ID.save(store, &(id + 1))?;
+    Ok(id)
+}
+
+pub fn next_team_match_id(store: &mut dyn Storage) -> StdResult<TeamMatchId> {
+    let id: TeamMatchId = NEXT_TEAM_MATCH_ID.may_load(store)?.unwrap_or_default();
+    NEXT_TEAM_MATCH_ID.save(store, &(id + 1))?;
+    Ok(id)
+}
+
+pub fn next_season_id(store: &mut dyn Storage) -> StdResult<SeasonId> {
+    let id: SeasonId = NEXT_SEASON_ID.may_load(store)?.unwrap_or_default();
+    NEXT_SEASON_ID.save(store, &(id + 1))?;
+    Ok(id)
+}
+
+pub fn next_puzzle_id(store: &mut dyn Storage) -> StdResult<PuzzleId> {
+    let id: PuzzleId = NEXT_PUZZLE_ID.may_load(store)?.unwrap_or_default();
+    NEXT_PUZZLE_ID.save(store, &(id + 1))?;
+    Ok(id)
+}
@@ -0,0 +1,124 @@
+use cosmwasm_std::{Addr, Coin, Int128, StdResult, Uint128};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::game::MatchResult;
+use crate::tournament::Color;
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq, JsonSchema)]
+pub struct ColorRecord {
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+}
+
+/// Stakes and winnings in one denomination.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct Wagers {
+    pub denom: String,
+    pub wagered: Uint128,
+    pub net_profit: Int128,
+}
+
+/// Career record of a player, over every finished match.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq, JsonSchema)]
+pub struct PlayerStats {
+    pub games: u32,
+    pub as_white: ColorRecord,
+    pub as_black: ColorRecord,
+    pub wagers: Vec<Wagers>,
+    pub win_streak: u32,
+    pub longest_win_streak: u32,
+    // Sum of the length of every game, see `average_plies`
+    pub total_plies: u64,
+}
+
+impl PlayerStats {
+    /// Records a game finished after `plies` half-moves, in which the player
+    /// staked `stake` (nothing for tournament games). Fails if the net profit
+    /// overflows.
+    pub fn record(
+        &mut self,
+        color: Color,
+        result: &MatchResult,
+        plies: u32,
+        stake: Option<&Coin>,
+    ) -> StdResult<()> {
+        let won = matches!(
+            (&color, result),
+            (Color::White, MatchResult::WhiteWins) | (Color::Black, MatchResult::BlackWins)
+        );
+        let record = match color {
+            Color::White => &mut self.as_white,
+            Color::Black => &mut self.as_black,
+        };
+        match result {
+            MatchResult::Draw => record.draws += 1,
+            _ if won => record.wins += 1,
+            _ => record.losses += 1,
+        }
+
+        self.games += 1;
+        self.total_plies += plies as u64;
+        self.win_streak = if won { self.win_streak + 1 } else { 0 };
+        self.longest_win_streak = self.longest_win_streak.max(self.win_streak);
+
+        let Some(stake) = stake.filter(|stake| !stake.amount.is_zero()) else {
+            return Ok(());
+        };
+        let index = match self.wagers.iter().position(|w| w.denom == stake.denom) {
+            Some(index) => index,
+            None => {
+                self.wagers.push(Wagers {
+                    denom: stake.denom.clone(),
+                    wagered: Uint128::zero(),
+                    net_profit: Int128::zero(),
+                });
+                self.wagers.len() - 1
+            }
+        };
+        let wagers = &mut self.wagers[index];
+        wagers.wagered = wagers.wagered.checked_add(stake.amount)?;
+        // Winners take the loser's stake, draws are refunded
+        let amount = Int128::try_from(stake.amount)?;
+        wagers.net_profit = match result {
+            MatchResult::Draw => wagers.net_profit,
+            _ if won => wagers.net_profit.checked_add(amount)?,
+            _ => wagers.net_profit.checked_sub(amount)?,
+        };
+        Ok(())
+    }
+
+    pub fn average_plies(&self) -> u64 {
+        self.total_plies
+            .checked_div(self.games as u64)
+            .unwrap_or_default()
+    }
+}
+
+/// Games between two players, stored once per pair under `pair_key`.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq, JsonSchema)]
+pub struct HeadToHead {
+    pub first_wins: u32,
+    pub second_wins: u32,
+    pub draws: u32,
+}
+
+impl HeadToHead {
+    pub fn record(&mut self, first_won: Option<bool>) {
+        match first_won {
+            Some(true) => self.first_wins += 1,
+            Some(false) => self.second_wins += 1,
+            None => self.draws += 1,
+        }
+    }
+}
+
+/// Orders a pair of players so that every pair has a single record.
+pub fn pair_key<'a>(a: &'a Addr, b: &'a Addr) -> (&'a Addr, &'a Addr) {
+    if a <= b {
+        (a, b)
+    } else {
+        (b, a)
+    }
+}
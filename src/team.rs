@@ -0,0 +1,145 @@
+use cosmwasm_std::{Addr, Coin};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::game::MatchResult;
+use crate::tournament::game_scores;
+
+pub type TeamMatchId = u64;
+
+pub const MAX_BOARDS: u32 = 16;
+
+/// Decides a team match whose board points are even.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub enum TeamTiebreak {
+    // Stakes are refunded
+    Split,
+    // The winner of the highest board with a decisive result wins
+    TopBoard,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub enum Team {
+    A,
+    B,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub enum TeamMatchState {
+    AwaitingTeamB,
+    // Boards start once every board player confirmed
+    AwaitingPlayers,
+    OnGoing,
+    Finished,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct TeamBoard {
+    pub match_id: String,
+    pub team_a_white: bool,
+    pub result: Option<MatchResult>,
+}
+
+impl TeamBoard {
+    pub fn winner(&self) -> Option<Team> {
+        match (&self.result, self.team_a_white) {
+            (Some(MatchResult::WhiteWins), true) | (Some(MatchResult::BlackWins), false) => {
+                Some(Team::A)
+            }
+            (Some(MatchResult::WhiteWins), false) | (Some(MatchResult::BlackWins), true) => {
+                Some(Team::B)
+            }
+            _ => None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct TeamMatch {
+    // Players from board 1 down, extra players are reserves
+    pub team_a: Vec<Addr>,
+    pub team_b: Vec<Addr>,
+    // Captains staked for their team and collect its winnings
+    pub captain_a: Addr,
+    pub captain_b: Option<Addr>,
+    pub boards: u32,
+    pub stake: Coin,
+    pub tiebreak: TeamTiebreak,
+    pub state: TeamMatchState,
+    // Team A has white on odd boards, once team B accepted
+    pub board_matches: Vec<TeamBoard>,
+    // Board players who agreed to play their board
+    #[serde(default)]
+    pub confirmed: Vec<Addr>,
+    // In half-points
+    pub score_a: u32,
+    pub score_b: u32,
+}
+
+impl TeamMatch {
+    pub fn new(
+        team_a: Vec<Addr>,
+        team_b: Vec<Addr>,
+        captain_a: Addr,
+        boards: u32,
+        stake: Coin,
+        tiebreak: TeamTiebreak,
+    ) -> TeamMatch {
+        Self {
+            team_a,
+            team_b,
+            captain_a,
+            captain_b: None,
+            boards,
+            stake,
+            tiebreak,
+            state: TeamMatchState::AwaitingTeamB,
+            board_matches: vec![],
+            confirmed: vec![],
+            score_a: 0,
+            score_b: 0,
+        }
+    }
+
+    /// Players on a board in either team, reserves aside.
+    pub fn board_players(&self) -> impl Iterator<Item = &Addr> {
+        let boards = self.boards as usize;
+        self.team_a[..boards].iter().chain(&self.team_b[..boards])
+    }
+
+    pub fn is_confirmed(&self) -> bool {
+        self.board_players()
+            .all(|player| self.confirmed.contains(player))
+    }
+
+    /// Records the result of a board (0 for board 1) and updates the score.
+    pub fn record(&mut self, board: usize, result: MatchResult) {
+        let (white_score, black_score) = game_scores(&result);
+        let board = &mut self.board_matches[board];
+        let (score_a, score_b) = match board.team_a_white {
+            true => (white_score, black_score),
+            false => (black_score, white_score),
+        };
+        self.score_a += score_a;
+        self.score_b += score_b;
+        board.result = Some(result);
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.board_matches
+            .iter()
+            .all(|board| board.result.is_some())
+    }
+
+    /// Winning team, by board points then `tiebreak`. None if it's split.
+    pub fn winner(&self) -> Option<Team> {
+        match self.score_a.cmp(&self.score_b) {
+            std::cmp::Ordering::Greater => Some(Team::A),
+            std::cmp::Ordering::Less => Some(Team::B),
+            std::cmp::Ordering::Equal => match self.tiebreak {
+                TeamTiebreak::Split => None,
+                TeamTiebreak::TopBoard => self.board_matches.iter().find_map(TeamBoard::winner),
+            },
+        }
+    }
+}
@@ -0,0 +1,242 @@
+//! Multi-test harness for end-to-end tests against cw-chess, enabled by the
+//! `testing` feature.
+//!
+//! ```ignore
+//! let mut suite = ChessAppBuilder::new()
+//!     .with_player("alice", coins(100, DENOM))
+//!     .with_player("bob", coins(100, DENOM))
+//!     .build();
+//! let id = suite.start_match("alice", "bob", Coin::new(10, DENOM)).unwrap();
+//! suite.play_pgn(&id, "1. f3 e5 2. g4 Qh4# 0-1").unwrap();
+//! suite.assert_balance("bob", 110);
+//! ```
+use cosmwasm_std::{Addr, Coin, CosmosMsg, Empty, StdResult, Uint128};
+use cozy_chess::{Board, Color};
+use cw_multi_test::error::{anyhow, AnyResult};
+use cw_multi_test::{App, AppBuilder, AppResponse, Contract, ContractWrapper, Executor};
+
+use crate::helpers::CwChessContract;
+use crate::msg::{InstantiateMsg, MatchResponse};
+use crate::notation::parse_san;
+
+pub const DENOM: &str = "untrn";
+pub const ADMIN: &str = "admin";
+pub const MIN_BET: u128 = 10;
+
+pub fn chess_contract() -> Box<dyn Contract<Empty>> {
+    let contract = ContractWrapper::new(
+        crate::contract::execute,
+        crate::contract::instantiate,
+        crate::contract::query,
+    )
+    .with_reply(crate::contract::reply)
+    .with_migrate(crate::contract::migrate);
+    Box::new(contract)
+}
+
+pub struct ChessAppBuilder {
+    admin: Addr,
+    min_bet: Coin,
+    players: Vec<(Addr, Vec<Coin>)>,
+}
+
+impl Default for ChessAppBuilder {
+    fn default() -> Self {
+        Self {
+            admin: Addr::unchecked(ADMIN),
+            min_bet: Coin::new(MIN_BET, DENOM),
+            players: vec![],
+        }
+    }
+}
+
+impl ChessAppBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_admin(mut self, admin: &str) -> Self {
+        self.admin = Addr::unchecked(admin);
+        self
+    }
+
+    pub fn with_min_bet(mut self, min_bet: Coin) -> Self {
+        self.min_bet = min_bet;
+        self
+    }
+
+    /// Adds an account holding `funds` from genesis.
+    pub fn with_player(mut self, player: &str, funds: Vec<Coin>) -> Self {
+        self.players.push((Addr::unchecked(player), funds));
+        self
+    }
+
+    /// Funds the players, then stores and instantiates the contract.
+    pub fn build(self) -> ChessApp {
+        let mut app = AppBuilder::new().build(|router, _, storage| {
+            for (player, funds) in &self.players {
+                router
+                    .bank
+                    .init_balance(storage, player, funds.clone())
+                    .unwrap();
+            }
+        });
+
+        let code_id = app.store_code(chess_contract());
+        let contract = app
+            .instantiate_contract(
+                code_id,
+                self.admin.clone(),
+                &InstantiateMsg {
+                    min_bet: self.min_bet.clone(),
+                },
+                &[],
+                "cw-chess",
+                Some(self.admin.to_string()),
+            )
+            .unwrap();
+
+        ChessApp {
+            app,
+            contract: CwChessContract(contract),
+            admin: self.admin,
+            denom: self.min_bet.denom,
+        }
+    }
+}
+
+pub struct ChessApp {
+    pub app: App,
+    pub contract: CwChessContract,
+    pub admin: Addr,
+    denom: String,
+}
+
+impl ChessApp {
+    /// Executes a message built with one of the `CwChessContract` helpers.
+    pub fn execute(&mut self, sender: &str, msg: StdResult<CosmosMsg>) -> AnyResult<AppResponse> {
+        self.app.execute(Addr::unchecked(sender), msg?)
+    }
+
+    /// Challenges `black`, an address or nickname, and returns the id of the
+    /// new match.
+    pub fn create_match(
+        &mut self,
+        white: &str,
+        black: &str,
+        bet: Coin,
+        rated: bool,
+    ) -> AnyResult<String> {
+        let msg = self.contract.create_match(black, rated, None, bet);
+        let response = self.execute(white, msg)?;
+        response
+            .events
+            .iter()
+            .filter(|event| event.ty == "wasm-match_created")
+            .flat_map(|event| &event.attributes)
+            .find(|attr| attr.key == "match_id")
+            .map(|attr| attr.value.clone())
+            .ok_or_else(|| anyhow!("no match_created event"))
+    }
+
+    /// Creates a rated match and has `black` join it with the same stake.
+    pub fn start_match(&mut self, white: &str, black: &str, bet: Coin) -> AnyResult<String> {
+        let match_id = self.create_match(white, black, bet.clone(), true)?;
+        let msg = self.contract.join_match(&match_id, None, bet);
+        self.execute(black, msg)?;
+        Ok(match_id)
+    }
+
+    /// Plays `moves` (e.g. `e2e4`), each sent by the player whose turn it is,
+    /// and returns the response to the last one.
+    pub fn play_uci(&mut self, match_id: &str, moves: &[&str]) -> AnyResult<AppResponse> {
+        let mut response = AppResponse::default();
+        for mv in moves {
+            let (_, player) = self.position(match_id)?;
+            let msg = self.contract.make_move(match_id, *mv);
+            response = self.app.execute(player, msg?)?;
+        }
+        Ok(response)
+    }
+
+    /// Plays the moves of a PGN movetext (e.g. `1. e4 e5 2. Nf3 Nc6`); tags,
+    /// comments, move numbers and the result are ignored.
+    pub fn play_pgn(&mut self, match_id: &str, pgn: &str) -> AnyResult<AppResponse> {
+        let mut response = AppResponse::default();
+        for san in pgn_moves(pgn) {
+            let (board, player) = self.position(match_id)?;
+            let mv = parse_san(&board, &san).ok_or_else(|| anyhow!("illegal move {san}"))?;
+            let msg = self.contract.make_move(match_id, mv.to_string());
+            response = self.app.execute(player, msg?)?;
+        }
+        Ok(response)
+    }
+
+    pub fn chess_match(&self, match_id: &str) -> StdResult<MatchResponse> {
+        self.contract.chess_match(&self.app.wrap(), match_id)
+    }
+
+    /// Balance of `addr` in the denom of the minimum bet.
+    pub fn balance(&self, addr: &str) -> Uint128 {
+        self.app
+            .wrap()
+            .query_balance(addr, &self.denom)
+            .unwrap()
+            .amount
+    }
+
+    pub fn assert_balance(&self, addr: &str, expected: u128) {
+        assert_eq!(
+            Uint128::new(expected),
+            self.balance(addr),
+            "unexpected {} balance of {addr}",
+            self.denom
+        );
+    }
+
+    // Current board, and the player to move
+    fn position(&self, match_id: &str) -> AnyResult<(Board, Addr)> {
+        let response = self.chess_match(match_id)?;
+        let board: Board = response
+            .board
+            .parse()
+            .map_err(|_| anyhow!("invalid board {}", response.board))?;
+        let player = match board.side_to_move() {
+            Color::White => response.chess_match.challenger,
+            Color::Black => response.chess_match.opponent,
+        };
+        Ok((board, player))
+    }
+}
+
+/// Moves of a PGN game, in standard algebraic notation.
+pub fn pgn_moves(pgn: &str) -> Vec<String> {
+    let mut movetext = String::new();
+    let mut depth = 0;
+    for line in pgn
+        .lines()
+        .filter(|line| !line.trim_start().starts_with('['))
+    {
+        for c in line.chars() {
+            match c {
+                '{' | '(' => depth += 1,
+                '}' | ')' => depth -= 1,
+                _ if depth == 0 => movetext.push(c),
+                _ => {}
+            }
+        }
+        movetext.push(' ');
+    }
+
+    movetext
+        .split_whitespace()
+        // Move numbers may be glued to the move, as in `1.e4`
+        .map(|token| token.rsplit('.').next().unwrap_or_default())
+        .filter(|token| {
+            !token.is_empty()
+                && !token.starts_with('$')
+                && !matches!(*token, "1-0" | "0-1" | "1/2-1/2" | "*")
+        })
+        .map(str::to_string)
+        .collect()
+}
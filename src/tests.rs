@@ -1,16 +1,44 @@
 use crate::{
+    challenge::ChallengePolicy,
     contract::*,
-    game::{Match, MatchState, NextMove},
+    eco::{classify, Opening, ECO_TABLE, MAX_OPENING_PLIES},
+    encoding::{pack_board, unpack_board},
+    error::{
+        ChallengeRejectedReason, InvalidIbcChannelReason, InvalidProfileReason,
+        InvalidPuzzleReason, InvalidTeamMatchReason,
+    },
+    game::{Adjudication, Match, MatchResult, MatchState, MatchStyle, NextMove, PlayedMove},
+    ibc::{ibc_timeout, IbcEscrow, IbcOpponent, IBC_ORDER, IBC_VERSION},
+    ibc_channel_connect, ibc_channel_open, ibc_packet_ack, ibc_packet_receive, ibc_packet_timeout,
+    market::{Market, SideBetConfig},
     msg::*,
+    notation::{parse_san, to_san},
+    puzzle::{
+        key_move_forces_mate, solution_commitment, Puzzle, SearchLimitExceeded, MAX_SEARCH_NODES,
+        REVEAL_WINDOW,
+    },
+    season::{keep_leaders, Season, SeasonStanding},
     state::*,
+    team::{Team, TeamMatch, TeamMatchState, TeamTiebreak},
+    tournament::{
+        greedy_swiss_pairings, seed_commitment, swiss_pairings, Color, PairingLimitExceeded,
+        Seeding, Standing, TieGame, TieGameKind, TournamentKind, TournamentState,
+    },
     ContractError,
 };
 
 use cosmwasm_std::{
+    from_json,
     testing::{
-        mock_dependencies_with_balances, mock_env, mock_info, MockApi, MockQuerier, MockStorage,
+        mock_dependencies_with_balances, mock_env, mock_ibc_channel_connect_ack,
+        mock_ibc_channel_open_init, mock_ibc_channel_open_try, mock_ibc_packet_ack,
+        mock_ibc_packet_recv, mock_ibc_packet_timeout, mock_info, MockApi, MockQuerier,
+        MockStorage,
     },
-    Addr, BankMsg, Coin, CosmosMsg, Env, Event, MessageInfo, OwnedDeps, Response, Uint128,
+    to_json_binary, Addr, Attribute, BankMsg, Binary, Coin, ContractInfoResponse, ContractResult,
+    CosmosMsg, Decimal, Env, Event, IbcAcknowledgement, IbcMsg, IbcOrder, MessageInfo, Order,
+    OwnedDeps, Reply, ReplyOn, Response, StdAck, SubMsg, SubMsgResult, SystemError, SystemResult,
+    Uint128, WasmMsg, WasmQuery,
 };
 // use cosmwasm_std::{BalanceResponse, BankQuery, QueryRequest};
 
@@ -128,7 +156,9 @@ fn create_match_succeeds() {
     let _res = instantiate(ctx.deps.as_mut(), ctx.env.clone(), admin_info, init_msg).unwrap();
 
     let create_msg = ExecuteMsg::CreateMatch {
-        opponent: ctx.player_b_addr.clone(),
+        opponent: ctx.player_b_addr.to_string(),
+        rated: None,
+        arbiter: None,
     };
 
     let player_a_info = ctx.player_a_info_with_bet();
@@ -195,7 +225,9 @@ fn abort_match_succeeds() {
     let _res = instantiate(ctx.deps.as_mut(), ctx.env.clone(), admin_info, init_msg).unwrap();
 
     let create_msg = ExecuteMsg::CreateMatch {
-        opponent: ctx.player_b_addr.clone(),
+        opponent: ctx.player_b_addr.to_string(),
+        rated: None,
+        arbiter: None,
     };
 
     let player_a_info = ctx.player_a_info_with_bet();
@@ -257,7 +289,9 @@ fn join_match_succeeds() {
     let _res = instantiate(ctx.deps.as_mut(), ctx.env.clone(), admin_info, init_msg).unwrap();
 
     let create_msg = ExecuteMsg::CreateMatch {
-        opponent: ctx.player_b_addr.clone(),
+        opponent: ctx.player_b_addr.to_string(),
+        rated: None,
+        arbiter: None,
     };
 
     let player_a_info = ctx.player_a_info_with_bet();
@@ -274,6 +308,7 @@ fn join_match_succeeds() {
 
     let abort_msg = ExecuteMsg::JoinMatch {
         match_id: hex::encode(match_id),
+        expected_arbiter: None,
     };
     let player_b_info = ctx.player_b_info_with_bet();
     let res = execute(ctx.deps.as_mut(), ctx.env.clone(), player_b_info, abort_msg).unwrap();
@@ -309,7 +344,9 @@ fn make_move_succeeds() {
     let _res = instantiate(ctx.deps.as_mut(), ctx.env.clone(), admin_info, init_msg).unwrap();
 
     let create_msg = ExecuteMsg::CreateMatch {
-        opponent: ctx.player_b_addr.clone(),
+        opponent: ctx.player_b_addr.to_string(),
+        rated: None,
+        arbiter: None,
     };
 
     let player_a_info = ctx.player_a_info_with_bet();
@@ -326,6 +363,7 @@ fn make_move_succeeds() {
 
     let abort_msg = ExecuteMsg::JoinMatch {
         match_id: hex::encode(match_id),
+        expected_arbiter: None,
     };
     let player_b_info = ctx.player_b_info_with_bet();
     let res = execute(ctx.deps.as_mut(), ctx.env.clone(), player_b_info, abort_msg).unwrap();
@@ -338,7 +376,7 @@ fn make_move_succeeds() {
     assert_eq!(0, res.messages.len());
 
     let actual = MATCHES.load(ctx.deps.as_ref().storage, match_id).unwrap();
-    let expected = Match::new_ext(
+    let mut expected = Match::new_ext(
         ctx.player_a_addr.clone(),
         ctx.player_b_addr.clone(),
         MatchState::OnGoing(NextMove::Blacks),
@@ -348,6 +386,18 @@ fn make_move_succeeds() {
         ctx.bet.clone(),
     )
     .set_board_state("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1".to_string());
+    expected.opening = Some(Opening {
+        eco: "B00".to_string(),
+        name: "King's Pawn Opening".to_string(),
+    });
+    assert_eq!(expected, actual);
+    let expected = PlayedMove {
+        mov: "e2e4".to_string(),
+        block_height: ctx.env.block.height,
+    };
+    let actual = MATCH_MOVES
+        .load(ctx.deps.as_ref().storage, (match_id.as_slice(), 0))
+        .unwrap();
     assert_eq!(expected, actual);
 
     let expected = Response::new()
@@ -357,6 +407,7 @@ fn make_move_succeeds() {
             Event::new("move_executed")
                 .add_attribute("match_id", hex::encode(match_id))
                 .add_attribute("player", &ctx.player_a_addr)
+                .add_attribute("signer", &ctx.player_a_addr)
                 .add_attribute("move", "e2e4"),
         );
     assert_eq!(expected, res);
@@ -423,6 +474,7 @@ fn make_move_succeeds() {
             Event::new("move_executed")
                 .add_attribute("match_id", hex::encode(match_id))
                 .add_attribute("player", &ctx.player_b_addr)
+                .add_attribute("signer", &ctx.player_b_addr)
                 .add_attribute("move", "f6f2"),
         )
         .add_event(
@@ -432,7 +484,9 @@ fn make_move_succeeds() {
                 .add_attribute(
                     "board",
                     "r1b1k1nr/pppp1ppp/2N5/2b5/4P3/2P5/PP3qPP/RNBQKB1R w KQkq - 0 7",
-                ),
+                )
+                .add_attribute("eco", "C45")
+                .add_attribute("opening", "Scotch Game"),
         )
         .add_message(CosmosMsg::Bank(BankMsg::Send {
             to_address: ctx.player_b_addr.to_string(),
@@ -452,7 +506,9 @@ fn draw_works() {
     let _res = instantiate(ctx.deps.as_mut(), ctx.env.clone(), admin_info, init_msg).unwrap();
 
     let create_msg = ExecuteMsg::CreateMatch {
-        opponent: ctx.player_b_addr.clone(),
+        opponent: ctx.player_b_addr.to_string(),
+        rated: None,
+        arbiter: None,
     };
 
     let player_a_info = ctx.player_a_info_with_bet();
@@ -469,6 +525,7 @@ fn draw_works() {
 
     let abort_msg = ExecuteMsg::JoinMatch {
         match_id: hex::encode(match_id),
+        expected_arbiter: None,
     };
     let player_b_info = ctx.player_b_info_with_bet();
     let res = execute(ctx.deps.as_mut(), ctx.env.clone(), player_b_info, abort_msg).unwrap();
@@ -521,6 +578,7 @@ fn draw_works() {
             Event::new("move_executed")
                 .add_attribute("match_id", hex::encode(match_id))
                 .add_attribute("player", &ctx.player_a_addr)
+                .add_attribute("signer", &ctx.player_a_addr)
                 .add_attribute("move", "c8e6"),
         )
         .add_event(
@@ -529,7 +587,9 @@ fn draw_works() {
                 .add_attribute(
                     "board",
                     "5bnr/4p1pq/4Qpkr/7p/2P4P/8/PP1PPPP1/RNB1KBNR b KQ - 2 10",
-                ),
+                )
+                .add_attribute("eco", "A10")
+                .add_attribute("opening", "English Opening"),
         )
         .add_message(CosmosMsg::Bank(BankMsg::Send {
             to_address: ctx.player_a_addr.to_string(),
@@ -542,6 +602,3690 @@ fn draw_works() {
     assert_eq!(expected, res);
 }
 
+#[test]
+fn round_robin_tournament_pays_out_by_rank() {
+    let mut ctx = TestContext::new();
+    let player_c_addr = Addr::unchecked("neutron1qnk2n4nlkpw9xfqntladh74w6ujtulwnmxnh3k");
+
+    let admin_info = ctx.admin_info();
+    let init_msg = InstantiateMsg {
+        min_bet: Coin::new(10, NATIVE_DENOM),
+    };
+    let _res = instantiate(
+        ctx.deps.as_mut(),
+        ctx.env.clone(),
+        admin_info.clone(),
+        init_msg,
+    )
+    .unwrap();
+
+    let create_msg = ExecuteMsg::CreateTournament {
+        kind: TournamentKind::RoundRobin,
+        entry_fee: ctx.bet.clone(),
+        max_players: 4,
+        time_control: MatchStyle::Blitz,
+        prize_split: vec![70, 30],
+    };
+    let _res = execute(
+        ctx.deps.as_mut(),
+        ctx.env.clone(),
+        admin_info.clone(),
+        create_msg,
+    )
+    .unwrap();
+
+    let players = [
+        ctx.player_a_addr.clone(),
+        ctx.player_b_addr.clone(),
+        player_c_addr.clone(),
+    ];
+    for player in players.iter() {
+        let info = mock_info(player.as_str(), &[ctx.bet.clone()]);
+        let msg = ExecuteMsg::RegisterTournament { tournament_id: 0 };
+        let _res = execute(ctx.deps.as_mut(), ctx.env.clone(), info, msg).unwrap();
+    }
+
+    let info = ctx.player_a_info_with_bet();
+    let err = execute(
+        ctx.deps.as_mut(),
+        ctx.env.clone(),
+        info,
+        ExecuteMsg::StartTournament {
+            tournament_id: 0,
+            seed: None,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(ContractError::NotTournamentOrganizer {}, err);
+
+    let res = execute(
+        ctx.deps.as_mut(),
+        ctx.env.clone(),
+        admin_info,
+        ExecuteMsg::StartTournament {
+            tournament_id: 0,
+            seed: None,
+        },
+    )
+    .unwrap();
+    assert_eq!(0, res.messages.len());
+
+    let tournament = TOURNAMENTS.load(ctx.deps.as_ref().storage, 0).unwrap();
+    assert_eq!(TournamentState::OnGoing, tournament.state);
+    assert_eq!(3, tournament.pending_games);
+
+    // Player A beats everyone, player B beats player C
+    let mut res = Response::new();
+    for nonce in 0..3u64 {
+        let match_id = MATCH_IDS.load(ctx.deps.as_ref().storage, nonce).unwrap();
+        let chess_match = MATCHES.load(ctx.deps.as_ref().storage, match_id).unwrap();
+        assert_eq!(Some(0), chess_match.tournament);
+        assert_eq!(MatchStyle::Blitz, chess_match.style);
+
+        let white = mock_info(chess_match.challenger.as_str(), &[]);
+        let black = mock_info(chess_match.opponent.as_str(), &[]);
+        let white_should_win = chess_match.challenger == ctx.player_a_addr
+            || (chess_match.challenger == ctx.player_b_addr
+                && chess_match.opponent == player_c_addr);
+        res = if white_should_win {
+            play_line(&mut ctx, match_id, &white, &black, SCHOLARS_MATE)
+        } else {
+            play_line(&mut ctx, match_id, &white, &black, FOOLS_MATE)
+        };
+    }
+
+    let tournament = TOURNAMENTS.load(ctx.deps.as_ref().storage, 0).unwrap();
+    assert_eq!(TournamentState::Finished, tournament.state);
+
+    // 30 untrn prize pool, split 70/30
+    assert_eq!(
+        vec![
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: ctx.player_a_addr.to_string(),
+                amount: vec![Coin::new(21, NATIVE_DENOM)],
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: ctx.player_b_addr.to_string(),
+                amount: vec![Coin::new(9, NATIVE_DENOM)],
+            })),
+        ],
+        res.messages
+    );
+
+    let standings: StandingsResponse = from_json(
+        query(
+            ctx.deps.as_ref(),
+            ctx.env.clone(),
+            QueryMsg::Standings { tournament_id: 0 },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    let ranking: Vec<(Addr, Decimal, Decimal)> = standings
+        .standings
+        .into_iter()
+        .map(|s| (s.player, s.points, s.sonneborn_berger))
+        .collect();
+    assert_eq!(
+        vec![
+            (
+                ctx.player_a_addr.clone(),
+                Decimal::percent(200),
+                Decimal::percent(100)
+            ),
+            (ctx.player_b_addr.clone(), Decimal::one(), Decimal::zero()),
+            (player_c_addr, Decimal::zero(), Decimal::zero()),
+        ],
+        ranking
+    );
+}
+
+#[test]
+fn register_tournament_requires_entry_fee() {
+    let mut ctx = TestContext::new();
+
+    let admin_info = ctx.admin_info();
+    let init_msg = InstantiateMsg {
+        min_bet: Coin::new(10, NATIVE_DENOM),
+    };
+    let _res = instantiate(
+        ctx.deps.as_mut(),
+        ctx.env.clone(),
+        admin_info.clone(),
+        init_msg,
+    )
+    .unwrap();
+
+    let err = execute(
+        ctx.deps.as_mut(),
+        ctx.env.clone(),
+        admin_info.clone(),
+        ExecuteMsg::CreateTournament {
+            kind: TournamentKind::RoundRobin,
+            entry_fee: ctx.bet.clone(),
+            max_players: 2,
+            time_control: MatchStyle::Rapid,
+            prize_split: vec![50, 30, 20],
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        ContractError::InvalidTournament {
+            reason: crate::error::InvalidTournamentReason::TooManyPrizes
+        },
+        err
+    );
+
+    let create_msg = ExecuteMsg::CreateTournament {
+        kind: TournamentKind::RoundRobin,
+        entry_fee: ctx.bet.clone(),
+        max_players: 2,
+        time_control: MatchStyle::Rapid,
+        prize_split: vec![100],
+    };
+    let _res = execute(ctx.deps.as_mut(), ctx.env.clone(), admin_info, create_msg).unwrap();
+
+    let register_msg = ExecuteMsg::RegisterTournament { tournament_id: 0 };
+    let info = ctx.player_a_no_bet();
+    let err = execute(
+        ctx.deps.as_mut(),
+        ctx.env.clone(),
+        info,
+        register_msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(
+        ContractError::InvalidBet {
+            reason: crate::error::InvalidBetReason::MissingBet
+        },
+        err
+    );
+
+    let info = ctx.player_a_info_with_bet();
+    let _res = execute(
+        ctx.deps.as_mut(),
+        ctx.env.clone(),
+        info.clone(),
+        register_msg.clone(),
+    )
+    .unwrap();
+    assert_eq!(
+        ContractError::AlreadyRegistered {},
+        execute(
+            ctx.deps.as_mut(),
+            ctx.env.clone(),
+            info,
+            register_msg.clone()
+        )
+        .unwrap_err()
+    );
+
+    let info = ctx.player_b_info_with_bet();
+    let _res = execute(
+        ctx.deps.as_mut(),
+        ctx.env.clone(),
+        info,
+        register_msg.clone(),
+    )
+    .unwrap();
+
+    let info = mock_info(
+        "neutron1qnk2n4nlkpw9xfqntladh74w6ujtulwnmxnh3k",
+        &[ctx.bet.clone()],
+    );
+    assert_eq!(
+        ContractError::TournamentFull {},
+        execute(
+            ctx.deps.as_mut(),
+            ctx.env.clone(),
+            info,
+            register_msg.clone()
+        )
+        .unwrap_err()
+    );
+
+    // A tournament that never starts can be called off, refunding entrants
+    let cancel_msg = ExecuteMsg::CancelTournament { tournament_id: 0 };
+    let info = ctx.player_a_no_bet();
+    assert_eq!(
+        ContractError::NotTournamentOrganizer {},
+        execute(ctx.deps.as_mut(), ctx.env.clone(), info, cancel_msg.clone()).unwrap_err()
+    );
+    let info = ctx.admin_info();
+    let res = execute(ctx.deps.as_mut(), ctx.env.clone(), info, cancel_msg).unwrap();
+    assert_eq!(
+        vec![
+            SubMsg::new(BankMsg::Send {
+                to_address: ctx.player_a_addr.to_string(),
+                amount: vec![ctx.bet.clone()],
+            }),
+            SubMsg::new(BankMsg::Send {
+                to_address: ctx.player_b_addr.to_string(),
+                amount: vec![ctx.bet.clone()],
+            }),
+        ],
+        res.messages
+    );
+    let tournament = TOURNAMENTS.load(&ctx.deps.storage, 0).unwrap();
+    assert_eq!(TournamentState::Cancelled, tournament.state);
+    assert!(!STANDINGS.has(&ctx.deps.storage, (0, &ctx.player_a_addr)));
+    let info = ctx.player_a_info_with_bet();
+    assert_eq!(
+        ContractError::RegistrationClosed {},
+        execute(ctx.deps.as_mut(), ctx.env.clone(), info, register_msg).unwrap_err()
+    );
+}
+
+#[test]
+fn swiss_tournament_pairs_rounds_with_byes() {
+    let mut ctx = TestContext::new();
+    let player_c_addr = Addr::unchecked("neutron1qnk2n4nlkpw9xfqntladh74w6ujtulwnmxnh3k");
+
+    let admin_info = ctx.admin_info();
+    let init_msg = InstantiateMsg {
+        min_bet: Coin::new(10, NATIVE_DENOM),
+    };
+    let _res = instantiate(
+        ctx.deps.as_mut(),
+        ctx.env.clone(),
+        admin_info.clone(),
+        init_msg,
+    )
+    .unwrap();
+
+    let create_msg = ExecuteMsg::CreateTournament {
+        kind: TournamentKind::Swiss { rounds: 2 },
+        entry_fee: ctx.bet.clone(),
+        max_players: 3,
+        time_control: MatchStyle::Daily,
+        prize_split: vec![100],
+    };
+    let _res = execute(
+        ctx.deps.as_mut(),
+        ctx.env.clone(),
+        admin_info.clone(),
+        create_msg,
+    )
+    .unwrap();
+
+    for player in [&ctx.player_a_addr, &ctx.player_b_addr, &player_c_addr] {
+        let info = mock_info(player.as_str(), &[ctx.bet.clone()]);
+        let msg = ExecuteMsg::RegisterTournament { tournament_id: 0 };
+        let _res = execute(ctx.deps.as_mut(), ctx.env.clone(), info, msg).unwrap();
+    }
+
+    let res = execute(
+        ctx.deps.as_mut(),
+        ctx.env.clone(),
+        admin_info,
+        ExecuteMsg::StartTournament {
+            tournament_id: 0,
+            seed: None,
+        },
+    )
+    .unwrap();
+    assert!(res.events.contains(
+        &Event::new("tournament_round")
+            .add_attribute("tournament_id", "0")
+            .add_attribute("round", "1")
+            .add_attribute("bye", &player_c_addr)
+    ));
+
+    // Round 1: A (white) beats B
+    let match_id = MATCH_IDS.load(ctx.deps.as_ref().storage, 0).unwrap();
+    let chess_match = MATCHES.load(ctx.deps.as_ref().storage, match_id).unwrap();
+    assert_eq!(ctx.player_a_addr, chess_match.challenger);
+    assert_eq!(ctx.player_b_addr, chess_match.opponent);
+
+    let pair_msg = ExecuteMsg::PairNextRound { tournament_id: 0 };
+    let info = ctx.player_b_no_bet();
+    assert_eq!(
+        ContractError::RoundInProgress {},
+        execute(
+            ctx.deps.as_mut(),
+            ctx.env.clone(),
+            info.clone(),
+            pair_msg.clone()
+        )
+        .unwrap_err()
+    );
+
+    let (white, black) = (ctx.player_a_no_bet(), ctx.player_b_no_bet());
+    let res = play_line(&mut ctx, match_id, &white, &black, SCHOLARS_MATE);
+    assert_eq!(0, res.messages.len());
+
+    // Round 2: B gets the bye, C has had fewer whites than A
+    let res = execute(
+        ctx.deps.as_mut(),
+        ctx.env.clone(),
+        info.clone(),
+        pair_msg.clone(),
+    )
+    .unwrap();
+    assert!(res.events.contains(
+        &Event::new("tournament_round")
+            .add_attribute("tournament_id", "0")
+            .add_attribute("round", "2")
+            .add_attribute("bye", &ctx.player_b_addr)
+    ));
+
+    let match_id = MATCH_IDS.load(ctx.deps.as_ref().storage, 1).unwrap();
+    let chess_match = MATCHES.load(ctx.deps.as_ref().storage, match_id).unwrap();
+    assert_eq!(player_c_addr, chess_match.challenger);
+    assert_eq!(ctx.player_a_addr, chess_match.opponent);
+
+    let white = mock_info(player_c_addr.as_str(), &[]);
+    let black = ctx.player_a_no_bet();
+    let res = play_line(&mut ctx, match_id, &white, &black, FOOLS_MATE);
+    assert_eq!(
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: ctx.player_a_addr.to_string(),
+            amount: vec![Coin::new(30, NATIVE_DENOM)],
+        }))],
+        res.messages
+    );
+
+    assert_eq!(
+        ContractError::TournamentNotOnGoing {},
+        execute(ctx.deps.as_mut(), ctx.env.clone(), info, pair_msg).unwrap_err()
+    );
+
+    let standings: StandingsResponse = from_json(
+        query(
+            ctx.deps.as_ref(),
+            ctx.env.clone(),
+            QueryMsg::Standings { tournament_id: 0 },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    let ranking: Vec<(Addr, Decimal, Decimal)> = standings
+        .standings
+        .into_iter()
+        .map(|s| (s.player, s.points, s.buchholz))
+        .collect();
+    assert_eq!(
+        vec![
+            (
+                ctx.player_a_addr.clone(),
+                Decimal::percent(200),
+                Decimal::percent(200)
+            ),
+            (
+                ctx.player_b_addr.clone(),
+                Decimal::one(),
+                Decimal::percent(200)
+            ),
+            (player_c_addr, Decimal::one(), Decimal::percent(200)),
+        ],
+        ranking
+    );
+}
+
+#[test]
+fn swiss_pairings_avoid_rematches() {
+    let players: Vec<Addr> = ["a", "b", "c", "d"]
+        .into_iter()
+        .map(Addr::unchecked)
+        .collect();
+    let mut standings: Vec<Standing> = players.iter().cloned().map(Standing::new).collect();
+
+    let (pairings, bye) = swiss_pairings(&players, &standings, 1).unwrap().unwrap();
+    assert_eq!(None, bye);
+    assert_eq!(
+        vec![
+            (players[0].clone(), players[2].clone()),
+            (players[1].clone(), players[3].clone()),
+        ],
+        pairings
+    );
+
+    // a beat c, b beat d: winners meet, then losers
+    standings[0].record(&players[2], Color::White, 2);
+    standings[2].record(&players[0], Color::Black, 0);
+    standings[1].record(&players[3], Color::White, 2);
+    standings[3].record(&players[1], Color::Black, 0);
+    let (pairings, _) = swiss_pairings(&players, &standings, 2).unwrap().unwrap();
+    assert_eq!(
+        vec![
+            (players[1].clone(), players[0].clone()),
+            (players[3].clone(), players[2].clone()),
+        ],
+        pairings
+    );
+
+    // a drew b and c drew d: only a-d and b-c remain, c is owed white
+    standings[0].record(&players[1], Color::Black, 1);
+    standings[1].record(&players[0], Color::White, 1);
+    standings[2].record(&players[3], Color::Black, 1);
+    standings[3].record(&players[2], Color::White, 1);
+    let (pairings, _) = swiss_pairings(&players, &standings, 3).unwrap().unwrap();
+    assert_eq!(
+        vec![
+            (players[0].clone(), players[3].clone()),
+            (players[2].clone(), players[1].clone()),
+        ],
+        pairings
+    );
+
+    standings[0].record(&players[3], Color::White, 2);
+    standings[3].record(&players[0], Color::Black, 0);
+    standings[1].record(&players[2], Color::Black, 2);
+    standings[2].record(&players[1], Color::White, 0);
+    assert_eq!(Ok(None), swiss_pairings(&players, &standings, 4));
+}
+
+#[test]
+fn swiss_pairings_fall_back_to_a_rematch_past_the_step_limit() {
+    let players: Vec<Addr> = (0..20)
+        .map(|i| Addr::unchecked(format!("player{i:02}")))
+        .collect();
+    let mut standings: Vec<Standing> = players.iter().cloned().map(Standing::new).collect();
+    // The bottom three lost to everyone but each other, so one of them is
+    // left without a new opponent, which the search only finds out after
+    // trying the many ways of pairing the players above them
+    for i in 17..20 {
+        for j in 0..17 {
+            standings[i].record(&players[j], Color::White, 0);
+            standings[j].record(&players[i], Color::Black, 2);
+        }
+    }
+    assert_eq!(
+        Err(PairingLimitExceeded),
+        swiss_pairings(&players, &standings, 18)
+    );
+
+    let (pairings, bye) = greedy_swiss_pairings(&players, &standings, 18);
+    assert_eq!(None, bye);
+    assert_eq!(10, pairings.len());
+    let rematches = pairings
+        .iter()
+        .filter(|(white, black)| {
+            let standing = standings.iter().find(|s| s.player == *white).unwrap();
+            standing.has_played(black)
+        })
+        .count();
+    assert_eq!(1, rematches);
+}
+
+#[test]
+fn knockout_tournament_breaks_draws_and_advances_winners() {
+    let mut ctx = TestContext::new();
+    let player_c_addr = Addr::unchecked("neutron1qnk2n4nlkpw9xfqntladh74w6ujtulwnmxnh3k");
+    let player_d_addr = Addr::unchecked("neutron1u4rc7lmfdkmcx6a6x5tyxwkgeezh3e0x2dzqmm");
+
+    let admin_info = ctx.admin_info();
+    let init_msg = InstantiateMsg {
+        min_bet: Coin::new(10, NATIVE_DENOM),
+    };
+    let _res = instantiate(
+        ctx.deps.as_mut(),
+        ctx.env.clone(),
+        admin_info.clone(),
+        init_msg,
+    )
+    .unwrap();
+
+    let create_msg = ExecuteMsg::CreateTournament {
+        kind: TournamentKind::Knockout {
+            seeding: Seeding::Rating,
+        },
+        entry_fee: ctx.bet.clone(),
+        max_players: 4,
+        time_control: MatchStyle::Rapid,
+        prize_split: vec![60, 30, 10],
+    };
+    let _res = execute(
+        ctx.deps.as_mut(),
+        ctx.env.clone(),
+        admin_info.clone(),
+        create_msg,
+    )
+    .unwrap();
+
+    let players = [
+        (ctx.player_a_addr.clone(), 1500),
+        (ctx.player_b_addr.clone(), 1400),
+        (player_c_addr.clone(), 1300),
+        (player_d_addr.clone(), 1200),
+    ];
+    // Register in reverse rating order, seeding must not depend on it. Nor
+    // does it depend on season ratings, ranked the other way round here
+    CURRENT_SEASON.save(ctx.deps.as_mut().storage, &0).unwrap();
+    for (player, rating) in players.iter().rev() {
+        RATINGS
+            .save(ctx.deps.as_mut().storage, player, rating)
+            .unwrap();
+        let standing = SeasonStanding {
+            rating: 2700 - rating,
+            ..SeasonStanding::new(player.clone())
+        };
+        SEASON_STANDINGS
+            .save(ctx.deps.as_mut().storage, (0, player), &standing)
+            .unwrap();
+        let info = mock_info(player.as_str(), &[ctx.bet.clone()]);
+        let msg = ExecuteMsg::RegisterTournament { tournament_id: 0 };
+        let _res = execute(ctx.deps.as_mut(), ctx.env.clone(), info, msg).unwrap();
+    }
+
+    let _res = execute(
+        ctx.deps.as_mut(),
+        ctx.env.clone(),
+        admin_info,
+        ExecuteMsg::StartTournament {
+            tournament_id: 0,
+            seed: None,
+        },
+    )
+    .unwrap();
+
+    // Seed 1 meets seed 4, seed 2 meets seed 3
+    let bracket = query_bracket(&ctx);
+    assert_eq!(2, bracket.rounds.len());
+    assert_eq!(
+        (Some(ctx.player_a_addr.clone()), Some(player_d_addr.clone())),
+        (
+            bracket.rounds[0][0].top.clone(),
+            bracket.rounds[0][0].bottom.clone()
+        )
+    );
+    assert_eq!(
+        (Some(ctx.player_b_addr.clone()), Some(player_c_addr.clone())),
+        (
+            bracket.rounds[0][1].top.clone(),
+            bracket.rounds[0][1].bottom.clone()
+        )
+    );
+
+    // A and D draw the regular game, the reversed game and the Armageddon,
+    // where D has black and goes through
+    let (player_a_addr, player_d) = (ctx.player_a_addr.clone(), player_d_addr.clone());
+    for (kind, white, black) in [
+        (TieGameKind::Regular, &player_a_addr, &player_d),
+        (TieGameKind::Reversed, &player_d, &player_a_addr),
+        (TieGameKind::Armageddon, &player_a_addr, &player_d),
+    ] {
+        let game = query_bracket(&ctx).rounds[0][0]
+            .games
+            .last()
+            .unwrap()
+            .clone();
+        assert_eq!(
+            (&kind, white, black),
+            (&game.kind, &game.white, &game.black)
+        );
+        let (white, black) = (
+            mock_info(white.as_str(), &[]),
+            mock_info(black.as_str(), &[]),
+        );
+        play_line(&mut ctx, match_id_of(&game), &white, &black, STALEMATE);
+    }
+
+    let game = query_bracket(&ctx).rounds[0][1].games[0].clone();
+    let (white, black) = (
+        ctx.player_b_no_bet(),
+        mock_info(player_c_addr.as_str(), &[]),
+    );
+    play_line(&mut ctx, match_id_of(&game), &white, &black, SCHOLARS_MATE);
+
+    let bracket = query_bracket(&ctx);
+    assert_eq!(Some(player_d_addr.clone()), bracket.rounds[0][0].winner);
+    assert_eq!(Some(ctx.player_b_addr.clone()), bracket.rounds[0][1].winner);
+
+    // Final: D (white) loses to B
+    let game = bracket.rounds[1][0].games[0].clone();
+    assert_eq!(player_d_addr, game.white);
+    let (white, black) = (
+        mock_info(player_d_addr.as_str(), &[]),
+        ctx.player_b_no_bet(),
+    );
+    let res = play_line(&mut ctx, match_id_of(&game), &white, &black, FOOLS_MATE);
+
+    // 40 untrn prize pool, semi-finalists share the third prize
+    assert_eq!(
+        vec![
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: ctx.player_b_addr.to_string(),
+                amount: vec![Coin::new(24, NATIVE_DENOM)],
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: player_d_addr.to_string(),
+                amount: vec![Coin::new(12, NATIVE_DENOM)],
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: ctx.player_a_addr.to_string(),
+                amount: vec![Coin::new(2, NATIVE_DENOM)],
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: player_c_addr.to_string(),
+                amount: vec![Coin::new(2, NATIVE_DENOM)],
+            })),
+        ],
+        res.messages
+    );
+    let tournament = TOURNAMENTS.load(ctx.deps.as_ref().storage, 0).unwrap();
+    assert_eq!(TournamentState::Finished, tournament.state);
+}
+
+#[test]
+fn random_knockout_draw_needs_the_committed_seed() {
+    let mut ctx = TestContext::new();
+    let _match_id = start_match(&mut ctx, false);
+    let admin_info = ctx.admin_info();
+    let create = |seed_commitment: String| ExecuteMsg::CreateTournament {
+        kind: TournamentKind::Knockout {
+            seeding: Seeding::Random { seed_commitment },
+        },
+        entry_fee: Coin::new(0, NATIVE_DENOM),
+        max_players: 2,
+        time_control: MatchStyle::Rapid,
+        prize_split: vec![100],
+    };
+    assert_eq!(
+        ContractError::InvalidTournament {
+            reason: crate::error::InvalidTournamentReason::SeedCommitment
+        },
+        execute(
+            ctx.deps.as_mut(),
+            ctx.env.clone(),
+            admin_info.clone(),
+            create("not a hash".to_string())
+        )
+        .unwrap_err()
+    );
+    let msg = create(seed_commitment("organizer secret"));
+    let _res = execute(ctx.deps.as_mut(), ctx.env.clone(), admin_info.clone(), msg).unwrap();
+    for info in [ctx.player_a_no_bet(), ctx.player_b_no_bet()] {
+        let msg = ExecuteMsg::RegisterTournament { tournament_id: 0 };
+        let _res = execute(ctx.deps.as_mut(), ctx.env.clone(), info, msg).unwrap();
+    }
+
+    for seed in [None, Some("another secret".to_string())] {
+        let msg = ExecuteMsg::StartTournament {
+            tournament_id: 0,
+            seed,
+        };
+        assert_eq!(
+            ContractError::InvalidSeed {},
+            execute(ctx.deps.as_mut(), ctx.env.clone(), admin_info.clone(), msg).unwrap_err()
+        );
+    }
+    let msg = ExecuteMsg::StartTournament {
+        tournament_id: 0,
+        seed: Some("organizer secret".to_string()),
+    };
+    let _res = execute(ctx.deps.as_mut(), ctx.env.clone(), admin_info, msg).unwrap();
+    let tie = query_bracket(&ctx).rounds[0][0].clone();
+    let mut seeded = [tie.top.unwrap(), tie.bottom.unwrap()];
+    seeded.sort();
+    let mut players = [ctx.player_a_addr.clone(), ctx.player_b_addr.clone()];
+    players.sort();
+    assert_eq!(players, seeded);
+}
+
+#[test]
+fn side_bets_settle_with_match_result() {
+    let mut ctx = TestContext::new();
+    let spectator_1 = "neutron1qnk2n4nlkpw9xfqntladh74w6ujtulwnmxnh3k";
+    let spectator_2 = "neutron1u4rc7lmfdkmcx6a6x5tyxwkgeezh3e0x2dzqmm";
+
+    let admin_info = ctx.admin_info();
+    let init_msg = InstantiateMsg {
+        min_bet: Coin::new(10, NATIVE_DENOM),
+    };
+    let _res = instantiate(
+        ctx.deps.as_mut(),
+        ctx.env.clone(),
+        admin_info.clone(),
+        init_msg,
+    )
+    .unwrap();
+
+    let config_msg = ExecuteMsg::UpdateSideBetConfig {
+        close_after_plies: 2,
+        house_fee_percent: 2,
+    };
+    let info = ctx.player_a_no_bet();
+    assert_eq!(
+        ContractError::Unauthorized {},
+        execute(ctx.deps.as_mut(), ctx.env.clone(), info, config_msg.clone()).unwrap_err()
+    );
+    let _res = execute(ctx.deps.as_mut(), ctx.env.clone(), admin_info, config_msg).unwrap();
+
+    let create_msg = ExecuteMsg::CreateMatch {
+        opponent: ctx.player_b_addr.to_string(),
+        rated: None,
+        arbiter: None,
+    };
+    let info = ctx.player_a_info_with_bet();
+    let _res = execute(ctx.deps.as_mut(), ctx.env.clone(), info, create_msg).unwrap();
+    let match_id = exec::match_id(&ctx.player_a_addr, &ctx.player_b_addr, 0u64);
+
+    let side_bet = |outcome: MatchResult| ExecuteMsg::PlaceSideBet {
+        match_id: hex::encode(match_id),
+        outcome,
+    };
+    let info = ctx.player_a_info_with_bet();
+    assert_eq!(
+        ContractError::PlayersCannotSideBet {},
+        execute(
+            ctx.deps.as_mut(),
+            ctx.env.clone(),
+            info,
+            side_bet(MatchResult::WhiteWins)
+        )
+        .unwrap_err()
+    );
+
+    for (bettor, outcome, amount) in [
+        (spectator_1, MatchResult::BlackWins, 20),
+        (spectator_2, MatchResult::WhiteWins, 10),
+        (spectator_2, MatchResult::BlackWins, 10),
+        (spectator_1, MatchResult::BlackWins, 10),
+    ] {
+        let info = mock_info(bettor, &[Coin::new(amount, NATIVE_DENOM)]);
+        let _res = execute(ctx.deps.as_mut(), ctx.env.clone(), info, side_bet(outcome)).unwrap();
+    }
+
+    let join_msg = ExecuteMsg::JoinMatch {
+        match_id: hex::encode(match_id),
+        expected_arbiter: None,
+    };
+    let info = ctx.player_b_info_with_bet();
+    let _res = execute(ctx.deps.as_mut(), ctx.env.clone(), info, join_msg).unwrap();
+
+    let (white, black) = (ctx.player_a_no_bet(), ctx.player_b_no_bet());
+    let _res = play_line(&mut ctx, match_id, &white, &black, &FOOLS_MATE[..2]);
+
+    let info = mock_info(spectator_1, &[Coin::new(10, NATIVE_DENOM)]);
+    assert_eq!(
+        ContractError::BettingClosed {},
+        execute(
+            ctx.deps.as_mut(),
+            ctx.env.clone(),
+            info,
+            side_bet(MatchResult::Draw)
+        )
+        .unwrap_err()
+    );
+
+    let market: Option<Market> = from_json(
+        query(
+            ctx.deps.as_ref(),
+            ctx.env.clone(),
+            QueryMsg::SideBetMarket {
+                match_id: hex::encode(match_id),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    let market = market.unwrap();
+    assert_eq!(Uint128::new(40), market.black_wins);
+    assert_eq!(2, market.bettors);
+
+    let res = play_line(&mut ctx, match_id, &white, &black, &FOOLS_MATE[2..]);
+
+    // 50 untrn pool minus 2% fee, shared 30:10 by the black backers
+    assert_eq!(
+        vec![
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: ctx.player_b_addr.to_string(),
+                amount: vec![Coin::new(20, NATIVE_DENOM)],
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: spectator_1.to_string(),
+                amount: vec![Coin::new(36, NATIVE_DENOM)],
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: spectator_2.to_string(),
+                amount: vec![Coin::new(12, NATIVE_DENOM)],
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "admin".to_string(),
+                amount: vec![Coin::new(2, NATIVE_DENOM)],
+            })),
+        ],
+        res.messages
+    );
+    assert_eq!(
+        Ok(None),
+        MARKETS.may_load(ctx.deps.as_ref().storage, match_id)
+    );
+}
+
+#[test]
+fn side_bets_refunded_when_match_aborted() {
+    let mut ctx = TestContext::new();
+    let spectator = "neutron1qnk2n4nlkpw9xfqntladh74w6ujtulwnmxnh3k";
+
+    let admin_info = ctx.admin_info();
+    let init_msg = InstantiateMsg {
+        min_bet: Coin::new(10, NATIVE_DENOM),
+    };
+    let _res = instantiate(ctx.deps.as_mut(), ctx.env.clone(), admin_info, init_msg).unwrap();
+
+    let create_msg = ExecuteMsg::CreateMatch {
+        opponent: ctx.player_b_addr.to_string(),
+        rated: None,
+        arbiter: None,
+    };
+    let info = ctx.player_a_info_with_bet();
+    let _res = execute(ctx.deps.as_mut(), ctx.env.clone(), info.clone(), create_msg).unwrap();
+    let match_id = exec::match_id(&ctx.player_a_addr, &ctx.player_b_addr, 0u64);
+
+    let bet_msg = ExecuteMsg::PlaceSideBet {
+        match_id: hex::encode(match_id),
+        outcome: MatchResult::Draw,
+    };
+    let spectator_info = mock_info(spectator, &[Coin::new(15, NATIVE_DENOM)]);
+    let _res = execute(ctx.deps.as_mut(), ctx.env.clone(), spectator_info, bet_msg).unwrap();
+
+    let abort_msg = ExecuteMsg::AbortMatch {
+        match_id: hex::encode(match_id),
+    };
+    let res = execute(ctx.deps.as_mut(), ctx.env.clone(), info, abort_msg).unwrap();
+    assert_eq!(
+        vec![
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: ctx.player_a_addr.to_string(),
+                amount: vec![ctx.bet.clone()],
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: spectator.to_string(),
+                amount: vec![Coin::new(15, NATIVE_DENOM)],
+            })),
+        ],
+        res.messages
+    );
+    assert!(res.events.contains(
+        &Event::new("side_bets_voided").add_attribute("match_id", hex::encode(match_id))
+    ));
+}
+
+#[test]
+fn move_keys_let_delegate_play_for_grantor() {
+    let mut ctx = TestContext::new();
+    let hot_wallet = "neutron1qnk2n4nlkpw9xfqntladh74w6ujtulwnmxnh3k";
+    let match_id = start_match(&mut ctx, true);
+
+    let grant_msg = ExecuteMsg::GrantMoveKey {
+        delegate: Addr::unchecked(hot_wallet),
+        expires: ctx.env.block.height + 100,
+        match_id: Some(hex::encode(match_id)),
+    };
+    let info = ctx.player_a_no_bet();
+    let _res = execute(ctx.deps.as_mut(), ctx.env.clone(), info, grant_msg).unwrap();
+
+    let res = play_move(&mut ctx, mock_info(hot_wallet, &[]), match_id, "e2e4").unwrap();
+    let expected = Response::new()
+        .add_attribute("action", "make_move")
+        .add_attribute("sender", hot_wallet)
+        .add_event(
+            Event::new("move_executed")
+                .add_attribute("match_id", hex::encode(match_id))
+                .add_attribute("player", &ctx.player_a_addr)
+                .add_attribute("signer", hot_wallet)
+                .add_attribute("move", "e2e4"),
+        );
+    assert_eq!(expected, res);
+
+    // The key only speaks for its grantor
+    assert_eq!(
+        ContractError::NotYourTurn {},
+        play_move(&mut ctx, mock_info(hot_wallet, &[]), match_id, "e7e5").unwrap_err()
+    );
+    let info = ctx.player_b_no_bet();
+    let _res = play_move(&mut ctx, info, match_id, "e7e5").unwrap();
+
+    // Expired keys are ignored
+    let mut env = ctx.env.clone();
+    env.block.height += 100;
+    let res = execute(
+        ctx.deps.as_mut(),
+        env,
+        mock_info(hot_wallet, &[]),
+        ExecuteMsg::MakeMove {
+            match_id: hex::encode(match_id),
+            move_fen: "d2d4".to_string(),
+        },
+    );
+    assert_eq!(ContractError::NotYourTurn {}, res.unwrap_err());
+
+    // Delegates can't create matches on the grantor's behalf
+    let create_msg = ExecuteMsg::CreateMatch {
+        opponent: ctx.player_b_addr.to_string(),
+        rated: None,
+        arbiter: None,
+    };
+    let info = mock_info(hot_wallet, std::slice::from_ref(&ctx.bet));
+    let _res = execute(ctx.deps.as_mut(), ctx.env.clone(), info, create_msg).unwrap();
+    assert_eq!(
+        Ok(None),
+        PLAYER_MATCHES.may_load(
+            ctx.deps.as_ref().storage,
+            (
+                &ctx.player_a_addr,
+                exec::match_id(&ctx.player_a_addr, &ctx.player_b_addr, 1u64)
+            )
+        )
+    );
+
+    let revoke_msg = ExecuteMsg::RevokeMoveKey {
+        delegate: Addr::unchecked(hot_wallet),
+    };
+    let info = ctx.player_a_no_bet();
+    let _res = execute(
+        ctx.deps.as_mut(),
+        ctx.env.clone(),
+        info.clone(),
+        revoke_msg.clone(),
+    )
+    .unwrap();
+    assert_eq!(
+        ContractError::NotYourTurn {},
+        play_move(&mut ctx, mock_info(hot_wallet, &[]), match_id, "d2d4").unwrap_err()
+    );
+    assert_eq!(
+        ContractError::UnknownMoveKey {},
+        execute(ctx.deps.as_mut(), ctx.env.clone(), info, revoke_msg).unwrap_err()
+    );
+}
+
+#[test]
+fn resign_and_draw_agreement_settle_match() {
+    let mut ctx = TestContext::new();
+    let match_id = start_match(&mut ctx, true);
+
+    let draw_msg = ExecuteMsg::OfferDraw {
+        match_id: hex::encode(match_id),
+    };
+    let info_a = ctx.player_a_no_bet();
+    let info_b = ctx.player_b_no_bet();
+    let res = execute(
+        ctx.deps.as_mut(),
+        ctx.env.clone(),
+        info_a.clone(),
+        draw_msg.clone(),
+    )
+    .unwrap();
+    assert!(res.messages.is_empty());
+    assert!(res.events.contains(
+        &Event::new("draw_offered")
+            .add_attribute("match_id", hex::encode(match_id))
+            .add_attribute("player", &ctx.player_a_addr)
+    ));
+
+    // Moving on declines the offer
+    let _res = play_line(&mut ctx, match_id, &info_a, &info_b, &["e2e4", "e7e5"]);
+    let _res = execute(
+        ctx.deps.as_mut(),
+        ctx.env.clone(),
+        info_b.clone(),
+        draw_msg.clone(),
+    )
+    .unwrap();
+    assert_eq!(
+        Some(ctx.player_b_addr.clone()),
+        DRAW_OFFERS
+            .may_load(ctx.deps.as_ref().storage, match_id)
+            .unwrap()
+    );
+    let res = execute(ctx.deps.as_mut(), ctx.env.clone(), info_a.clone(), draw_msg).unwrap();
+    assert!(res.events.iter().any(|e| e.ty == "match_drawn"));
+    assert_eq!(
+        vec![
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: ctx.player_a_addr.to_string(),
+                amount: vec![ctx.bet.clone()],
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: ctx.player_b_addr.to_string(),
+                amount: vec![ctx.bet.clone()],
+            })),
+        ],
+        res.messages
+    );
+
+    let match_id = start_match(&mut ctx, true);
+    let resign_msg = ExecuteMsg::Resign {
+        match_id: hex::encode(match_id),
+    };
+    let res = execute(
+        ctx.deps.as_mut(),
+        ctx.env.clone(),
+        info_a,
+        resign_msg.clone(),
+    )
+    .unwrap();
+    assert!(res.events.contains(
+        &Event::new("match_won")
+            .add_attribute("match_id", hex::encode(match_id))
+            .add_attribute("winner", &ctx.player_b_addr)
+            .add_attribute(
+                "board",
+                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"
+            )
+            .add_attribute("reason", "resignation")
+    ));
+    assert_eq!(
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: ctx.player_b_addr.to_string(),
+            amount: vec![Coin::new(20, NATIVE_DENOM)],
+        }))],
+        res.messages
+    );
+    assert!(matches!(
+        execute(ctx.deps.as_mut(), ctx.env.clone(), info_b, resign_msg).unwrap_err(),
+        ContractError::UnknownMatch {}
+    ));
+}
+
+#[test]
+fn bot_contracts_notified_of_their_turn() {
+    let mut ctx = TestContext::new();
+    let bot = Addr::unchecked("neutron1u4rc7lmfdkmcx6a6x5tyxwkgeezh3e0x2dzqmm");
+    let bot_contract = bot.to_string();
+    ctx.deps.querier.update_wasm(move |query| match query {
+        WasmQuery::ContractInfo { contract_addr } if *contract_addr == bot_contract => {
+            SystemResult::Ok(ContractResult::Ok(
+                to_json_binary(&ContractInfoResponse::default()).unwrap(),
+            ))
+        }
+        _ => SystemResult::Err(SystemError::NoSuchContract {
+            addr: "unknown".to_string(),
+        }),
+    });
+
+    let admin_info = ctx.admin_info();
+    let init_msg = InstantiateMsg {
+        min_bet: Coin::new(10, NATIVE_DENOM),
+    };
+    let _res = instantiate(ctx.deps.as_mut(), ctx.env.clone(), admin_info, init_msg).unwrap();
+
+    let info = ctx.player_a_no_bet();
+    assert_eq!(
+        ContractError::NotAContract {},
+        execute(
+            ctx.deps.as_mut(),
+            ctx.env.clone(),
+            info,
+            ExecuteMsg::RegisterBotCallback {}
+        )
+        .unwrap_err()
+    );
+    let bot_info = mock_info(bot.as_str(), std::slice::from_ref(&ctx.bet));
+    let _res = execute(
+        ctx.deps.as_mut(),
+        ctx.env.clone(),
+        bot_info.clone(),
+        ExecuteMsg::RegisterBotCallback {},
+    )
+    .unwrap();
+
+    let create_msg = ExecuteMsg::CreateMatch {
+        opponent: bot.to_string(),
+        rated: None,
+        arbiter: None,
+    };
+    let info = ctx.player_a_info_with_bet();
+    let _res = execute(ctx.deps.as_mut(), ctx.env.clone(), info, create_msg).unwrap();
+    let match_id = exec::match_id(&ctx.player_a_addr, &bot, 0u64);
+
+    // White moves first, the bot has nothing to do yet
+    let join_msg = ExecuteMsg::JoinMatch {
+        match_id: hex::encode(match_id),
+        expected_arbiter: None,
+    };
+    let res = execute(
+        ctx.deps.as_mut(),
+        ctx.env.clone(),
+        bot_info.clone(),
+        join_msg,
+    )
+    .unwrap();
+    assert!(res.messages.is_empty());
+
+    let info = ctx.player_a_no_bet();
+    let res = play_move(&mut ctx, info, match_id, "e2e4").unwrap();
+    let expected = SubMsg::reply_on_error(
+        WasmMsg::Execute {
+            contract_addr: bot.to_string(),
+            msg: to_json_binary(&BotExecuteMsg::YourTurn {
+                match_id: hex::encode(match_id),
+                fen: "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1".to_string(),
+                last_move: Some("e2e4".to_string()),
+            })
+            .unwrap(),
+            funds: vec![],
+        },
+        BOT_CALLBACK_REPLY_ID,
+    )
+    .with_gas_limit(BOT_CALLBACK_GAS_LIMIT);
+    assert_eq!(vec![expected], res.messages);
+
+    // A failing bot doesn't revert the move
+    let res = reply(
+        ctx.deps.as_mut(),
+        ctx.env.clone(),
+        Reply {
+            id: BOT_CALLBACK_REPLY_ID,
+            result: SubMsgResult::Err("out of gas".to_string()),
+        },
+    )
+    .unwrap();
+    assert!(res.messages.is_empty());
+
+    let _res = execute(
+        ctx.deps.as_mut(),
+        ctx.env.clone(),
+        bot_info.clone(),
+        ExecuteMsg::UnregisterBotCallback {},
+    )
+    .unwrap();
+    let res = play_move(&mut ctx, bot_info, match_id, "e7e5").unwrap();
+    assert!(res.messages.is_empty());
+}
+
+#[test]
+fn hooks_notified_when_match_finishes() {
+    let mut ctx = TestContext::new();
+    let hook = Addr::unchecked("neutron1u4rc7lmfdkmcx6a6x5tyxwkgeezh3e0x2dzqmm");
+    let match_id = start_match(&mut ctx, true);
+
+    let add_msg = ExecuteMsg::AddHook { addr: hook.clone() };
+    let info = ctx.player_a_no_bet();
+    assert_eq!(
+        ContractError::Unauthorized {},
+        execute(ctx.deps.as_mut(), ctx.env.clone(), info, add_msg.clone()).unwrap_err()
+    );
+    let admin_info = ctx.admin_info();
+    let _res = execute(
+        ctx.deps.as_mut(),
+        ctx.env.clone(),
+        admin_info.clone(),
+        add_msg.clone(),
+    )
+    .unwrap();
+    assert_eq!(
+        ContractError::HookAlreadyRegistered {},
+        execute(
+            ctx.deps.as_mut(),
+            ctx.env.clone(),
+            admin_info.clone(),
+            add_msg
+        )
+        .unwrap_err()
+    );
+
+    let hooks: HooksResponse =
+        from_json(query(ctx.deps.as_ref(), ctx.env.clone(), QueryMsg::Hooks {}).unwrap()).unwrap();
+    assert_eq!(vec![hook.clone()], hooks.hooks);
+
+    let (info_a, info_b) = (ctx.player_a_no_bet(), ctx.player_b_no_bet());
+    let res = play_line(&mut ctx, match_id, &info_a, &info_b, FOOLS_MATE);
+    let expected = SubMsg::reply_on_error(
+        WasmMsg::Execute {
+            contract_addr: hook.to_string(),
+            msg: to_json_binary(&HookExecuteMsg::MatchFinishedHook {
+                match_id: hex::encode(match_id),
+                white: ctx.player_a_addr.clone(),
+                black: ctx.player_b_addr.clone(),
+                result: MatchResult::BlackWins,
+                pot: Coin::new(20, NATIVE_DENOM),
+            })
+            .unwrap(),
+            funds: vec![],
+        },
+        HOOK_REPLY_ID,
+    )
+    .with_gas_limit(HOOK_GAS_LIMIT);
+    assert_eq!(Some(&expected), res.messages.last());
+
+    // A failing hook doesn't revert the finishing move
+    let res = reply(
+        ctx.deps.as_mut(),
+        ctx.env.clone(),
+        Reply {
+            id: HOOK_REPLY_ID,
+            result: SubMsgResult::Err("out of gas".to_string()),
+        },
+    )
+    .unwrap();
+    assert!(res.messages.is_empty());
+
+    let remove_msg = ExecuteMsg::RemoveHook { addr: hook };
+    let _res = execute(
+        ctx.deps.as_mut(),
+        ctx.env.clone(),
+        admin_info.clone(),
+        remove_msg.clone(),
+    )
+    .unwrap();
+    assert_eq!(
+        ContractError::UnknownHook {},
+        execute(ctx.deps.as_mut(), ctx.env.clone(), admin_info, remove_msg).unwrap_err()
+    );
+
+    let match_id = start_match(&mut ctx, true);
+    let res = play_line(&mut ctx, match_id, &info_a, &info_b, FOOLS_MATE);
+    assert_eq!(1, res.messages.len());
+}
+
+#[test]
+fn rematch_swaps_colours_with_same_stake() {
+    let mut ctx = TestContext::new();
+    let match_id = start_match(&mut ctx, true);
+    let (info_a, info_b) = (ctx.player_a_no_bet(), ctx.player_b_no_bet());
+    let _res = play_line(&mut ctx, match_id, &info_a, &info_b, FOOLS_MATE);
+
+    let offer_msg = ExecuteMsg::OfferRematch {
+        match_id: hex::encode(match_id),
+    };
+    let accept_msg = ExecuteMsg::AcceptRematch {
+        match_id: hex::encode(match_id),
+    };
+    assert!(matches!(
+        execute(
+            ctx.deps.as_mut(),
+            ctx.env.clone(),
+            info_a.clone(),
+            offer_msg.clone()
+        )
+        .unwrap_err(),
+        ContractError::InvalidBet {
+            reason: crate::error::InvalidBetReason::MissingBet
+        }
+    ));
+    let info = ctx.player_b_info_with_bet();
+    assert_eq!(
+        ContractError::NoRematchOffer {},
+        execute(
+            ctx.deps.as_mut(),
+            ctx.env.clone(),
+            info.clone(),
+            accept_msg.clone()
+        )
+        .unwrap_err()
+    );
+    let _res = execute(ctx.deps.as_mut(), ctx.env.clone(), info, offer_msg.clone()).unwrap();
+
+    // The offerer can't accept its own offer
+    let info = ctx.player_b_info_with_bet();
+    assert_eq!(
+        ContractError::Unauthorized {},
+        execute(ctx.deps.as_mut(), ctx.env.clone(), info, accept_msg.clone()).unwrap_err()
+    );
+
+    let nonce = NEXT_NONCE.load(ctx.deps.as_ref().storage).unwrap();
+    let info = ctx.player_a_info_with_bet();
+    let res = execute(ctx.deps.as_mut(), ctx.env.clone(), info, accept_msg.clone()).unwrap();
+    let rematch_id = exec::match_id(&ctx.player_b_addr, &ctx.player_a_addr, nonce);
+    assert!(res
+        .events
+        .contains(&Event::new("match_started").add_attribute("match_id", hex::encode(rematch_id))));
+
+    let rematch = MATCHES.load(ctx.deps.as_ref().storage, rematch_id).unwrap();
+    assert_eq!(ctx.player_b_addr, rematch.challenger);
+    assert_eq!(ctx.player_a_addr, rematch.opponent);
+    assert_eq!(ctx.bet, rematch.bet);
+    assert_eq!(MatchState::OnGoing(NextMove::Whites), rematch.state);
+    assert_eq!(
+        ContractError::UnknownMatch {},
+        execute(
+            ctx.deps.as_mut(),
+            ctx.env.clone(),
+            info_a.clone(),
+            accept_msg
+        )
+        .unwrap_err()
+    );
+
+    // Offers lapse after the rematch window, but stay refundable
+    let _res = play_line(&mut ctx, rematch_id, &info_b, &info_a, FOOLS_MATE);
+    let offer_msg = ExecuteMsg::OfferRematch {
+        match_id: hex::encode(rematch_id),
+    };
+    let info = ctx.player_a_info_with_bet();
+    let _res = execute(ctx.deps.as_mut(), ctx.env.clone(), info, offer_msg).unwrap();
+
+    let mut env = ctx.env.clone();
+    env.block.height += REMATCH_WINDOW + 1;
+    let info = ctx.player_b_info_with_bet();
+    let accept_msg = ExecuteMsg::AcceptRematch {
+        match_id: hex::encode(rematch_id),
+    };
+    assert_eq!(
+        ContractError::RematchUnavailable {},
+        execute(ctx.deps.as_mut(), env.clone(), info, accept_msg).unwrap_err()
+    );
+    let cancel_msg = ExecuteMsg::CancelRematch {
+        match_id: hex::encode(rematch_id),
+    };
+    let res = execute(ctx.deps.as_mut(), env, info_a.clone(), cancel_msg).unwrap();
+    assert_eq!(
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: ctx.player_a_addr.to_string(),
+            amount: vec![ctx.bet.clone()],
+        }))],
+        res.messages
+    );
+    assert!(!FINISHED_MATCHES.has(ctx.deps.as_ref().storage, rematch_id));
+
+    // Matches nobody offered a rematch of are forgotten once the window is over
+    let lapsed_id = start_match(&mut ctx, true);
+    let _res = play_line(&mut ctx, lapsed_id, &info_a, &info_b, FOOLS_MATE);
+    assert!(FINISHED_MATCHES.has(ctx.deps.as_ref().storage, lapsed_id));
+    ctx.env.block.height += REMATCH_WINDOW + 1;
+    let match_id = start_match(&mut ctx, true);
+    let _res = play_line(&mut ctx, match_id, &info_a, &info_b, FOOLS_MATE);
+    assert!(!FINISHED_MATCHES.has(ctx.deps.as_ref().storage, lapsed_id));
+    assert!(FINISHED_MATCHES.has(ctx.deps.as_ref().storage, match_id));
+}
+
+#[test]
+fn takebacks_need_consent_in_unrated_matches() {
+    let mut ctx = TestContext::new();
+    let (info_a, info_b) = (ctx.player_a_no_bet(), ctx.player_b_no_bet());
+
+    let match_id = start_match(&mut ctx, true);
+    let _res = play_move(&mut ctx, info_a.clone(), match_id, "e2e4").unwrap();
+    let request_msg = ExecuteMsg::RequestTakeback {
+        match_id: hex::encode(match_id),
+        plies: 1,
+    };
+    assert_eq!(
+        ContractError::TakebacksNotAllowed {},
+        execute(
+            ctx.deps.as_mut(),
+            ctx.env.clone(),
+            info_a.clone(),
+            request_msg
+        )
+        .unwrap_err()
+    );
+
+    let match_id = start_match(&mut ctx, false);
+    let start = ctx.env.block.height;
+    let _res = play_move(&mut ctx, info_a.clone(), match_id, "e2e4").unwrap();
+    ctx.env.block.height += 1;
+    let _res = play_move(&mut ctx, info_b.clone(), match_id, "e7e5").unwrap();
+    ctx.env.block.height += 1;
+    let _res = play_move(&mut ctx, info_a.clone(), match_id, "d1h5").unwrap();
+
+    let request = |plies| ExecuteMsg::RequestTakeback {
+        match_id: hex::encode(match_id),
+        plies,
+    };
+    let accept_msg = ExecuteMsg::AcceptTakeback {
+        match_id: hex::encode(match_id),
+    };
+    assert_eq!(
+        ContractError::InvalidTakeback {},
+        execute(
+            ctx.deps.as_mut(),
+            ctx.env.clone(),
+            info_a.clone(),
+            request(4)
+        )
+        .unwrap_err()
+    );
+    let _res = execute(
+        ctx.deps.as_mut(),
+        ctx.env.clone(),
+        info_a.clone(),
+        request(1),
+    )
+    .unwrap();
+    assert_eq!(
+        ContractError::Unauthorized {},
+        execute(
+            ctx.deps.as_mut(),
+            ctx.env.clone(),
+            info_a.clone(),
+            accept_msg.clone()
+        )
+        .unwrap_err()
+    );
+
+    // Playing on declines the request
+    let _res = play_move(&mut ctx, info_b.clone(), match_id, "b8c6").unwrap();
+    assert_eq!(
+        ContractError::NoTakebackRequest {},
+        execute(
+            ctx.deps.as_mut(),
+            ctx.env.clone(),
+            info_a.clone(),
+            accept_msg.clone()
+        )
+        .unwrap_err()
+    );
+
+    let _res = execute(
+        ctx.deps.as_mut(),
+        ctx.env.clone(),
+        info_b.clone(),
+        request(3),
+    )
+    .unwrap();
+    BOT_CALLBACKS
+        .save(ctx.deps.as_mut().storage, &ctx.player_b_addr, &())
+        .unwrap();
+    let config = SideBetConfig {
+        close_after_plies: 2,
+        house_fee_percent: 2,
+    };
+    SIDE_BET_CONFIG
+        .save(ctx.deps.as_mut().storage, &config)
+        .unwrap();
+    let res = execute(
+        ctx.deps.as_mut(),
+        ctx.env.clone(),
+        info_a.clone(),
+        accept_msg,
+    )
+    .unwrap();
+
+    // The side to move is told its turn came back
+    let expected = SubMsg::reply_on_error(
+        WasmMsg::Execute {
+            contract_addr: ctx.player_b_addr.to_string(),
+            msg: to_json_binary(&BotExecuteMsg::YourTurn {
+                match_id: hex::encode(match_id),
+                fen: "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1".to_string(),
+                last_move: None,
+            })
+            .unwrap(),
+            funds: vec![],
+        },
+        BOT_CALLBACK_REPLY_ID,
+    )
+    .with_gas_limit(BOT_CALLBACK_GAS_LIMIT);
+    assert_eq!(vec![expected], res.messages);
+
+    // Betting had closed before the takeback and stays closed
+    let info = mock_info("spectator", &[Coin::new(10, NATIVE_DENOM)]);
+    let side_bet_msg = ExecuteMsg::PlaceSideBet {
+        match_id: hex::encode(match_id),
+        outcome: MatchResult::Draw,
+    };
+    assert_eq!(
+        ContractError::BettingClosed {},
+        execute(ctx.deps.as_mut(), ctx.env.clone(), info, side_bet_msg).unwrap_err()
+    );
+    assert!(res.events.contains(
+        &Event::new("takeback_accepted")
+            .add_attribute("match_id", hex::encode(match_id))
+            .add_attribute("plies", "3")
+            .add_attribute(
+                "board",
+                "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1"
+            )
+    ));
+
+    let chess_match = MATCHES.load(ctx.deps.as_ref().storage, match_id).unwrap();
+    assert_eq!(MatchState::OnGoing(NextMove::Blacks), chess_match.state);
+    assert_eq!(start, chess_match.last_move);
+    let history = MATCH_MOVES
+        .prefix(match_id.as_slice())
+        .range(ctx.deps.as_ref().storage, None, None, Order::Ascending)
+        .map(|entry| entry.unwrap().1)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        vec![PlayedMove {
+            mov: "e2e4".to_string(),
+            block_height: start,
+        }],
+        history
+    );
+    let _res = play_move(&mut ctx, info_b, match_id, "c7c5").unwrap();
+}
+
+#[test]
+fn conditional_moves_reply_to_opponent() {
+    let mut ctx = TestContext::new();
+    let (info_a, info_b) = (ctx.player_a_no_bet(), ctx.player_b_no_bet());
+    let match_id = start_match(&mut ctx, true);
+    let _res = play_line(&mut ctx, match_id, &info_a, &info_b, &["e2e4", "e7e5"]);
+
+    let set_msg = |lines: &[&[&str]]| ExecuteMsg::SetConditionalMoves {
+        match_id: hex::encode(match_id),
+        lines: lines
+            .iter()
+            .map(|line| line.iter().map(|m| m.to_string()).collect())
+            .collect(),
+    };
+    assert_eq!(
+        ContractError::NotYourTurn {},
+        execute(
+            ctx.deps.as_mut(),
+            ctx.env.clone(),
+            info_a.clone(),
+            set_msg(&[&["b8c6"]])
+        )
+        .unwrap_err()
+    );
+    assert_eq!(
+        ContractError::IllegalMove {},
+        execute(
+            ctx.deps.as_mut(),
+            ctx.env.clone(),
+            info_b.clone(),
+            set_msg(&[&["g1f3", "e5e4"]])
+        )
+        .unwrap_err()
+    );
+    let lines: &[&[&str]] = &[&["g1f3", "b8c6", "f1c4", "g8f6"], &["d2d4", "e5d4"]];
+    let _res = execute(
+        ctx.deps.as_mut(),
+        ctx.env.clone(),
+        info_b.clone(),
+        set_msg(lines),
+    )
+    .unwrap();
+
+    let res = play_move(&mut ctx, info_a.clone(), match_id, "g1f3").unwrap();
+    assert!(res.events.contains(
+        &Event::new("move_executed")
+            .add_attribute("match_id", hex::encode(match_id))
+            .add_attribute("player", &ctx.player_b_addr)
+            .add_attribute("signer", &ctx.env.contract.address)
+            .add_attribute("move", "b8c6")
+    ));
+    let chess_match = MATCHES.load(ctx.deps.as_ref().storage, match_id).unwrap();
+    assert_eq!(MatchState::OnGoing(NextMove::Whites), chess_match.state);
+    assert_eq!(
+        Some(vec![vec!["f1c4".to_string(), "g8f6".to_string()]]),
+        CONDITIONAL_MOVES
+            .may_load(
+                ctx.deps.as_ref().storage,
+                (match_id.as_slice(), &ctx.player_b_addr)
+            )
+            .unwrap()
+    );
+
+    // Deviating from the queued lines drops them
+    let res = play_move(&mut ctx, info_a, match_id, "d2d4").unwrap();
+    assert_eq!(1, res.events.len());
+    assert_eq!(
+        None,
+        CONDITIONAL_MOVES
+            .may_load(
+                ctx.deps.as_ref().storage,
+                (match_id.as_slice(), &ctx.player_b_addr)
+            )
+            .unwrap()
+    );
+    let _res = play_move(&mut ctx, info_b, match_id, "e5d4").unwrap();
+}
+
+#[test]
+fn arbiters_adjudicate_matches() {
+    let mut ctx = TestContext::new();
+    let arbiter = "neutron1qnk2n4nlkpw9xfqntladh74w6ujtulwnmxnh3k";
+    let match_id = start_match(&mut ctx, true);
+    let adjudicate = |match_id, result| ExecuteMsg::Adjudicate {
+        match_id: hex::encode(match_id),
+        result,
+        reason: "player lost access to their wallet".to_string(),
+    };
+
+    // The admin arbitrates until an arbiter is appointed
+    let info = ctx.player_a_no_bet();
+    assert_eq!(
+        ContractError::Unauthorized {},
+        execute(
+            ctx.deps.as_mut(),
+            ctx.env.clone(),
+            info,
+            adjudicate(match_id, Adjudication::WhiteWins)
+        )
+        .unwrap_err()
+    );
+    let admin_info = ctx.admin_info();
+    let res = execute(
+        ctx.deps.as_mut(),
+        ctx.env.clone(),
+        admin_info.clone(),
+        adjudicate(match_id, Adjudication::WhiteWins),
+    )
+    .unwrap();
+    assert_eq!(
+        Event::new("match_adjudicated")
+            .add_attribute("match_id", hex::encode(match_id))
+            .add_attribute("arbiter", "admin")
+            .add_attribute("result", "WhiteWins")
+            .add_attribute("reason", "player lost access to their wallet")
+            .add_attribute(
+                "board",
+                "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"
+            ),
+        res.events[0]
+    );
+    assert_eq!(
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: ctx.player_a_addr.to_string(),
+            amount: vec![Coin::new(20, NATIVE_DENOM)],
+        }))],
+        res.messages
+    );
+
+    let update_msg = ExecuteMsg::UpdateArbiter {
+        arbiter: Addr::unchecked(arbiter),
+    };
+    let _res = execute(
+        ctx.deps.as_mut(),
+        ctx.env.clone(),
+        admin_info.clone(),
+        update_msg,
+    )
+    .unwrap();
+    let match_id = start_match(&mut ctx, true);
+    assert_eq!(
+        ContractError::Unauthorized {},
+        execute(
+            ctx.deps.as_mut(),
+            ctx.env.clone(),
+            admin_info,
+            adjudicate(match_id, Adjudication::Draw)
+        )
+        .unwrap_err()
+    );
+    let res = execute(
+        ctx.deps.as_mut(),
+        ctx.env.clone(),
+        mock_info(arbiter, &[]),
+        adjudicate(match_id, Adjudication::Void),
+    )
+    .unwrap();
+    assert_eq!(
+        vec![
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: ctx.player_a_addr.to_string(),
+                amount: vec![ctx.bet.clone()],
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: ctx.player_b_addr.to_string(),
+                amount: vec![ctx.bet.clone()],
+            })),
+        ],
+        res.messages
+    );
+    assert!(MATCHES
+        .may_load(ctx.deps.as_ref().storage, match_id)
+        .unwrap()
+        .is_none());
+
+    // Matches can have their own arbiter, agreed on when the opponent joins
+    let match_arbiter = "neutron1u4rc7lmfdkmcx6a6x5tyxwkgeezh3e0x2dzqmm";
+    let create_msg = ExecuteMsg::CreateMatch {
+        opponent: ctx.player_b_addr.to_string(),
+        rated: None,
+        arbiter: Some(Addr::unchecked(match_arbiter)),
+    };
+    let info = ctx.player_a_info_with_bet();
+    let _res = execute(ctx.deps.as_mut(), ctx.env.clone(), info, create_msg).unwrap();
+    let match_id = exec::match_id(&ctx.player_a_addr, &ctx.player_b_addr, 2u64);
+    let join_msg = |expected_arbiter: Option<&str>| ExecuteMsg::JoinMatch {
+        match_id: hex::encode(match_id),
+        expected_arbiter: expected_arbiter.map(Addr::unchecked),
+    };
+    let info = ctx.player_b_info_with_bet();
+    assert_eq!(
+        ContractError::ArbiterMismatch {},
+        execute(ctx.deps.as_mut(), ctx.env.clone(), info, join_msg(None)).unwrap_err()
+    );
+    let current: Addr = from_json(
+        query(
+            ctx.deps.as_ref(),
+            ctx.env.clone(),
+            QueryMsg::Arbiter {
+                match_id: Some(hex::encode(match_id)),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(Addr::unchecked(match_arbiter), current);
+
+    assert_eq!(
+        ContractError::InvalidAdjudication {},
+        execute(
+            ctx.deps.as_mut(),
+            ctx.env.clone(),
+            mock_info(match_arbiter, &[]),
+            adjudicate(match_id, Adjudication::BlackWins)
+        )
+        .unwrap_err()
+    );
+    let info = ctx.player_b_info_with_bet();
+    let join = join_msg(Some(match_arbiter));
+    let _res = execute(ctx.deps.as_mut(), ctx.env.clone(), info, join).unwrap();
+    let res = execute(
+        ctx.deps.as_mut(),
+        ctx.env.clone(),
+        mock_info(match_arbiter, &[]),
+        adjudicate(match_id, Adjudication::Void),
+    )
+    .unwrap();
+    assert_eq!(
+        vec![
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: ctx.player_a_addr.to_string(),
+                amount: vec![ctx.bet.clone()],
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: ctx.player_b_addr.to_string(),
+                amount: vec![ctx.bet.clone()],
+            })),
+        ],
+        res.messages
+    );
+}
+
+#[test]
+fn packed_board_round_trips() {
+    let mut positions = vec![
+        "r3k2r/8/8/8/8/8/8/R3K2R w Kq - 12 40".to_string(),
+        "4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 2".to_string(),
+        "8/8/8/8/8/8/8/K6k b - - 99 300".to_string(),
+    ];
+    for line in [FOOLS_MATE, SCHOLARS_MATE, STALEMATE] {
+        let mut board = cozy_chess::Board::default();
+        for move_fen in line {
+            board.play(move_fen.parse().unwrap());
+            positions.push(board.to_string());
+        }
+    }
+
+    for fen in positions {
+        let board = Match::decode_board(&fen).unwrap();
+        let packed = pack_board(&board);
+        assert!(packed.len() <= 30);
+        assert_eq!(board, unpack_board(&packed).unwrap());
+        assert_eq!(fen, unpack_board(&packed).unwrap().to_string());
+    }
+
+    let packed = pack_board(&cozy_chess::Board::default());
+    assert!(unpack_board(&packed[..packed.len() - 1]).is_err());
+    assert!(unpack_board(&packed[..10]).is_err());
+}
+
+#[test]
+fn san_resolves_to_legal_moves() {
+    let board = cozy_chess::Board::default();
+    let san = |board: &cozy_chess::Board, san: &str| parse_san(board, san).map(|mv| mv.to_string());
+    assert_eq!(Some("e2e4".to_string()), san(&board, "e4"));
+    assert_eq!(Some("g1f3".to_string()), san(&board, "Nf3!?"));
+    assert_eq!(None, san(&board, "e5"));
+    assert_eq!(None, san(&board, "Qd4"));
+
+    // Both rooks reach d1, only disambiguation tells them apart
+    let board: cozy_chess::Board = "4k3/8/8/8/8/8/8/R4RK1 w - - 0 1".parse().unwrap();
+    assert_eq!(None, san(&board, "Rd1"));
+    assert_eq!(Some("a1d1".to_string()), san(&board, "Rad1"));
+    assert_eq!(Some("f1d1".to_string()), san(&board, "Rfd1+"));
+
+    let board: cozy_chess::Board = "4k3/P7/8/8/8/8/8/R3K2R w KQ - 0 1".parse().unwrap();
+    assert_eq!(Some("e1h1".to_string()), san(&board, "O-O"));
+    assert_eq!(Some("e1a1".to_string()), san(&board, "O-O-O"));
+    assert_eq!(Some("a7a8q".to_string()), san(&board, "a8=Q+"));
+    assert_eq!(Some("a7a8n".to_string()), san(&board, "a8N"));
+}
+
+#[test]
+fn validate_line_replays_moves_without_state() {
+    let ctx = TestContext::new();
+    let validate = |start_fen: Option<&str>, moves: &[&str]| -> ValidateLineResponse {
+        let msg = QueryMsg::ValidateLine {
+            start_fen: start_fen.map(str::to_string),
+            moves: moves.iter().map(|mv| mv.to_string()).collect(),
+        };
+        from_json(query(ctx.deps.as_ref(), ctx.env.clone(), msg).unwrap()).unwrap()
+    };
+
+    assert_eq!(
+        ValidateLineResponse::Valid {
+            fen: "r1bqkb1r/pppp1Qpp/2n2n2/4p3/2B1P3/8/PPPP1PPP/RNB1K1NR b KQkq - 0 4".to_string(),
+            state: MatchState::Won,
+            san: ["e4", "e5", "Bc4", "Nc6", "Qh5", "Nf6", "Qxf7#"]
+                .map(str::to_string)
+                .to_vec(),
+        },
+        validate(None, SCHOLARS_MATE)
+    );
+    assert_eq!(
+        ValidateLineResponse::Invalid {
+            index: 7,
+            reason: "Match already finished".to_string(),
+        },
+        validate(None, &[SCHOLARS_MATE, &["e8e7"]].concat())
+    );
+    assert_eq!(
+        ValidateLineResponse::Invalid {
+            index: 1,
+            reason: "Illegal move".to_string(),
+        },
+        validate(None, &["e2e4", "e2e4"])
+    );
+    assert_eq!(
+        ValidateLineResponse::Invalid {
+            index: 0,
+            reason: "Invalid move encoding".to_string(),
+        },
+        validate(None, &["e2-e4"])
+    );
+
+    // From a custom position, castling king-to-rook
+    let ValidateLineResponse::Valid { state, san, .. } = validate(
+        Some("r3k3/8/8/8/8/8/8/R3K2R w KQq - 0 1"),
+        &["e1h1", "e8a8"],
+    ) else {
+        panic!("expected a valid line");
+    };
+    assert_eq!(MatchState::OnGoing(NextMove::Whites), state);
+    assert_eq!(vec!["O-O", "O-O-O"], san);
+    assert!(query(
+        ctx.deps.as_ref(),
+        ctx.env.clone(),
+        QueryMsg::ValidateLine {
+            start_fen: Some("not a board".to_string()),
+            moves: vec![],
+        },
+    )
+    .is_err());
+}
+
+#[test]
+fn san_disambiguates_and_marks_checks() {
+    let board: cozy_chess::Board = "n6k/8/8/8/8/8/8/R4RK1 w - - 0 1".parse().unwrap();
+    let san = |uci: &str| to_san(&board, uci.parse().unwrap());
+    assert_eq!("Rad1", san("a1d1"));
+    assert_eq!("Rfe1", san("f1e1"));
+    assert_eq!("Rxa8+", san("a1a8"));
+
+    let board: cozy_chess::Board = "7k/8/8/R7/8/8/8/R5K1 w - - 0 1".parse().unwrap();
+    assert_eq!("R1a3", to_san(&board, "a1a3".parse().unwrap()));
+    assert_eq!("Kg2", to_san(&board, "g1g2".parse().unwrap()));
+}
+
+#[test]
+fn stats_track_careers_and_head_to_head() {
+    let mut ctx = TestContext::new();
+    let (white, black) = (ctx.player_a_no_bet(), ctx.player_b_no_bet());
+    let stake = ctx.bet.amount.u128() as i128;
+    for line in [FOOLS_MATE, SCHOLARS_MATE, SCHOLARS_MATE, STALEMATE] {
+        let match_id = start_match(&mut ctx, false);
+        play_line(&mut ctx, match_id, &white, &black, line);
+    }
+
+    let stats = |ctx: &TestContext, player: &Addr| -> PlayerStatsResponse {
+        let msg = QueryMsg::PlayerStats {
+            player: player.to_string(),
+        };
+        from_json(query(ctx.deps.as_ref(), ctx.env.clone(), msg).unwrap()).unwrap()
+    };
+    let white_stats = stats(&ctx, &white.sender);
+    assert_eq!(4, white_stats.stats.games);
+    assert_eq!(
+        crate::stats::ColorRecord {
+            wins: 2,
+            draws: 1,
+            losses: 1
+        },
+        white_stats.stats.as_white
+    );
+    assert_eq!(
+        crate::stats::ColorRecord::default(),
+        white_stats.stats.as_black
+    );
+    assert_eq!(
+        (0, 2),
+        (
+            white_stats.stats.win_streak,
+            white_stats.stats.longest_win_streak
+        )
+    );
+    // (4 + 7 + 7 + 19) / 4 plies
+    assert_eq!(9, white_stats.average_plies);
+    assert_eq!(
+        vec![crate::stats::Wagers {
+            denom: NATIVE_DENOM.to_string(),
+            wagered: ctx.bet.amount * Uint128::new(4),
+            net_profit: cosmwasm_std::Int128::new(stake),
+        }],
+        white_stats.stats.wagers
+    );
+    let black_stats = stats(&ctx, &black.sender);
+    assert_eq!(2, black_stats.stats.as_black.losses);
+    assert_eq!(
+        cosmwasm_std::Int128::new(-stake),
+        black_stats.stats.wagers[0].net_profit
+    );
+
+    // Records read the same from either side
+    let h2h = |a: &Addr, b: &Addr| -> HeadToHeadResponse {
+        let msg = QueryMsg::HeadToHead {
+            a: a.to_string(),
+            b: b.to_string(),
+        };
+        from_json(query(ctx.deps.as_ref(), ctx.env.clone(), msg).unwrap()).unwrap()
+    };
+    let record = h2h(&black.sender, &white.sender);
+    assert_eq!((1, 2, 1), (record.a_wins, record.b_wins, record.draws));
+    let record = h2h(&white.sender, &black.sender);
+    assert_eq!((2, 1, 1), (record.a_wins, record.b_wins, record.draws));
+
+    // Stakes too large for the signed profit are refused rather than wrapped
+    let mut whale = crate::stats::PlayerStats::default();
+    let stake = Coin::new(u128::MAX, NATIVE_DENOM);
+    assert!(whale
+        .record(Color::White, &MatchResult::WhiteWins, 3, Some(&stake))
+        .is_err());
+}
+
+#[test]
+fn profiles_have_unique_nicknames() {
+    let mut ctx = TestContext::new();
+    let _match_id = start_match(&mut ctx, true);
+    let (info_a, info_b) = (ctx.player_a_no_bet(), ctx.player_b_no_bet());
+    let profile = |nickname: &str| ExecuteMsg::SetProfile {
+        nickname: nickname.to_string(),
+        country: Some("BE".to_string()),
+        avatar_uri: Some(
+            "ipfs://bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi".to_string(),
+        ),
+        bio: None,
+    };
+
+    for (nickname, reason) in [
+        ("ab", InvalidProfileReason::NicknameLength),
+        (
+            "a_very_long_nickname_",
+            InvalidProfileReason::NicknameLength,
+        ),
+        ("9lives", InvalidProfileReason::NicknameCharset),
+        ("magnus carlsen", InvalidProfileReason::NicknameCharset),
+    ] {
+        assert_eq!(
+            ContractError::InvalidProfile { reason },
+            execute(
+                ctx.deps.as_mut(),
+                ctx.env.clone(),
+                info_b.clone(),
+                profile(nickname)
+            )
+            .unwrap_err()
+        );
+    }
+    let msg = ExecuteMsg::SetProfile {
+        nickname: "DrNykterstein".to_string(),
+        country: Some("be".to_string()),
+        avatar_uri: None,
+        bio: None,
+    };
+    assert_eq!(
+        ContractError::InvalidProfile {
+            reason: InvalidProfileReason::Country
+        },
+        execute(ctx.deps.as_mut(), ctx.env.clone(), info_b.clone(), msg).unwrap_err()
+    );
+
+    let _res = execute(
+        ctx.deps.as_mut(),
+        ctx.env.clone(),
+        info_b.clone(),
+        profile("DrNykterstein"),
+    )
+    .unwrap();
+    assert_eq!(
+        ContractError::NicknameTaken {},
+        execute(
+            ctx.deps.as_mut(),
+            ctx.env.clone(),
+            info_a.clone(),
+            profile("drnykterstein")
+        )
+        .unwrap_err()
+    );
+
+    // Nicknames resolve in any case, and can stand in for the opponent's address
+    let resolve = |ctx: &TestContext, nickname: &str| -> Option<Addr> {
+        let msg = QueryMsg::ResolveNickname {
+            nickname: nickname.to_string(),
+        };
+        from_json(query(ctx.deps.as_ref(), ctx.env.clone(), msg).unwrap()).unwrap()
+    };
+    assert_eq!(Some(info_b.sender.clone()), resolve(&ctx, "DRNYKTERSTEIN"));
+    let nonce = NEXT_NONCE.load(ctx.deps.as_ref().storage).unwrap();
+    let create_msg = ExecuteMsg::CreateMatch {
+        opponent: "drNykterstein".to_string(),
+        rated: None,
+        arbiter: None,
+    };
+    let info = ctx.player_a_info_with_bet();
+    let _res = execute(ctx.deps.as_mut(), ctx.env.clone(), info, create_msg).unwrap();
+    let match_id = exec::match_id(&info_a.sender, &info_b.sender, nonce);
+    assert!(MATCHES.has(ctx.deps.as_ref().storage, match_id));
+
+    // Renaming releases the previous nickname
+    let _res = execute(
+        ctx.deps.as_mut(),
+        ctx.env.clone(),
+        info_b.clone(),
+        profile("Magnus"),
+    )
+    .unwrap();
+    assert_eq!(None, resolve(&ctx, "DrNykterstein"));
+    let _res = execute(
+        ctx.deps.as_mut(),
+        ctx.env.clone(),
+        info_a.clone(),
+        profile("DrNykterstein"),
+    )
+    .unwrap();
+    let stored: Option<crate::profile::Profile> = from_json(
+        query(
+            ctx.deps.as_ref(),
+            ctx.env.clone(),
+            QueryMsg::Profile {
+                address: info_b.sender.to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        Some("Magnus".to_string()),
+        stored.map(|profile| profile.nickname)
+    );
+}
+
+#[test]
+fn seasons_rank_rated_results_and_pay_prizes() {
+    let mut ctx = TestContext::new();
+    let (white, black) = (ctx.player_a_no_bet(), ctx.player_b_no_bet());
+    // Instantiates the contract, the match itself is never finished
+    let _match_id = start_match(&mut ctx, true);
+    let pool = Coin::new(100, NATIVE_DENOM);
+    let start = ExecuteMsg::StartSeason {
+        prize_pool: pool.clone(),
+        prize_split: vec![70, 30],
+    };
+    assert_eq!(
+        ContractError::Unauthorized {},
+        execute(
+            ctx.deps.as_mut(),
+            ctx.env.clone(),
+            white.clone(),
+            start.clone()
+        )
+        .unwrap_err()
+    );
+    let admin = mock_info("admin", std::slice::from_ref(&pool));
+    let _res = execute(
+        ctx.deps.as_mut(),
+        ctx.env.clone(),
+        admin.clone(),
+        start.clone(),
+    )
+    .unwrap();
+    assert_eq!(
+        ContractError::SeasonInProgress {},
+        execute(
+            ctx.deps.as_mut(),
+            ctx.env.clone(),
+            admin.clone(),
+            start.clone()
+        )
+        .unwrap_err()
+    );
+
+    // Black wins, white wins twice, and unrated matches don't count
+    let match_id = start_match(&mut ctx, true);
+    play_line(&mut ctx, match_id, &white, &black, FOOLS_MATE);
+    for rated in [true, true, false] {
+        let match_id = start_match(&mut ctx, rated);
+        play_line(&mut ctx, match_id, &white, &black, SCHOLARS_MATE);
+    }
+
+    let leaderboard_page = |ctx: &TestContext, season_id, start_after: Option<&Addr>, limit| {
+        let msg = QueryMsg::SeasonLeaderboard {
+            season_id,
+            start_after: start_after.map(Addr::to_string),
+            limit,
+        };
+        let res: SeasonLeaderboardResponse =
+            from_json(query(ctx.deps.as_ref(), ctx.env.clone(), msg).unwrap()).unwrap();
+        res.standings
+    };
+    let leaderboard = |ctx: &TestContext, season_id| SeasonLeaderboardResponse {
+        standings: leaderboard_page(ctx, season_id, None, None),
+    };
+    let standings = leaderboard(&ctx, 0).standings;
+    assert_eq!(
+        vec![white.sender.clone(), black.sender.clone()],
+        standings
+            .iter()
+            .map(|s| s.player.clone())
+            .collect::<Vec<_>>()
+    );
+    let top = &standings[0];
+    assert_eq!((4, 2, 0, 1), (top.points, top.wins, top.draws, top.losses));
+    assert!(top.rating > crate::rating::DEFAULT_RATING);
+    // The leaderboard pages in rank order
+    assert_eq!(standings[..1], leaderboard_page(&ctx, 0, None, Some(1))[..]);
+    assert_eq!(
+        standings[1..],
+        leaderboard_page(&ctx, 0, Some(&white.sender), Some(1))[..]
+    );
+
+    ctx.env.block.height += 100;
+    let res = execute(
+        ctx.deps.as_mut(),
+        ctx.env.clone(),
+        mock_info("admin", &[]),
+        ExecuteMsg::EndSeason { limit: None },
+    )
+    .unwrap();
+    assert_eq!(
+        vec![
+            SubMsg::new(BankMsg::Send {
+                to_address: white.sender.to_string(),
+                amount: vec![Coin::new(70, NATIVE_DENOM)],
+            }),
+            SubMsg::new(BankMsg::Send {
+                to_address: black.sender.to_string(),
+                amount: vec![Coin::new(30, NATIVE_DENOM)],
+            }),
+        ],
+        res.messages
+    );
+
+    // The next season starts from scratch, the last one stays queryable
+    let _res = execute(ctx.deps.as_mut(), ctx.env.clone(), admin, start).unwrap();
+    let match_id = start_match(&mut ctx, true);
+    play_line(&mut ctx, match_id, &white, &black, FOOLS_MATE);
+    assert_eq!(2, leaderboard(&ctx, 1).standings[0].points);
+    assert_eq!(black.sender, leaderboard(&ctx, 1).standings[0].player);
+    assert_eq!(standings, leaderboard(&ctx, 0).standings);
+
+    // All-time ratings carry over every rated match played so far
+    let mut ratings = (crate::rating::DEFAULT_RATING, crate::rating::DEFAULT_RATING);
+    for result in [
+        MatchResult::BlackWins,
+        MatchResult::WhiteWins,
+        MatchResult::WhiteWins,
+        MatchResult::BlackWins,
+    ] {
+        ratings = crate::rating::update_ratings(ratings.0, ratings.1, &result);
+    }
+    for (player, rating) in [(&white, ratings.0), (&black, ratings.1)] {
+        let msg = QueryMsg::PlayerStats {
+            player: player.sender.to_string(),
+        };
+        let stats: PlayerStatsResponse =
+            from_json(query(ctx.deps.as_ref(), ctx.env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(rating, stats.rating);
+    }
+    assert_ne!(ratings.1, leaderboard(&ctx, 1).standings[0].rating);
+    let season: Season = from_json(
+        query(
+            ctx.deps.as_ref(),
+            ctx.env.clone(),
+            QueryMsg::Season { season_id: 0 },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(Some(ctx.env.block.height), season.end);
+    assert_eq!(2, season.prizes.len());
+
+    // Standings are ranked a page at a time, results stop counting meanwhile
+    let end = ExecuteMsg::EndSeason { limit: Some(1) };
+    let admin = mock_info("admin", &[]);
+    for _ in 0..2 {
+        let res = execute(
+            ctx.deps.as_mut(),
+            ctx.env.clone(),
+            admin.clone(),
+            end.clone(),
+        )
+        .unwrap();
+        assert!(res.messages.is_empty());
+        let match_id = start_match(&mut ctx, true);
+        play_line(&mut ctx, match_id, &white, &black, SCHOLARS_MATE);
+    }
+    assert_eq!(
+        ContractError::SeasonInProgress {},
+        execute(
+            ctx.deps.as_mut(),
+            ctx.env.clone(),
+            mock_info("admin", std::slice::from_ref(&pool)),
+            ExecuteMsg::StartSeason {
+                prize_pool: pool.clone(),
+                prize_split: vec![100],
+            }
+        )
+        .unwrap_err()
+    );
+    let res = execute(ctx.deps.as_mut(), ctx.env.clone(), admin, end).unwrap();
+    assert_eq!(
+        vec![
+            SubMsg::new(BankMsg::Send {
+                to_address: black.sender.to_string(),
+                amount: vec![Coin::new(70, NATIVE_DENOM)],
+            }),
+            SubMsg::new(BankMsg::Send {
+                to_address: white.sender.to_string(),
+                amount: vec![Coin::new(30, NATIVE_DENOM)],
+            }),
+        ],
+        res.messages
+    );
+    assert!(!SEASON_CLOSE.exists(ctx.deps.as_ref().storage));
+}
+
+#[test]
+fn season_close_keeps_only_prize_contenders() {
+    let standing = |player: &str, points, rating| SeasonStanding {
+        player: Addr::unchecked(player),
+        points,
+        rating,
+        wins: 0,
+        draws: 0,
+        losses: 0,
+    };
+    let standings = vec![
+        standing("a", 2, 1216),
+        standing("b", 4, 1230),
+        standing("c", 2, 1216),
+        standing("d", 0, 1184),
+    ];
+    let players = |standings: Vec<SeasonStanding>| -> Vec<String> {
+        standings
+            .into_iter()
+            .map(|standing| standing.player.to_string())
+            .collect()
+    };
+    assert_eq!(vec!["b"], players(keep_leaders(standings.clone(), 1)));
+    // Players tied for the last paid place all stay in contention
+    assert_eq!(vec!["b", "a", "c"], players(keep_leaders(standings, 2)));
+}
+
+// White mates in two with 1. Kb6 Kb8 2. Rh8#
+const MATE_IN_TWO: &str = "k7/8/2K5/8/8/8/8/7R w - - 0 1";
+
+#[test]
+fn mate_search_checks_every_defence() {
+    let board: cozy_chess::Board = MATE_IN_TWO.parse().unwrap();
+    let key = |mv: &str| mv.parse().unwrap();
+    let search = |mv, mate_in| key_move_forces_mate(&board, key(mv), mate_in, MAX_SEARCH_NODES);
+    assert_eq!(Ok(true), search("c6b6", 2));
+    assert_eq!(Ok(false), search("c6b6", 1));
+    // 1. Rh8+ Ka7 escapes
+    assert_eq!(Ok(false), search("h1h8", 2));
+
+    // Mate in two needs every reply to 1... Kb8 searched
+    assert_eq!(
+        Err(SearchLimitExceeded),
+        key_move_forces_mate(&board, key("c6b6"), 2, 3)
+    );
+}
+
+#[test]
+fn puzzles_pay_first_committed_solver() {
+    let mut ctx = TestContext::new();
+    let info = ctx.admin_info();
+    let msg = InstantiateMsg {
+        min_bet: Coin::new(10, NATIVE_DENOM),
+    };
+    instantiate(ctx.deps.as_mut(), ctx.env.clone(), info, msg).unwrap();
+
+    let reward = Coin::new(50, NATIVE_DENOM);
+    let post = |mate_in| ExecuteMsg::PostPuzzle {
+        fen: MATE_IN_TWO.to_string(),
+        mate_in,
+        reward: reward.clone(),
+    };
+    let poster = mock_info("admin", std::slice::from_ref(&reward));
+    assert_eq!(
+        ContractError::InvalidPuzzle {
+            reason: InvalidPuzzleReason::MateInOutOfRange
+        },
+        execute(ctx.deps.as_mut(), ctx.env.clone(), poster.clone(), post(4)).unwrap_err()
+    );
+    let _res = execute(ctx.deps.as_mut(), ctx.env.clone(), poster.clone(), post(2)).unwrap();
+
+    let (solver, copycat) = (ctx.player_a_no_bet(), ctx.player_b_no_bet());
+    let line: Vec<String> = ["c6b6", "a8b8", "h1h8"].map(str::to_string).to_vec();
+    let solve = |salt: &str| ExecuteMsg::SolvePuzzle {
+        puzzle_id: 0,
+        line: line.clone(),
+        salt: salt.to_string(),
+    };
+    let commit = ExecuteMsg::CommitSolution {
+        puzzle_id: 0,
+        commitment: solution_commitment(&solver.sender, 0, &line, "s3cret"),
+    };
+    let _res = execute(ctx.deps.as_mut(), ctx.env.clone(), solver.clone(), commit).unwrap();
+
+    // Reveals must come after the commitment's block
+    assert_eq!(
+        ContractError::NoSolutionCommitment {},
+        execute(
+            ctx.deps.as_mut(),
+            ctx.env.clone(),
+            solver.clone(),
+            solve("s3cret")
+        )
+        .unwrap_err()
+    );
+    ctx.env.block.height += 1;
+    assert_eq!(
+        ContractError::CommitmentMismatch {},
+        execute(
+            ctx.deps.as_mut(),
+            ctx.env.clone(),
+            solver.clone(),
+            solve("guess")
+        )
+        .unwrap_err()
+    );
+    // Copying a pending reveal is useless without an earlier commitment
+    let commit = ExecuteMsg::CommitSolution {
+        puzzle_id: 0,
+        commitment: solution_commitment(&copycat.sender, 0, &line, "copy"),
+    };
+    let _res = execute(ctx.deps.as_mut(), ctx.env.clone(), copycat.clone(), commit).unwrap();
+    assert_eq!(
+        ContractError::NoSolutionCommitment {},
+        execute(
+            ctx.deps.as_mut(),
+            ctx.env.clone(),
+            copycat.clone(),
+            solve("s3cret")
+        )
+        .unwrap_err()
+    );
+
+    let res = execute(
+        ctx.deps.as_mut(),
+        ctx.env.clone(),
+        solver.clone(),
+        solve("s3cret"),
+    )
+    .unwrap();
+    assert_eq!(
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: solver.sender.to_string(),
+            amount: vec![reward.clone()],
+        })],
+        res.messages
+    );
+    let puzzle: Puzzle = from_json(
+        query(
+            ctx.deps.as_ref(),
+            ctx.env.clone(),
+            QueryMsg::Puzzle { puzzle_id: 0 },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(Some(solver.sender.clone()), puzzle.solver);
+    assert!(!SOLUTION_COMMITMENTS.has(ctx.deps.as_ref().storage, (0, &copycat.sender)));
+    assert_eq!(
+        ContractError::PuzzleAlreadySolved {},
+        execute(
+            ctx.deps.as_mut(),
+            ctx.env.clone(),
+            poster.clone(),
+            ExecuteMsg::WithdrawPuzzle { puzzle_id: 0 }
+        )
+        .unwrap_err()
+    );
+
+    // A line that mates only because the defence blundered is no solution
+    let _res = execute(ctx.deps.as_mut(), ctx.env.clone(), poster.clone(), post(2)).unwrap();
+    let blunder: Vec<String> = ["h1h7", "a8b8", "h7h8"].map(str::to_string).to_vec();
+    let commit = ExecuteMsg::CommitSolution {
+        puzzle_id: 1,
+        commitment: solution_commitment(&copycat.sender, 1, &blunder, ""),
+    };
+    let _res = execute(ctx.deps.as_mut(), ctx.env.clone(), copycat.clone(), commit).unwrap();
+    ctx.env.block.height += 1;
+    let reveal = ExecuteMsg::SolvePuzzle {
+        puzzle_id: 1,
+        line: blunder,
+        salt: String::new(),
+    };
+    assert_eq!(
+        ContractError::IncorrectSolution {},
+        execute(ctx.deps.as_mut(), ctx.env.clone(), copycat.clone(), reveal).unwrap_err()
+    );
+
+    // Unsolved puzzles are refunded to their poster only
+    let withdraw = ExecuteMsg::WithdrawPuzzle { puzzle_id: 1 };
+    assert_eq!(
+        ContractError::NotPuzzlePoster {},
+        execute(
+            ctx.deps.as_mut(),
+            ctx.env.clone(),
+            copycat.clone(),
+            withdraw.clone()
+        )
+        .unwrap_err()
+    );
+    // The poster can't pull the reward from under a pending reveal, but
+    // closes the puzzle to new commitments until the pending ones lapse
+    let res = execute(
+        ctx.deps.as_mut(),
+        ctx.env.clone(),
+        poster.clone(),
+        withdraw.clone(),
+    )
+    .unwrap();
+    assert!(res.messages.is_empty());
+    assert_eq!(
+        Event::new("puzzle_withdrawal_requested")
+            .add_attribute("puzzle_id", "1")
+            .add_attribute(
+                "withdrawable",
+                (ctx.env.block.height + REVEAL_WINDOW + 1).to_string()
+            ),
+        res.events[0]
+    );
+    assert_eq!(
+        ContractError::PendingSolutions {},
+        execute(
+            ctx.deps.as_mut(),
+            ctx.env.clone(),
+            poster.clone(),
+            withdraw.clone()
+        )
+        .unwrap_err()
+    );
+    // A griefer can't keep the withdrawal pending by committing again
+    let griefer = mock_info("griefer", &[]);
+    for _ in 0..3 {
+        ctx.env.block.height += REVEAL_WINDOW / 3;
+        let commit = ExecuteMsg::CommitSolution {
+            puzzle_id: 1,
+            commitment: "00".repeat(32),
+        };
+        assert_eq!(
+            ContractError::PuzzleWithdrawing {},
+            execute(ctx.deps.as_mut(), ctx.env.clone(), griefer.clone(), commit).unwrap_err()
+        );
+    }
+    ctx.env.block.height += REVEAL_WINDOW % 3;
+    let res = execute(ctx.deps.as_mut(), ctx.env.clone(), poster, withdraw).unwrap();
+    assert_eq!(
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: "admin".to_string(),
+            amount: vec![reward],
+        })],
+        res.messages
+    );
+    assert!(!PUZZLES.has(ctx.deps.as_ref().storage, 1));
+    assert!(!SOLUTION_COMMITMENTS.has(ctx.deps.as_ref().storage, (1, &copycat.sender)));
+}
+
+#[test]
+fn openings_are_classified_as_played() {
+    // Every line of the table is legal and reachable by `play_move`
+    for (eco, name, line) in ECO_TABLE {
+        let moves: Vec<&str> = line.split(' ').collect();
+        assert!(moves.len() <= MAX_OPENING_PLIES, "{eco} {name}");
+        let mut chess_match = Match::new(
+            Addr::unchecked("white"),
+            Addr::unchecked("black"),
+            0,
+            Coin::default(),
+        );
+        let history: Vec<PlayedMove> = moves
+            .iter()
+            .map(|move_fen| {
+                let mov = Match::decode_move(move_fen).unwrap();
+                chess_match.play_move(&mov, 0).unwrap()
+            })
+            .collect();
+        chess_match.classify_opening(&history);
+        let opening = chess_match.opening.unwrap();
+        assert_eq!((*eco, *name), (opening.eco.as_str(), opening.name.as_str()));
+    }
+    assert_eq!(None, classify(["a2a3"]));
+
+    const NAJDORF: &[&str] = &[
+        "e2e4", "c7c5", "g1f3", "d7d6", "d2d4", "c5d4", "f3d4", "g8f6", "b1c3", "a7a6", "c1e3",
+        "e7e5",
+    ];
+    let mut ctx = TestContext::new();
+    let match_id = start_match(&mut ctx, false);
+    let (white, black) = (ctx.player_a_no_bet(), ctx.player_b_no_bet());
+    let _res = play_line(&mut ctx, match_id, &white, &black, NAJDORF);
+
+    // Leaving the book keeps the deepest opening reached
+    let najdorf = Some(Opening {
+        eco: "B90".to_string(),
+        name: "Sicilian Defence: Najdorf Variation".to_string(),
+    });
+    let res: MatchResponse = from_json(
+        query(
+            ctx.deps.as_ref(),
+            ctx.env.clone(),
+            QueryMsg::Match {
+                match_id: hex::encode(match_id),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(najdorf, res.chess_match.opening);
+
+    let mut taken_back = res.chess_match.clone();
+    taken_back
+        .take_back(&res.moves[..res.moves.len() - 3])
+        .unwrap();
+    assert_eq!(
+        Some("B56".to_string()),
+        taken_back.opening.map(|opening| opening.eco)
+    );
+
+    let resign_msg = ExecuteMsg::Resign {
+        match_id: hex::encode(match_id),
+    };
+    let res = execute(ctx.deps.as_mut(), ctx.env.clone(), black, resign_msg).unwrap();
+    let event = res.events.iter().find(|e| e.ty == "match_won").unwrap();
+    let attribute = |key: &str| {
+        event
+            .attributes
+            .iter()
+            .find(|attr| attr.key == key)
+            .map(|attr| attr.value.as_str())
+    };
+    assert_eq!(Some("B90"), attribute("eco"));
+    assert_eq!(
+        Some("Sicilian Defence: Najdorf Variation"),
+        attribute("opening")
+    );
+}
+
+#[test]
+fn challenge_policies_reject_unwanted_challenges() {
+    let mut ctx = TestContext::new();
+    let _match_id = start_match(&mut ctx, true);
+    let (challenger, opponent) = (ctx.player_a_addr.clone(), ctx.player_b_info_with_bet());
+    let friend = Addr::unchecked("neutron1qnk2n4nlkpw9xfqntladh74w6ujtulwnmxnh3k");
+    let accepts_all = ChallengePolicy::default();
+    let create_msg = ExecuteMsg::CreateMatch {
+        opponent: ctx.player_b_addr.to_string(),
+        rated: None,
+        arbiter: None,
+    };
+
+    for (policy, reason) in [
+        (
+            ChallengePolicy {
+                blocked: vec![challenger.clone()],
+                ..accepts_all.clone()
+            },
+            ChallengeRejectedReason::Blocked,
+        ),
+        (
+            ChallengePolicy {
+                allow_only: Some(vec![friend.clone()]),
+                ..accepts_all.clone()
+            },
+            ChallengeRejectedReason::NotAllowed,
+        ),
+        (
+            ChallengePolicy {
+                min_rating: Some(1300),
+                ..accepts_all.clone()
+            },
+            ChallengeRejectedReason::RatingTooLow,
+        ),
+        (
+            ChallengePolicy {
+                min_bet: Some(Uint128::new(11)),
+                ..accepts_all.clone()
+            },
+            ChallengeRejectedReason::BetTooLow,
+        ),
+        (
+            ChallengePolicy {
+                max_bet: Some(Uint128::new(9)),
+                ..accepts_all.clone()
+            },
+            ChallengeRejectedReason::BetTooHigh,
+        ),
+    ] {
+        let msg = ExecuteMsg::SetChallengePolicy {
+            blocked: policy.blocked,
+            allow_only: policy.allow_only,
+            min_rating: policy.min_rating,
+            min_bet: policy.min_bet,
+            max_bet: policy.max_bet,
+        };
+        let _res = execute(ctx.deps.as_mut(), ctx.env.clone(), opponent.clone(), msg).unwrap();
+        let info = ctx.player_a_info_with_bet();
+        assert_eq!(
+            ContractError::ChallengeRejected { reason },
+            execute(ctx.deps.as_mut(), ctx.env.clone(), info, create_msg.clone()).unwrap_err()
+        );
+    }
+
+    let policy = ChallengePolicy {
+        blocked: vec![friend.clone()],
+        allow_only: None,
+        min_rating: Some(1200),
+        min_bet: Some(Uint128::new(10)),
+        max_bet: Some(Uint128::new(10)),
+    };
+    let msg = ExecuteMsg::SetChallengePolicy {
+        blocked: policy.blocked.clone(),
+        allow_only: None,
+        min_rating: policy.min_rating,
+        min_bet: policy.min_bet,
+        max_bet: policy.max_bet,
+    };
+    let _res = execute(ctx.deps.as_mut(), ctx.env.clone(), opponent.clone(), msg).unwrap();
+    let query_msg = QueryMsg::ChallengePolicy {
+        player: ctx.player_b_addr.to_string(),
+    };
+    let stored: ChallengePolicy =
+        from_json(query(ctx.deps.as_ref(), ctx.env.clone(), query_msg.clone()).unwrap()).unwrap();
+    assert_eq!(policy, stored);
+    let info = ctx.player_a_info_with_bet();
+    let _res = execute(ctx.deps.as_mut(), ctx.env.clone(), info, create_msg).unwrap();
+
+    // Setting the default policy drops it
+    let msg = ExecuteMsg::SetChallengePolicy {
+        blocked: vec![],
+        allow_only: None,
+        min_rating: None,
+        min_bet: None,
+        max_bet: None,
+    };
+    let _res = execute(ctx.deps.as_mut(), ctx.env.clone(), opponent, msg).unwrap();
+    assert!(!CHALLENGE_POLICIES.has(ctx.deps.as_ref().storage, &ctx.player_b_addr));
+    let stored: ChallengePolicy =
+        from_json(query(ctx.deps.as_ref(), ctx.env.clone(), query_msg).unwrap()).unwrap();
+    assert_eq!(accepts_all, stored);
+}
+
+#[test]
+fn vote_matches_play_the_plurality_move() {
+    let mut ctx = TestContext::new();
+    let _match_id = start_match(&mut ctx, false);
+    let champion = ctx.player_a_no_bet();
+    let voters: Vec<MessageInfo> = ["voter1", "voter2", "voter3"]
+        .map(|voter| mock_info(voter, &[]))
+        .to_vec();
+    let create = |voters: Vec<Addr>| ExecuteMsg::CreateVoteMatch {
+        voters,
+        voters_color: Color::Black,
+        voting_period: 10,
+    };
+
+    for invalid in [vec![], vec![champion.sender.clone()]] {
+        assert_eq!(
+            ContractError::InvalidVoteGroup {},
+            execute(
+                ctx.deps.as_mut(),
+                ctx.env.clone(),
+                champion.clone(),
+                create(invalid)
+            )
+            .unwrap_err()
+        );
+    }
+    let res = execute(
+        ctx.deps.as_mut(),
+        ctx.env.clone(),
+        champion.clone(),
+        create(voters.iter().map(|info| info.sender.clone()).collect()),
+    )
+    .unwrap();
+    let match_id = res.events[0].attributes[1].value.clone();
+    let match_bytes: [u8; 32] = hex::decode(&match_id).unwrap().try_into().unwrap();
+
+    // Voters play a side, so they can't bet on the match
+    let side_bet_msg = ExecuteMsg::PlaceSideBet {
+        match_id: match_id.clone(),
+        outcome: MatchResult::Draw,
+    };
+    let info = mock_info(voters[0].sender.as_str(), &[Coin::new(10, NATIVE_DENOM)]);
+    assert_eq!(
+        ContractError::PlayersCannotSideBet {},
+        execute(ctx.deps.as_mut(), ctx.env.clone(), info, side_bet_msg).unwrap_err()
+    );
+
+    let vote = |match_id: &str, move_fen: &str| ExecuteMsg::VoteMove {
+        match_id: match_id.to_string(),
+        move_fen: move_fen.to_string(),
+    };
+    let finalize = ExecuteMsg::FinalizeVote {
+        match_id: match_id.clone(),
+    };
+    let cast = |ctx: &mut TestContext, voter: &MessageInfo, move_fen: &str| {
+        execute(
+            ctx.deps.as_mut(),
+            ctx.env.clone(),
+            voter.clone(),
+            vote(&match_id, move_fen),
+        )
+    };
+
+    assert_eq!(
+        ContractError::NotYourTurn {},
+        cast(&mut ctx, &voters[0], "e7e5").unwrap_err()
+    );
+    let _res = play_move(&mut ctx, champion.clone(), match_bytes, "e2e4").unwrap();
+    let outsider = ctx.player_b_no_bet();
+    assert_eq!(
+        ContractError::NotAVoter {},
+        cast(&mut ctx, &outsider, "e7e5").unwrap_err()
+    );
+    assert_eq!(
+        ContractError::IllegalMove {},
+        cast(&mut ctx, &voters[0], "e7e4").unwrap_err()
+    );
+    let _res = cast(&mut ctx, &voters[0], "e7e5").unwrap();
+    let _res = cast(&mut ctx, &voters[1], "c7c5").unwrap();
+    let _res = cast(&mut ctx, &voters[2], "c7c5").unwrap();
+    // Voters can change their mind while voting is open
+    let _res = cast(&mut ctx, &voters[2], "e7e5").unwrap();
+
+    let votes: VotesResponse = from_json(
+        query(
+            ctx.deps.as_ref(),
+            ctx.env.clone(),
+            QueryMsg::Votes {
+                match_id: match_id.clone(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        VotesResponse {
+            voters_color: Color::Black,
+            voting_ends: ctx.env.block.height + 10,
+            tally: vec![("e7e5".to_string(), 2), ("c7c5".to_string(), 1)],
+        },
+        votes
+    );
+    assert_eq!(
+        ContractError::VotingStillOpen {},
+        execute(
+            ctx.deps.as_mut(),
+            ctx.env.clone(),
+            outsider.clone(),
+            finalize.clone()
+        )
+        .unwrap_err()
+    );
+
+    ctx.env.block.height += 10;
+    assert_eq!(
+        ContractError::VotingClosed {},
+        cast(&mut ctx, &voters[1], "d7d5").unwrap_err()
+    );
+    let res = execute(
+        ctx.deps.as_mut(),
+        ctx.env.clone(),
+        outsider.clone(),
+        finalize.clone(),
+    )
+    .unwrap();
+    assert_eq!(
+        Event::new("vote_finalized")
+            .add_attribute("match_id", &match_id)
+            .add_attribute("move", "e7e5")
+            .add_attribute("votes", "2"),
+        res.events[0]
+    );
+    let played = MATCH_MOVES
+        .load(ctx.deps.as_ref().storage, (match_bytes.as_slice(), 1))
+        .unwrap();
+    assert_eq!("e7e5", played.mov);
+
+    // Ties go to the first move in UCI order
+    let _res = play_move(&mut ctx, champion.clone(), match_bytes, "g1f3").unwrap();
+    let _res = cast(&mut ctx, &voters[0], "g8f6").unwrap();
+    let _res = cast(&mut ctx, &voters[1], "b8c6").unwrap();
+    ctx.env.block.height += 10;
+    let _res = execute(
+        ctx.deps.as_mut(),
+        ctx.env.clone(),
+        outsider.clone(),
+        finalize.clone(),
+    )
+    .unwrap();
+    let played = MATCH_MOVES
+        .load(ctx.deps.as_ref().storage, (match_bytes.as_slice(), 3))
+        .unwrap();
+    assert_eq!("b8c6", played.mov);
+
+    // A group that doesn't vote loses, vote matches are unstaked
+    let _res = play_move(&mut ctx, champion.clone(), match_bytes, "f1b5").unwrap();
+    ctx.env.block.height += 10;
+    let res = execute(ctx.deps.as_mut(), ctx.env.clone(), outsider, finalize).unwrap();
+    assert!(res.messages.is_empty());
+    let won = &res.events[0];
+    assert_eq!(champion.sender.as_str(), won.attributes[1].value);
+    assert_eq!(Attribute::new("reason", "no_votes"), won.attributes[3]);
+    assert!(!VOTE_GROUPS.has(ctx.deps.as_ref().storage, match_bytes));
+
+    // Only the champion has stats, the contract stands in for the voters
+    let storage = ctx.deps.as_ref().storage;
+    let stats = PLAYER_STATS.load(storage, &champion.sender).unwrap();
+    assert_eq!((1, 1), (stats.games, stats.as_white.wins));
+    assert!(!PLAYER_STATS.has(storage, &ctx.env.contract.address));
+    assert!(HEAD_TO_HEAD.is_empty(storage));
+
+    // The voters can't be offered a rematch
+    let offer_msg = ExecuteMsg::OfferRematch {
+        match_id: match_id.clone(),
+    };
+    assert_eq!(
+        ContractError::UnknownMatch {},
+        execute(ctx.deps.as_mut(), ctx.env.clone(), champion, offer_msg).unwrap_err()
+    );
+}
+
+#[test]
+fn team_matches_settle_stakes_when_the_last_board_finishes() {
+    let mut ctx = TestContext::new();
+    let _match_id = start_match(&mut ctx, false);
+    let [a1, a2, b1, b2] = ["club_a1", "club_a2", "club_b1", "club_b2"].map(|p| mock_info(p, &[]));
+    let stake = Coin::new(50, NATIVE_DENOM);
+    let create = |team_a: &[&MessageInfo], team_b: &[&MessageInfo], boards: u32| {
+        ExecuteMsg::CreateTeamMatch {
+            team_a: team_a.iter().map(|info| info.sender.clone()).collect(),
+            team_b: team_b.iter().map(|info| info.sender.clone()).collect(),
+            boards,
+            stake: stake.clone(),
+            tiebreak: TeamTiebreak::TopBoard,
+        }
+    };
+    let captain_a = mock_info(a1.sender.as_str(), std::slice::from_ref(&stake));
+
+    for (msg, reason) in [
+        (
+            create(&[&a1], &[&b1], 0),
+            InvalidTeamMatchReason::BoardCount,
+        ),
+        (
+            create(&[&a1, &a2], &[&b1], 2),
+            InvalidTeamMatchReason::TeamTooSmall,
+        ),
+        (
+            create(&[&a1, &a2], &[&b1, &a2], 2),
+            InvalidTeamMatchReason::DuplicatePlayer,
+        ),
+    ] {
+        assert_eq!(
+            ContractError::InvalidTeamMatch { reason },
+            execute(ctx.deps.as_mut(), ctx.env.clone(), captain_a.clone(), msg).unwrap_err()
+        );
+    }
+    let msg = create(&[&a1, &a2], &[&b1, &b2], 2);
+    assert_eq!(
+        ContractError::Unauthorized {},
+        execute(
+            ctx.deps.as_mut(),
+            ctx.env.clone(),
+            mock_info(b1.sender.as_str(), std::slice::from_ref(&stake)),
+            msg.clone()
+        )
+        .unwrap_err()
+    );
+    let _res = execute(ctx.deps.as_mut(), ctx.env.clone(), captain_a.clone(), msg).unwrap();
+
+    // Board players are challenged by their board opponent
+    let captain_b = mock_info(b2.sender.as_str(), std::slice::from_ref(&stake));
+    let accept = ExecuteMsg::AcceptTeamMatch { team_match_id: 0 };
+    let policy = |blocked: Vec<Addr>| ExecuteMsg::SetChallengePolicy {
+        blocked,
+        allow_only: None,
+        min_rating: None,
+        min_bet: None,
+        max_bet: None,
+    };
+    let msg = policy(vec![a1.sender.clone()]);
+    let _res = execute(ctx.deps.as_mut(), ctx.env.clone(), b1.clone(), msg).unwrap();
+    assert_eq!(
+        ContractError::ChallengeRejected {
+            reason: ChallengeRejectedReason::Blocked,
+        },
+        execute(
+            ctx.deps.as_mut(),
+            ctx.env.clone(),
+            captain_b.clone(),
+            accept.clone()
+        )
+        .unwrap_err()
+    );
+    let _res = execute(
+        ctx.deps.as_mut(),
+        ctx.env.clone(),
+        b1.clone(),
+        policy(vec![]),
+    )
+    .unwrap();
+    let res = execute(
+        ctx.deps.as_mut(),
+        ctx.env.clone(),
+        captain_b.clone(),
+        accept,
+    )
+    .unwrap();
+    let board_match = |board: usize| -> [u8; 32] {
+        hex::decode(&res.events[0].attributes[board].value)
+            .unwrap()
+            .try_into()
+            .unwrap()
+    };
+    let (board_1, board_2) = (board_match(1), board_match(2));
+
+    // Boards start once every board player confirmed, captains did by accepting
+    let confirm = ExecuteMsg::ConfirmTeamBoard { team_match_id: 0 };
+    let join = ExecuteMsg::JoinMatch {
+        match_id: hex::encode(board_1),
+        expected_arbiter: None,
+    };
+    assert_eq!(
+        ContractError::Unauthorized {},
+        execute(ctx.deps.as_mut(), ctx.env.clone(), b1.clone(), join).unwrap_err()
+    );
+    let outsider = ctx.player_b_no_bet();
+    assert_eq!(
+        ContractError::Unauthorized {},
+        execute(
+            ctx.deps.as_mut(),
+            ctx.env.clone(),
+            outsider,
+            confirm.clone()
+        )
+        .unwrap_err()
+    );
+    let res = execute(
+        ctx.deps.as_mut(),
+        ctx.env.clone(),
+        b1.clone(),
+        confirm.clone(),
+    )
+    .unwrap();
+    assert_eq!(1, res.events.len());
+    let res = execute(ctx.deps.as_mut(), ctx.env.clone(), a2.clone(), confirm).unwrap();
+    assert_eq!("team_match_started", res.events[1].ty);
+
+    // Colours alternate, team A has white on board 1
+    let res = play_line(&mut ctx, board_1, &a1, &b1, FOOLS_MATE);
+    assert!(res.messages.is_empty());
+    let event = res
+        .events
+        .iter()
+        .find(|event| event.ty == "team_board_finished")
+        .unwrap();
+    assert_eq!(
+        &Event::new("team_board_finished")
+            .add_attribute("team_match_id", "0")
+            .add_attribute("board", "1")
+            .add_attribute("score_a", "0")
+            .add_attribute("score_b", "2"),
+        event
+    );
+
+    // Points are even, team B won the top board
+    let res = play_line(&mut ctx, board_2, &b2, &a2, FOOLS_MATE);
+    assert_eq!(
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: b2.sender.to_string(),
+            amount: vec![Coin::new(100, NATIVE_DENOM)],
+        })],
+        res.messages
+    );
+    let team_match: TeamMatch = from_json(
+        query(
+            ctx.deps.as_ref(),
+            ctx.env.clone(),
+            QueryMsg::TeamMatch { team_match_id: 0 },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(TeamMatchState::Finished, team_match.state);
+    assert_eq!((2, 2), (team_match.score_a, team_match.score_b));
+    // Boards are only played as part of their team match
+    let offer_msg = ExecuteMsg::OfferRematch {
+        match_id: hex::encode(board_2),
+    };
+    assert_eq!(
+        ContractError::UnknownMatch {},
+        execute(ctx.deps.as_mut(), ctx.env.clone(), b2.clone(), offer_msg).unwrap_err()
+    );
+    assert_eq!(Some(Team::B), team_match.winner());
+    let split = TeamMatch {
+        tiebreak: TeamTiebreak::Split,
+        ..team_match
+    };
+    assert_eq!(None, split.winner());
+
+    // Team A's captain can withdraw until team B accepts
+    let msg = create(&[&a1, &a2], &[&b1, &b2], 1);
+    let _res = execute(ctx.deps.as_mut(), ctx.env.clone(), captain_a.clone(), msg).unwrap();
+    let abort = ExecuteMsg::AbortTeamMatch { team_match_id: 1 };
+    assert_eq!(
+        ContractError::Unauthorized {},
+        execute(
+            ctx.deps.as_mut(),
+            ctx.env.clone(),
+            a2.clone(),
+            abort.clone()
+        )
+        .unwrap_err()
+    );
+    let res = execute(ctx.deps.as_mut(), ctx.env.clone(), a1.clone(), abort).unwrap();
+    assert_eq!(
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: "club_a1".to_string(),
+            amount: vec![stake.clone()],
+        })],
+        res.messages
+    );
+    let refunds = vec![
+        SubMsg::new(BankMsg::Send {
+            to_address: "club_a1".to_string(),
+            amount: vec![stake.clone()],
+        }),
+        SubMsg::new(BankMsg::Send {
+            to_address: "club_b2".to_string(),
+            amount: vec![stake.clone()],
+        }),
+    ];
+
+    // Either captain can withdraw until the boards start
+    for team_match_id in [2, 3] {
+        let msg = create(&[&a1, &a2], &[&b1, &b2], 2);
+        let _res = execute(ctx.deps.as_mut(), ctx.env.clone(), captain_a.clone(), msg).unwrap();
+        let accept = ExecuteMsg::AcceptTeamMatch { team_match_id };
+        let _res = execute(
+            ctx.deps.as_mut(),
+            ctx.env.clone(),
+            captain_b.clone(),
+            accept,
+        )
+        .unwrap();
+    }
+    let abort = ExecuteMsg::AbortTeamMatch { team_match_id: 2 };
+    let res = execute(ctx.deps.as_mut(), ctx.env.clone(), b2.clone(), abort).unwrap();
+    assert_eq!(refunds, res.messages);
+    assert!(!TEAM_MATCHES.has(ctx.deps.as_ref().storage, 2));
+
+    // A stalled board is voided with its team match
+    let confirm = ExecuteMsg::ConfirmTeamBoard { team_match_id: 3 };
+    for player in [&b1, &a2] {
+        let _res = execute(
+            ctx.deps.as_mut(),
+            ctx.env.clone(),
+            player.clone(),
+            confirm.clone(),
+        )
+        .unwrap();
+    }
+    let team_match: TeamMatch = from_json(
+        query(
+            ctx.deps.as_ref(),
+            ctx.env.clone(),
+            QueryMsg::TeamMatch { team_match_id: 3 },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(TeamMatchState::OnGoing, team_match.state);
+    let board_1: [u8; 32] = hex::decode(&team_match.board_matches[0].match_id)
+        .unwrap()
+        .try_into()
+        .unwrap();
+    let _res = play_move(&mut ctx, a1, board_1, "e2e4").unwrap();
+    let void = ExecuteMsg::Adjudicate {
+        match_id: team_match.board_matches[1].match_id.clone(),
+        result: Adjudication::Void,
+        reason: "board 2 abandoned".to_string(),
+    };
+    let admin_info = ctx.admin_info();
+    let res = execute(ctx.deps.as_mut(), ctx.env.clone(), admin_info, void).unwrap();
+    assert_eq!(refunds, res.messages);
+    assert_eq!("team_match_voided", res.events[1].ty);
+    let storage = ctx.deps.as_ref().storage;
+    assert!(!TEAM_MATCHES.has(storage, 3));
+    assert!(!MATCHES.has(storage, board_1));
+    assert!(TEAM_BOARDS.is_empty(storage));
+}
+
+#[test]
+fn cross_chain_matches_relay_moves_and_settle_escrows() {
+    // `ctx` hosts the match, `peer` runs on the opponent's chain
+    let mut ctx = TestContext::new();
+    let mut peer = TestContext::new();
+    let _match_id = start_match(&mut ctx, false);
+    let _match_id = start_match(&mut peer, false);
+    let channel_id = "channel-0";
+
+    // Channels are only opened to peers the admin allowed
+    let msg = mock_ibc_channel_open_try(channel_id, IBC_ORDER, IBC_VERSION);
+    assert_eq!(
+        ContractError::InvalidIbcChannel {
+            reason: InvalidIbcChannelReason::UnknownPeer,
+        },
+        ibc_channel_open(ctx.deps.as_mut(), ctx.env.clone(), msg).unwrap_err()
+    );
+    let add_peer = ExecuteMsg::AddIbcPeer {
+        connection_id: "connection-2".to_string(),
+        port_id: "their_port".to_string(),
+    };
+    let info = ctx.player_a_no_bet();
+    assert_eq!(
+        ContractError::Unauthorized {},
+        execute(ctx.deps.as_mut(), ctx.env.clone(), info, add_peer.clone()).unwrap_err()
+    );
+    for chain in [&mut ctx, &mut peer] {
+        let admin_info = chain.admin_info();
+        let _res = execute(
+            chain.deps.as_mut(),
+            chain.env.clone(),
+            admin_info,
+            add_peer.clone(),
+        )
+        .unwrap();
+    }
+
+    for (order, version, reason) in [
+        (
+            IbcOrder::Ordered,
+            IBC_VERSION,
+            InvalidIbcChannelReason::Order,
+        ),
+        (
+            IbcOrder::Unordered,
+            "ics20-1",
+            InvalidIbcChannelReason::Version,
+        ),
+    ] {
+        let msg = mock_ibc_channel_open_init(channel_id, order, version);
+        assert_eq!(
+            ContractError::InvalidIbcChannel { reason },
+            ibc_channel_open(ctx.deps.as_mut(), ctx.env.clone(), msg).unwrap_err()
+        );
+    }
+    for chain in [&mut ctx, &mut peer] {
+        let msg = mock_ibc_channel_open_try(channel_id, IBC_ORDER, IBC_VERSION);
+        ibc_channel_open(chain.deps.as_mut(), chain.env.clone(), msg).unwrap();
+        let msg = mock_ibc_channel_connect_ack(channel_id, IBC_ORDER, IBC_VERSION);
+        let _res = ibc_channel_connect(chain.deps.as_mut(), chain.env.clone(), msg).unwrap();
+    }
+
+    let remote = mock_info("cosmos1rdkyt7xghf9w73v8s0dlvjqq03mhsv607la4tl", &[]);
+    let stake = Coin::new(25, "uatom");
+    let create_paid_to = |channel_id: &str, payout_address: &str| ExecuteMsg::CreateIbcMatch {
+        channel_id: channel_id.to_string(),
+        opponent: remote.sender.to_string(),
+        opponent_bet: stake.clone(),
+        payout_address: payout_address.to_string(),
+    };
+    let create = |channel_id: &str| {
+        create_paid_to(channel_id, "cosmos1uh2xazfgdn49al5gwardy20uwav3hmz2ul3pru")
+    };
+    let challenger = ctx.player_a_info_with_bet();
+    // Addresses on the peer chain must at least be well-formed bech32
+    for payout_address in [
+        "peer_challenger",
+        "cosmos1uh2xazfgdn49al5gwardy20uwav3hmz2ul3prv",
+        "COSMOS1UH2XAZFGDN49AL5GWARDY20UWAV3HMZ2UL3PRU",
+    ] {
+        assert_eq!(
+            ContractError::InvalidAddress {},
+            execute(
+                ctx.deps.as_mut(),
+                ctx.env.clone(),
+                challenger.clone(),
+                create_paid_to(channel_id, payout_address)
+            )
+            .unwrap_err()
+        );
+    }
+    assert_eq!(
+        ContractError::UnknownChannel {},
+        execute(
+            ctx.deps.as_mut(),
+            ctx.env.clone(),
+            challenger.clone(),
+            create("channel-9")
+        )
+        .unwrap_err()
+    );
+    let res = execute(
+        ctx.deps.as_mut(),
+        ctx.env.clone(),
+        challenger.clone(),
+        create(channel_id),
+    )
+    .unwrap();
+    let match_id = res.events[0].attributes[3].value.clone();
+
+    // The opponent escrows their stake on their chain, then the host starts the match
+    let join = |match_id: &str| ExecuteMsg::JoinIbcMatch {
+        channel_id: channel_id.to_string(),
+        match_id: match_id.to_string(),
+        payout_address: "host_payout".to_string(),
+    };
+    // Someone else escrowing dust for the match doesn't keep the opponent out
+    let squatter = mock_info("squatter", &[Coin::new(1, "uatom")]);
+    let res = execute(
+        peer.deps.as_mut(),
+        peer.env.clone(),
+        squatter.clone(),
+        join(&match_id),
+    )
+    .unwrap();
+    let packet = sent_packet(&res.messages);
+    let (ack, _) = receive_packet(&mut ctx, channel_id, &packet);
+    assert_eq!(StdAck::error("Invalid opponent").to_binary(), ack);
+    let msg = mock_ibc_packet_ack(channel_id, &packet, IbcAcknowledgement::new(ack)).unwrap();
+    let res = ibc_packet_ack(peer.deps.as_mut(), peer.env.clone(), msg).unwrap();
+    assert_eq!(
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: squatter.sender.to_string(),
+            amount: squatter.funds,
+        })],
+        res.messages
+    );
+
+    let joiner = mock_info(remote.sender.as_str(), std::slice::from_ref(&stake));
+    let res = execute(
+        peer.deps.as_mut(),
+        peer.env.clone(),
+        joiner.clone(),
+        join(&match_id),
+    )
+    .unwrap();
+    let packet = sent_packet(&res.messages);
+    let (ack, _) = receive_packet(&mut ctx, channel_id, &packet);
+    assert_eq!(StdAck::success(b"\x01").to_binary(), ack);
+    let msg = mock_ibc_packet_ack(channel_id, &packet, IbcAcknowledgement::new(ack)).unwrap();
+    let _res = ibc_packet_ack(peer.deps.as_mut(), peer.env.clone(), msg).unwrap();
+    assert_eq!(
+        ContractError::NotAwaitingOpponent {},
+        execute(
+            peer.deps.as_mut(),
+            peer.env.clone(),
+            joiner.clone(),
+            join(&match_id)
+        )
+        .unwrap_err()
+    );
+    let opponent: Option<IbcOpponent> = from_json(
+        query(
+            ctx.deps.as_ref(),
+            ctx.env.clone(),
+            QueryMsg::IbcOpponent {
+                match_id: match_id.clone(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        Some(Addr::unchecked("host_payout")),
+        opponent.unwrap().payout_address
+    );
+    // The opponent can't bet on the match through their payout address
+    let side_bet_msg = ExecuteMsg::PlaceSideBet {
+        match_id: match_id.clone(),
+        outcome: MatchResult::BlackWins,
+    };
+    let info = mock_info("host_payout", &[ctx.bet.clone()]);
+    assert_eq!(
+        ContractError::PlayersCannotSideBet {},
+        execute(ctx.deps.as_mut(), ctx.env.clone(), info, side_bet_msg).unwrap_err()
+    );
+
+    // Moves of the opponent are relayed through the peer contract
+    let relay = |peer: &mut TestContext, host: &mut TestContext, move_fen: &str| {
+        let msg = ExecuteMsg::RelayMove {
+            channel_id: channel_id.to_string(),
+            match_id: match_id.clone(),
+            move_fen: move_fen.to_string(),
+        };
+        let res = execute(peer.deps.as_mut(), peer.env.clone(), remote.clone(), msg).unwrap();
+        let packet = sent_packet(&res.messages);
+        let (ack, res) = receive_packet(host, channel_id, &packet);
+        let msg =
+            mock_ibc_packet_ack(channel_id, &packet, IbcAcknowledgement::new(ack.clone())).unwrap();
+        let outcome = ibc_packet_ack(peer.deps.as_mut(), peer.env.clone(), msg).unwrap();
+        (ack, res, outcome)
+    };
+    let make_move = |ctx: &mut TestContext, move_fen: &str| {
+        let msg = ExecuteMsg::MakeMove {
+            match_id: match_id.clone(),
+            move_fen: move_fen.to_string(),
+        };
+        let info = ctx.player_a_no_bet();
+        execute(ctx.deps.as_mut(), ctx.env.clone(), info, msg).unwrap()
+    };
+    assert_eq!(
+        ContractError::Unauthorized {},
+        execute(
+            peer.deps.as_mut(),
+            peer.env.clone(),
+            mock_info("someone_else", &[]),
+            ExecuteMsg::RelayMove {
+                channel_id: channel_id.to_string(),
+                match_id: match_id.clone(),
+                move_fen: "e7e5".to_string(),
+            },
+        )
+        .unwrap_err()
+    );
+    let _res = make_move(&mut ctx, FOOLS_MATE[0]);
+    let (ack, _, outcome) = relay(&mut peer, &mut ctx, "e7e4");
+    assert_eq!(StdAck::error("Illegal move").to_binary(), ack);
+    assert_eq!("ibc_move_failed", outcome.events[0].ty);
+    let _res = relay(&mut peer, &mut ctx, FOOLS_MATE[1]);
+    let _res = make_move(&mut ctx, FOOLS_MATE[2]);
+
+    // The opponent mates: the challenger's stake is paid out on the host
+    // chain, the opponent's released on theirs
+    let (_, res, _) = relay(&mut peer, &mut ctx, FOOLS_MATE[3]);
+    let settlement = ChessPacket::SettleMatch {
+        match_id: match_id.clone(),
+        player: remote.sender.to_string(),
+        pay_to: remote.sender.to_string(),
+    };
+    let settlement_msg = SubMsg::new(IbcMsg::SendPacket {
+        channel_id: channel_id.to_string(),
+        data: to_json_binary(&settlement).unwrap(),
+        timeout: ibc_timeout(&ctx.env),
+    });
+    assert_eq!(
+        vec![
+            SubMsg::new(BankMsg::Send {
+                to_address: "host_payout".to_string(),
+                amount: vec![ctx.bet.clone()],
+            }),
+            settlement_msg.clone(),
+        ],
+        res.messages
+    );
+    let msg = mock_ibc_packet_timeout(channel_id, &settlement).unwrap();
+    let res = ibc_packet_timeout(ctx.deps.as_mut(), ctx.env.clone(), msg).unwrap();
+    assert_eq!(vec![settlement_msg.clone()], res.messages);
+    // The settlement is sent again while the peer migrates, other failures
+    // are only reported
+    let settlement_ack = |ctx: &mut TestContext, error: &str| {
+        let ack = IbcAcknowledgement::new(StdAck::error(error));
+        let msg = mock_ibc_packet_ack(channel_id, &settlement, ack).unwrap();
+        ibc_packet_ack(ctx.deps.as_mut(), ctx.env.clone(), msg).unwrap()
+    };
+    let migrating = ContractError::MigrationInProgress {}.to_string();
+    let res = settlement_ack(&mut ctx, &migrating);
+    assert_eq!(vec![settlement_msg], res.messages);
+    assert_eq!("ibc_settlement_resent", res.events[0].ty);
+    let res = settlement_ack(&mut ctx, "Unknown escrow");
+    assert!(res.messages.is_empty());
+    assert_eq!("ibc_settlement_failed", res.events[0].ty);
+    let (_, res) = receive_packet(&mut peer, channel_id, &settlement);
+    assert_eq!(
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: remote.sender.to_string(),
+            amount: vec![stake.clone()],
+        })],
+        res.messages
+    );
+    assert!(IBC_OPPONENTS
+        .may_load(
+            &ctx.deps.storage,
+            hex::decode(&match_id).unwrap().try_into().unwrap()
+        )
+        .unwrap()
+        .is_none());
+    // Neither is a cross-chain match kept for a rematch
+    let offer_msg = ExecuteMsg::OfferRematch {
+        match_id: match_id.clone(),
+    };
+    let info = mock_info(challenger.sender.as_str(), &[ctx.bet.clone()]);
+    assert_eq!(
+        ContractError::UnknownMatch {},
+        execute(ctx.deps.as_mut(), ctx.env.clone(), info, offer_msg).unwrap_err()
+    );
+
+    // Stakes the host turns down, or never hears of, are refunded
+    let res = execute(
+        ctx.deps.as_mut(),
+        ctx.env.clone(),
+        challenger,
+        create(channel_id),
+    )
+    .unwrap();
+    let match_id = res.events[0].attributes[3].value.clone();
+    let refund = |peer: &mut TestContext, stake: Coin, failure: Option<Binary>| {
+        let joiner = mock_info(remote.sender.as_str(), std::slice::from_ref(&stake));
+        let res = execute(
+            peer.deps.as_mut(),
+            peer.env.clone(),
+            joiner,
+            join(&match_id),
+        )
+        .unwrap();
+        let packet = sent_packet(&res.messages);
+        let res = match failure {
+            Some(ack) => {
+                let ack = IbcAcknowledgement::new(ack);
+                let msg = mock_ibc_packet_ack(channel_id, &packet, ack).unwrap();
+                ibc_packet_ack(peer.deps.as_mut(), peer.env.clone(), msg).unwrap()
+            }
+            None => {
+                let msg = mock_ibc_packet_timeout(channel_id, &packet).unwrap();
+                ibc_packet_timeout(peer.deps.as_mut(), peer.env.clone(), msg).unwrap()
+            }
+        };
+        assert_eq!(
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: remote.sender.to_string(),
+                amount: vec![stake],
+            })],
+            res.messages
+        );
+        let escrow: Option<IbcEscrow> = from_json(
+            query(
+                peer.deps.as_ref(),
+                peer.env.clone(),
+                QueryMsg::IbcEscrow {
+                    channel_id: channel_id.to_string(),
+                    match_id: match_id.clone(),
+                    player: remote.sender.to_string(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(None, escrow);
+    };
+    let packet = ChessPacket::JoinMatch {
+        match_id: match_id.clone(),
+        player: remote.sender.to_string(),
+        stake: Coin::new(5, "uatom"),
+        payout_address: "host_payout".to_string(),
+    };
+    let (ack, _) = receive_packet(&mut ctx, channel_id, &packet);
+    assert_eq!(StdAck::error("Invalid bet").to_binary(), ack);
+    refund(&mut peer, Coin::new(5, "uatom"), Some(ack));
+    refund(&mut peer, stake.clone(), None);
+
+    // A payee the peer can't pay gets the stake refunded rather than stuck
+    let joiner = mock_info(remote.sender.as_str(), std::slice::from_ref(&stake));
+    let res = execute(
+        peer.deps.as_mut(),
+        peer.env.clone(),
+        joiner,
+        join(&match_id),
+    )
+    .unwrap();
+    let packet = sent_packet(&res.messages);
+    let ack = IbcAcknowledgement::new(StdAck::success(b"\x01"));
+    let msg = mock_ibc_packet_ack(channel_id, &packet, ack).unwrap();
+    let _res = ibc_packet_ack(peer.deps.as_mut(), peer.env.clone(), msg).unwrap();
+    let settlement = ChessPacket::SettleMatch {
+        match_id: match_id.clone(),
+        player: remote.sender.to_string(),
+        pay_to: "X".to_string(),
+    };
+    let (ack, res) = receive_packet(&mut peer, channel_id, &settlement);
+    assert_eq!(StdAck::success(b"\x01").to_binary(), ack);
+    assert_eq!(
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: remote.sender.to_string(),
+            amount: vec![stake],
+        })],
+        res.messages
+    );
+
+    // Packets are only handled when the contract dispatches them to itself
+    let msg = ExecuteMsg::ReceiveIbcPacket {
+        channel_id: channel_id.to_string(),
+        data: to_json_binary(&packet).unwrap(),
+    };
+    let info = ctx.player_a_no_bet();
+    assert_eq!(
+        ContractError::Unauthorized {},
+        execute(ctx.deps.as_mut(), ctx.env.clone(), info, msg).unwrap_err()
+    );
+}
+
+const FOOLS_MATE: &[&str] = &["f2f3", "e7e5", "g2g4", "d8h4"];
+const SCHOLARS_MATE: &[&str] = &["e2e4", "e7e5", "f1c4", "b8c6", "d1h5", "g8f6", "h5f7"];
+const STALEMATE: &[&str] = &[
+    "c2c4", "h7h5", "h2h4", "a7a5", "d1a4", "a8a6", "a4a5", "a6h6", "a5c7", "f7f6", "c7d7", "e8f7",
+    "d7b7", "d8d3", "b7b8", "d3h7", "b8c8", "f7g6", "c8e6",
+];
+
+/// Instantiates the contract if needed and starts a match between players A
+/// and B, returning its id.
+fn start_match(ctx: &mut TestContext, rated: bool) -> [u8; 32] {
+    if ADMIN.may_load(ctx.deps.as_ref().storage).unwrap().is_none() {
+        let admin_info = ctx.admin_info();
+        let init_msg = InstantiateMsg {
+            min_bet: Coin::new(10, NATIVE_DENOM),
+        };
+        let _res = instantiate(ctx.deps.as_mut(), ctx.env.clone(), admin_info, init_msg).unwrap();
+    }
+
+    let nonce = NEXT_NONCE.load(ctx.deps.as_ref().storage).unwrap();
+    let create_msg = ExecuteMsg::CreateMatch {
+        opponent: ctx.player_b_addr.to_string(),
+        rated: Some(rated),
+        arbiter: None,
+    };
+    let info = ctx.player_a_info_with_bet();
+    let _res = execute(ctx.deps.as_mut(), ctx.env.clone(), info, create_msg).unwrap();
+    let match_id = exec::match_id(&ctx.player_a_addr, &ctx.player_b_addr, nonce);
+
+    let join_msg = ExecuteMsg::JoinMatch {
+        match_id: hex::encode(match_id),
+        expected_arbiter: None,
+    };
+    let info = ctx.player_b_info_with_bet();
+    let _res = execute(ctx.deps.as_mut(), ctx.env.clone(), info, join_msg).unwrap();
+    match_id
+}
+
+// Packet of the IBC message among `messages`
+fn sent_packet(messages: &[SubMsg]) -> ChessPacket {
+    messages
+        .iter()
+        .find_map(|msg| match &msg.msg {
+            CosmosMsg::Ibc(IbcMsg::SendPacket { data, .. }) => Some(from_json(data).unwrap()),
+            _ => None,
+        })
+        .unwrap()
+}
+
+/// Delivers a packet as the chain would: runs the handler the contract sends
+/// itself and, if it fails, the reply whose data replaces the acknowledgement.
+/// Returns the acknowledgement and the handler's response, or the reply's.
+fn receive_packet(
+    ctx: &mut TestContext,
+    channel_id: &str,
+    packet: &ChessPacket,
+) -> (Binary, Response) {
+    let msg = mock_ibc_packet_recv(channel_id, packet).unwrap();
+    let res = ibc_packet_receive(ctx.deps.as_mut(), ctx.env.clone(), msg).unwrap();
+    let [SubMsg {
+        id,
+        msg: CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr, msg, ..
+        }),
+        reply_on: ReplyOn::Error,
+        ..
+    }] = res.messages.as_slice()
+    else {
+        panic!("packet handler not dispatched");
+    };
+    assert_eq!(ctx.env.contract.address.as_str(), contract_addr);
+    let info = mock_info(contract_addr, &[]);
+    match execute(
+        ctx.deps.as_mut(),
+        ctx.env.clone(),
+        info,
+        from_json(msg).unwrap(),
+    ) {
+        Ok(handled) => (res.acknowledgement, handled),
+        Err(err) => {
+            let failure = Reply {
+                id: *id,
+                result: SubMsgResult::Err(err.to_string()),
+            };
+            let res = reply(ctx.deps.as_mut(), ctx.env.clone(), failure).unwrap();
+            (res.data.clone().unwrap(), res)
+        }
+    }
+}
+
+fn query_bracket(ctx: &TestContext) -> BracketResponse {
+    from_json(
+        query(
+            ctx.deps.as_ref(),
+            ctx.env.clone(),
+            QueryMsg::Bracket { tournament_id: 0 },
+        )
+        .unwrap(),
+    )
+    .unwrap()
+}
+
+fn match_id_of(game: &TieGame) -> [u8; 32] {
+    hex::decode(&game.match_id).unwrap().try_into().unwrap()
+}
+
+fn play_line(
+    ctx: &mut TestContext,
+    match_id: [u8; 32],
+    white: &MessageInfo,
+    black: &MessageInfo,
+    moves: &[&str],
+) -> Response {
+    let mut res = Response::new();
+    for (ply, move_fen) in moves.iter().enumerate() {
+        let info = if ply % 2 == 0 { white } else { black };
+        res = play_move(ctx, info.clone(), match_id, move_fen).unwrap();
+    }
+    res
+}
+
 fn play_move(
     ctx: &mut TestContext,
     info: MessageInfo,
@@ -0,0 +1,626 @@
+use cosmwasm_std::{Addr, Coin, Decimal, Uint128};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+
+use crate::game::{MatchResult, MatchStyle};
+
+pub type TournamentId = u64;
+
+// Games to be played, as (white, black)
+pub type Pairings = Vec<(Addr, Addr)>;
+
+// Prize split is expressed in percent of the prize pool
+pub const PRIZE_SPLIT_TOTAL: u32 = 100;
+
+// Upper bound on the pairing search, to keep `PairNextRound` gas-bounded
+pub const MAX_PAIRING_STEPS: u32 = 10_000;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub enum TournamentKind {
+    RoundRobin,
+    Swiss { rounds: u32 },
+    Knockout { seeding: Seeding },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub enum Seeding {
+    // By the players' all-time ratings
+    Rating,
+    // Drawn from a secret the organizer commits to before registration and
+    // reveals in `StartTournament`, so block proposers can't steer the draw.
+    // The commitment is the hex SHA-256 hash of the secret.
+    Random { seed_commitment: String },
+}
+
+/// Commitment to the secret of a random knockout draw.
+pub fn seed_commitment(secret: &str) -> String {
+    hex::encode(Sha256::digest(secret.as_bytes()))
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub enum Color {
+    White,
+    Black,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub enum TournamentState {
+    Registration,
+    OnGoing,
+    Finished,
+    // Called off during registration, entry fees refunded
+    Cancelled,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct Tournament {
+    pub organizer: Addr,
+    pub kind: TournamentKind,
+    pub entry_fee: Coin,
+    pub max_players: u32,
+    pub time_control: MatchStyle,
+    pub prize_split: Vec<u32>,
+    pub players: Vec<Addr>,
+    pub state: TournamentState,
+    pub round: u32,
+    pub pending_games: u32,
+}
+
+impl Tournament {
+    pub fn new(
+        organizer: Addr,
+        kind: TournamentKind,
+        entry_fee: Coin,
+        max_players: u32,
+        time_control: MatchStyle,
+        prize_split: Vec<u32>,
+    ) -> Tournament {
+        Self {
+            organizer,
+            kind,
+            entry_fee,
+            max_players,
+            time_control,
+            prize_split,
+            players: vec![],
+            state: TournamentState::Registration,
+            round: 0,
+            pending_games: 0,
+        }
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.players.len() as u32 >= self.max_players
+    }
+
+    /// Whether the next Swiss round is ready to be paired.
+    pub fn needs_pairing(&self) -> bool {
+        match self.kind {
+            TournamentKind::Swiss { rounds } => self.pending_games == 0 && self.round < rounds,
+            TournamentKind::RoundRobin | TournamentKind::Knockout { .. } => false,
+        }
+    }
+
+    pub fn prize_pool(&self) -> Uint128 {
+        self.entry_fee
+            .amount
+            .checked_mul(Uint128::from(self.players.len() as u128))
+            .unwrap_or(Uint128::MAX)
+    }
+}
+
+/// Score of a single game, in half-points (win = 2, draw = 1, loss = 0).
+/// A game without opponent is a bye.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct GameRecord {
+    pub opponent: Option<Addr>,
+    pub color: Option<Color>,
+    pub score: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct Standing {
+    pub player: Addr,
+    // Total score, in half-points
+    pub points: u32,
+    pub games: Vec<GameRecord>,
+}
+
+impl Standing {
+    pub fn new(player: Addr) -> Standing {
+        Self {
+            player,
+            points: 0,
+            games: vec![],
+        }
+    }
+
+    pub fn record(&mut self, opponent: &Addr, color: Color, score: u32) {
+        self.points += score;
+        self.games.push(GameRecord {
+            opponent: Some(opponent.clone()),
+            color: Some(color),
+            score,
+        });
+    }
+
+    pub fn record_bye(&mut self) {
+        self.points += 2;
+        self.games.push(GameRecord {
+            opponent: None,
+            color: None,
+            score: 2,
+        });
+    }
+
+    pub fn has_played(&self, opponent: &Addr) -> bool {
+        self.games
+            .iter()
+            .any(|game| game.opponent.as_ref() == Some(opponent))
+    }
+
+    fn games_against(&self, opponent: &Addr) -> usize {
+        self.games
+            .iter()
+            .filter(|game| game.opponent.as_ref() == Some(opponent))
+            .count()
+    }
+
+    pub fn had_bye(&self) -> bool {
+        self.games.iter().any(|game| game.opponent.is_none())
+    }
+
+    /// Number of games played with white minus games played with black.
+    fn color_balance(&self) -> i32 {
+        self.games
+            .iter()
+            .map(|game| match game.color {
+                Some(Color::White) => 1,
+                Some(Color::Black) => -1,
+                None => 0,
+            })
+            .sum()
+    }
+
+    fn last_color(&self) -> Option<&Color> {
+        self.games.iter().rev().find_map(|game| game.color.as_ref())
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct RankedStanding {
+    pub player: Addr,
+    pub points: Decimal,
+    pub buchholz: Decimal,
+    pub sonneborn_berger: Decimal,
+    pub games: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub enum TieGameKind {
+    Regular,
+    // Replay of a drawn regular game with reversed colours
+    Reversed,
+    // Decider after two draws, a draw counts as a win for black
+    Armageddon,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct TieGame {
+    pub match_id: String,
+    pub kind: TieGameKind,
+    pub white: Addr,
+    pub black: Addr,
+    pub result: Option<MatchResult>,
+}
+
+/// A knockout pairing. The `top` player is the better seeded one and has
+/// white in the regular and Armageddon games.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct BracketTie {
+    pub round: u32,
+    pub slot: u32,
+    pub top: Option<Addr>,
+    pub bottom: Option<Addr>,
+    pub games: Vec<TieGame>,
+    pub winner: Option<Addr>,
+}
+
+impl BracketTie {
+    pub fn new(round: u32, slot: u32) -> BracketTie {
+        Self {
+            round,
+            slot,
+            top: None,
+            bottom: None,
+            games: vec![],
+            winner: None,
+        }
+    }
+
+    pub fn loser(&self) -> Option<&Addr> {
+        let winner = self.winner.as_ref()?;
+        [&self.top, &self.bottom]
+            .into_iter()
+            .flatten()
+            .find(|player| *player != winner)
+    }
+
+    /// Colours and kind of the next game of this tie, if it is still undecided.
+    pub fn next_game(&self) -> Option<(TieGameKind, Addr, Addr)> {
+        let (top, bottom) = (self.top.clone()?, self.bottom.clone()?);
+        if self.winner.is_some() {
+            return None;
+        }
+        match self.games.last().map(|game| &game.kind) {
+            None => Some((TieGameKind::Regular, top, bottom)),
+            Some(TieGameKind::Regular) => Some((TieGameKind::Reversed, bottom, top)),
+            Some(TieGameKind::Reversed) => Some((TieGameKind::Armageddon, top, bottom)),
+            Some(TieGameKind::Armageddon) => None,
+        }
+    }
+
+    /// Records the result of the current game and returns the winner of the
+    /// tie if it is decided.
+    pub fn record(&mut self, result: &MatchResult) -> Option<Addr> {
+        let game = self.games.last_mut()?;
+        game.result = Some(result.clone());
+        let winner = match (result, &game.kind) {
+            (MatchResult::WhiteWins, _) => Some(game.white.clone()),
+            (MatchResult::BlackWins, _) | (MatchResult::Draw, TieGameKind::Armageddon) => {
+                Some(game.black.clone())
+            }
+            (MatchResult::Draw, _) => None,
+        };
+        self.winner = winner.clone();
+        winner
+    }
+}
+
+/// Number of rounds of a knockout bracket, if the number of players is a power of two.
+pub fn bracket_rounds(players: usize) -> Option<u32> {
+    (players >= 2 && players.is_power_of_two()).then(|| players.trailing_zeros())
+}
+
+/// Seed numbers (0-based) in bracket order, so that the best seeds can only
+/// meet in the latest rounds, e.g. [0, 3, 1, 2] for 4 players.
+pub fn bracket_order(players: usize) -> Vec<usize> {
+    let mut order = vec![0];
+    while order.len() < players {
+        let size = order.len() * 2;
+        order = order
+            .into_iter()
+            .flat_map(|seed| [seed, size - 1 - seed])
+            .collect();
+    }
+    order
+}
+
+/// Deterministic Fisher-Yates shuffle driven by a seed hash.
+pub fn shuffle(players: &mut [Addr], seed: [u8; 32]) {
+    let mut hash = seed;
+    for i in (1..players.len()).rev() {
+        hash = Sha256::digest(hash).into();
+        let rand = u64::from_be_bytes(hash[..8].try_into().unwrap());
+        players.swap(i, (rand % (i as u64 + 1)) as usize);
+    }
+}
+
+/// Final knockout ranking, best first: the champion, the finalist, then the
+/// players eliminated in each earlier round, who share their rank.
+pub fn knockout_ranking(ties: &[BracketTie], rounds: u32) -> Vec<Vec<Addr>> {
+    let mut groups = vec![];
+    if let Some(champion) = ties
+        .iter()
+        .find(|tie| tie.round == rounds)
+        .and_then(|tie| tie.winner.clone())
+    {
+        groups.push(vec![champion]);
+    }
+    for round in (1..=rounds).rev() {
+        let losers: Vec<Addr> = ties
+            .iter()
+            .filter(|tie| tie.round == round)
+            .filter_map(|tie| tie.loser().cloned())
+            .collect();
+        if !losers.is_empty() {
+            groups.push(losers);
+        }
+    }
+    groups
+}
+
+/// Half-point scores for white and black, given the result of a game.
+pub fn game_scores(result: &MatchResult) -> (u32, u32) {
+    match result {
+        MatchResult::WhiteWins => (2, 0),
+        MatchResult::BlackWins => (0, 2),
+        MatchResult::Draw => (1, 1),
+    }
+}
+
+/// All round-robin pairings as (white, black), using the circle method
+/// so that colours are spread evenly across players.
+pub fn round_robin_pairings(players: &[Addr]) -> Pairings {
+    let mut slots: Vec<Option<&Addr>> = players.iter().map(Some).collect();
+    if slots.len() % 2 == 1 {
+        // Odd number of players, whoever meets the empty slot sits out that round
+        slots.push(None);
+    }
+
+    let n = slots.len();
+    let mut pairings = vec![];
+    for round in 0..n.saturating_sub(1) {
+        for i in 0..n / 2 {
+            if let (Some(a), Some(b)) = (slots[i], slots[n - 1 - i]) {
+                if (round + i) % 2 == 0 {
+                    pairings.push((a.clone(), b.clone()));
+                } else {
+                    pairings.push((b.clone(), a.clone()));
+                }
+            }
+        }
+        slots[1..].rotate_right(1);
+    }
+    pairings
+}
+
+/// Buchholz score, in half-points: the sum of the scores of all opponents met.
+pub fn buchholz(standing: &Standing, points: &HashMap<&Addr, u32>) -> u32 {
+    standing
+        .games
+        .iter()
+        .filter_map(|game| game.opponent.as_ref())
+        .map(|opponent| points.get(opponent).copied().unwrap_or_default())
+        .sum()
+}
+
+/// Sonneborn-Berger score, in quarter-points: the sum of the scores of
+/// defeated opponents plus half the scores of drawn opponents.
+pub fn sonneborn_berger(standing: &Standing, points: &HashMap<&Addr, u32>) -> u32 {
+    standing
+        .games
+        .iter()
+        .filter_map(|game| {
+            game.opponent
+                .as_ref()
+                .map(|opponent| (opponent, game.score))
+        })
+        .map(|(opponent, score)| score * points.get(opponent).copied().unwrap_or_default())
+        .sum()
+}
+
+/// Rank standings by score, then by the tie-breaks of the tournament kind
+/// (Buchholz first for Swiss, Sonneborn-Berger first for round robin),
+/// then registration order.
+pub fn rank_standings(
+    kind: &TournamentKind,
+    players: &[Addr],
+    standings: &[Standing],
+) -> Vec<RankedStanding> {
+    let points: HashMap<&Addr, u32> = standings.iter().map(|s| (&s.player, s.points)).collect();
+
+    let mut ranked: Vec<(&Standing, usize, u32, u32)> = standings
+        .iter()
+        .map(|s| {
+            (
+                s,
+                seed(players, &s.player),
+                buchholz(s, &points),
+                sonneborn_berger(s, &points),
+            )
+        })
+        .collect();
+    ranked.sort_by(|(a, seed_a, bh_a, sb_a), (b, seed_b, bh_b, sb_b)| {
+        let tie_breaks = match kind {
+            TournamentKind::Swiss { .. } => bh_b.cmp(bh_a).then(sb_b.cmp(sb_a)),
+            TournamentKind::RoundRobin | TournamentKind::Knockout { .. } => {
+                sb_b.cmp(sb_a).then(bh_b.cmp(bh_a))
+            }
+        };
+        b.points
+            .cmp(&a.points)
+            .then(tie_breaks)
+            .then(seed_a.cmp(seed_b))
+    });
+
+    ranked
+        .into_iter()
+        .map(|(s, _, bh, sb)| RankedStanding {
+            player: s.player.clone(),
+            points: Decimal::from_ratio(s.points, 2u32),
+            buchholz: Decimal::from_ratio(bh, 2u32),
+            sonneborn_berger: Decimal::from_ratio(sb, 4u32),
+            games: s.games.len() as u32,
+        })
+        .collect()
+}
+
+fn seed(players: &[Addr], player: &Addr) -> usize {
+    players
+        .iter()
+        .position(|p| p == player)
+        .unwrap_or(usize::MAX)
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct PairingLimitExceeded;
+
+/// Pairings for the next Swiss round, as (white, black), plus the player
+/// receiving a bye if there is an odd number of players.
+///
+/// Players are ordered by score then registration order. The lowest ranked
+/// player without a bye sits out, then each remaining player is paired,
+/// highest ranked first, against the top of the lower half of their score
+/// group, floating down to lower groups when needed. Pairings never repeat;
+/// `None` is returned if that is not possible. The search gives up after
+/// `MAX_PAIRING_STEPS`, see `greedy_swiss_pairings`.
+pub fn swiss_pairings(
+    players: &[Addr],
+    standings: &[Standing],
+    round: u32,
+) -> Result<Option<(Pairings, Option<Addr>)>, PairingLimitExceeded> {
+    let (ranked, bye) = rank_for_pairing(players, standings);
+    let mut steps = 0u32;
+    let Some(pairs) = pair_remaining(&ranked, &mut steps)? else {
+        return Ok(None);
+    };
+    let pairings = pairs
+        .into_iter()
+        .map(|(a, b)| assign_colors(a, b, round))
+        .collect();
+    Ok(Some((pairings, bye)))
+}
+
+/// Pairings for the next Swiss round when `swiss_pairings` gave up: each
+/// player, highest ranked first, takes the first candidate it would have
+/// tried that it met the fewest times. Players only get a rematch when they
+/// have met everyone left.
+pub fn greedy_swiss_pairings(
+    players: &[Addr],
+    standings: &[Standing],
+    round: u32,
+) -> (Pairings, Option<Addr>) {
+    let (mut remaining, bye) = rank_for_pairing(players, standings);
+    let mut pairings = vec![];
+    while let Some((top, rest)) = remaining.split_first() {
+        let Some(idx) =
+            candidates(top, rest).min_by_key(|idx| top.games_against(&rest[*idx].player))
+        else {
+            break;
+        };
+        pairings.push(assign_colors(top, rest[idx], round));
+        remaining = rest
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| *i != idx)
+            .map(|(_, s)| *s)
+            .collect();
+    }
+    (pairings, bye)
+}
+
+/// Players to pair, by score then registration order, and the one sitting
+/// out if there is an odd number of them.
+fn rank_for_pairing<'a>(
+    players: &[Addr],
+    standings: &'a [Standing],
+) -> (Vec<&'a Standing>, Option<Addr>) {
+    let mut ranked: Vec<&Standing> = standings.iter().collect();
+    ranked.sort_by(|a, b| {
+        b.points
+            .cmp(&a.points)
+            .then(seed(players, &a.player).cmp(&seed(players, &b.player)))
+    });
+
+    let mut bye = None;
+    if ranked.len() % 2 == 1 {
+        let idx = ranked
+            .iter()
+            .rposition(|s| !s.had_bye())
+            .unwrap_or(ranked.len() - 1);
+        bye = Some(ranked.remove(idx).player.clone());
+    }
+    (ranked, bye)
+}
+
+/// Candidate opponents of `top` in `rest`, by index. Candidates from the top
+/// player's score group come first, starting from the middle of the group,
+/// then lower groups in rank order.
+fn candidates(top: &Standing, rest: &[&Standing]) -> impl Iterator<Item = usize> {
+    let group_len = rest.iter().take_while(|s| s.points == top.points).count();
+    let half = group_len.div_ceil(2).saturating_sub(1);
+    (half..group_len)
+        .chain(0..half)
+        .chain(group_len..rest.len())
+}
+
+type Pairs<'a> = Vec<(&'a Standing, &'a Standing)>;
+
+fn pair_remaining<'a>(
+    remaining: &[&'a Standing],
+    steps: &mut u32,
+) -> Result<Option<Pairs<'a>>, PairingLimitExceeded> {
+    let (top, rest) = match remaining.split_first() {
+        Some(split) => split,
+        None => return Ok(Some(vec![])),
+    };
+
+    for idx in candidates(top, rest) {
+        *steps += 1;
+        if *steps > MAX_PAIRING_STEPS {
+            return Err(PairingLimitExceeded);
+        }
+
+        let opponent = rest[idx];
+        if top.has_played(&opponent.player) {
+            continue;
+        }
+        let others: Vec<&Standing> = rest
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| *i != idx)
+            .map(|(_, s)| *s)
+            .collect();
+        if let Some(mut pairs) = pair_remaining(&others, steps)? {
+            pairs.insert(0, (*top, opponent));
+            return Ok(Some(pairs));
+        }
+    }
+    Ok(None)
+}
+
+/// The player who has had white less often gets white, then the one who had
+/// black last; otherwise the higher ranked player alternates by round.
+fn assign_colors(higher: &Standing, lower: &Standing, round: u32) -> (Addr, Addr) {
+    let higher_gets_white = match higher.color_balance().cmp(&lower.color_balance()) {
+        std::cmp::Ordering::Less => true,
+        std::cmp::Ordering::Greater => false,
+        std::cmp::Ordering::Equal => match (higher.last_color(), lower.last_color()) {
+            (Some(Color::Black), Some(Color::White)) => true,
+            (Some(Color::White), Some(Color::Black)) => false,
+            _ => round % 2 == 1,
+        },
+    };
+
+    if higher_gets_white {
+        (higher.player.clone(), lower.player.clone())
+    } else {
+        (lower.player.clone(), higher.player.clone())
+    }
+}
+
+/// Prize amounts by final rank, for players grouped by shared rank. Players
+/// sharing a rank split the prizes of the ranks they cover. Rounding dust and
+/// the shares of ranks that nobody finished in go to the winner.
+pub fn prize_shares(
+    pool: Uint128,
+    prize_split: &[u32],
+    ranking: &[Vec<Addr>],
+) -> Vec<(Addr, Uint128)> {
+    let mut shares = vec![];
+    let mut rank = 0;
+    for group in ranking {
+        let group_prize: Uint128 = prize_split
+            .iter()
+            .skip(rank)
+            .take(group.len())
+            .map(|pct| pool.multiply_ratio(*pct, PRIZE_SPLIT_TOTAL))
+            .sum();
+        for player in group {
+            shares.push((
+                player.clone(),
+                group_prize.multiply_ratio(1u128, group.len() as u128),
+            ));
+        }
+        rank += group.len();
+    }
+
+    let distributed: Uint128 = shares.iter().map(|(_, amount)| amount).sum();
+    if let Some((_, first)) = shares.first_mut() {
+        *first += pool - distributed;
+    }
+    shares
+}
@@ -0,0 +1,62 @@
+use cosmwasm_std::Addr;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::game::Match;
+use crate::tournament::Color;
+
+// Bound on the members of a group, to keep tallying gas-bounded
+pub const MAX_VOTERS: usize = 100;
+
+/// Voters playing one side of a vote match, under the contract's address.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct VoteGroup {
+    pub voters: Vec<Addr>,
+    pub color: Color,
+    // Blocks the group has to vote on each of its moves
+    pub voting_period: u64,
+    // Votes on the group's current move
+    pub votes: Vec<(Addr, String)>,
+}
+
+impl VoteGroup {
+    pub fn new(voters: Vec<Addr>, color: Color, voting_period: u64) -> VoteGroup {
+        Self {
+            voters,
+            color,
+            voting_period,
+            votes: vec![],
+        }
+    }
+
+    /// Records `voter`'s vote, replacing any earlier one.
+    pub fn vote(&mut self, voter: &Addr, mov: String) {
+        match self.votes.iter_mut().find(|(addr, _)| addr == voter) {
+            Some((_, vote)) => *vote = mov,
+            None => self.votes.push((voter.clone(), mov)),
+        }
+    }
+
+    /// Votes per move, most voted first. Moves with as many votes come in
+    /// move order, so the first one wins ties.
+    pub fn tally(&self) -> Vec<(String, u32)> {
+        let mut counts: BTreeMap<&str, u32> = BTreeMap::new();
+        for (_, mov) in &self.votes {
+            *counts.entry(mov).or_default() += 1;
+        }
+        let mut tally: Vec<(String, u32)> = counts
+            .into_iter()
+            .map(|(mov, count)| (mov.to_string(), count))
+            .collect();
+        tally.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        tally
+    }
+
+    /// Block height voting on the group's current move closes at, counted
+    /// from the opponent's last move.
+    pub fn voting_ends(&self, chess_match: &Match) -> u64 {
+        // `last_move` is behind the start until a move is played
+        chess_match.last_move.max(chess_match.start) + self.voting_period
+    }
+}